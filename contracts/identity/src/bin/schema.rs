@@ -1,10 +1,11 @@
 use cosmwasm_schema::write_api;
-use identity::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use identity::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
 
 fn main() {
     write_api! {
         instantiate: InstantiateMsg,
         execute: ExecuteMsg,
         query: QueryMsg,
+        migrate: MigrateMsg,
     }
 }
@@ -0,0 +1,174 @@
+// Gregorian calendar arithmetic on unix timestamps, without std time
+use core::fmt;
+use core::str::FromStr;
+
+pub const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+// Real-world offsets run from UTC-12:00 to UTC+14:00
+pub const MAX_UTC_OFFSET_MINUTES: i32 = 14 * 60;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct YearMonth {
+    pub year: i64,
+    pub month: u32, // 1..=12
+}
+
+impl YearMonth {
+    // Calendar month holding `timestamp` once shifted by `utc_offset_minutes`
+    pub fn of(timestamp: u64, utc_offset_minutes: i32) -> Self {
+        let local = timestamp as i64 + i64::from(utc_offset_minutes) * 60;
+        let (year, month, _) = civil_from_days(local.div_euclid(SECONDS_PER_DAY));
+        YearMonth { year, month }
+    }
+
+    pub fn previous(self) -> Self {
+        if self.month == 1 {
+            YearMonth { year: self.year - 1, month: 12 }
+        } else {
+            YearMonth { year: self.year, month: self.month - 1 }
+        }
+    }
+
+    pub fn quarter(self) -> u32 {
+        (self.month - 1) / 3 + 1
+    }
+}
+
+// Formats as `YYYY-MM`
+impl fmt::Display for YearMonth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}", self.year, self.month)
+    }
+}
+
+// Parses the `YYYY-MM` form written by `Display`
+impl FromStr for YearMonth {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (year, month) = s.split_once('-').ok_or(())?;
+        let year = year.parse().map_err(|_| ())?;
+        let month = month.parse().map_err(|_| ())?;
+        if !(1..=12).contains(&month) {
+            return Err(());
+        }
+        Ok(YearMonth { year, month })
+    }
+}
+
+// (year, month, day) for a count of days since 1970-01-01, after
+// Howard Hinnant's `civil_from_days`
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097); // [0, 146096]
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365; // [0, 399]
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100); // [0, 365]
+    let mp = (5 * doy + 2) / 153; // [0, 11], March-based
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+// Days since 1970-01-01 for a calendar date; inverse of `civil_from_days`
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400); // [0, 399]
+    let mp = i64::from((month + 9) % 12); // [0, 11], March-based
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1; // [0, 365]
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy; // [0, 146096]
+    era * 146_097 + doe - 719_468
+}
+
+// `YYYY-MM-DD` for the UTC day holding `timestamp`
+pub fn format_date(timestamp: u64) -> String {
+    let (year, month, day) = civil_from_days(timestamp as i64 / SECONDS_PER_DAY);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+pub fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Moves `timestamp` by whole calendar months, keeping the time of day. The day
+// is clamped to the target month, so Jan 31 plus one month is the last of February
+pub fn add_months(timestamp: i64, months: i64) -> i64 {
+    let days = timestamp.div_euclid(SECONDS_PER_DAY);
+    let (year, month, day) = civil_from_days(days);
+    let index = year * 12 + i64::from(month) - 1 + months;
+    let (year, month) = (index.div_euclid(12), index.rem_euclid(12) as u32 + 1);
+    let day = day.min(days_in_month(year, month));
+    days_from_civil(year, month, day) * SECONDS_PER_DAY + timestamp.rem_euclid(SECONDS_PER_DAY)
+}
+
+// Unix seconds, or an ISO-8601 date (`2024-02-29`) or date-time
+// (`2024-02-29T13:45:00Z`, `2024-02-29T13:45:00.5+02:00`). Times without an
+// offset are taken as UTC and fractions of a second are dropped
+pub fn parse_timestamp(value: &str) -> Option<i64> {
+    if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) {
+        return value.parse().ok();
+    }
+
+    let (date, time) = match value.split_once('T') {
+        Some((date, time)) => (date, Some(time)),
+        None => (value, None),
+    };
+    let mut parts = date.split('-');
+    let year = digits(parts.next()?, 4)?;
+    let month = digits(parts.next()?, 2)?;
+    let day = digits(parts.next()?, 2)?;
+    if parts.next().is_some() || !(1..=12).contains(&month) || day == 0 || day > days_in_month(year.into(), month) {
+        return None;
+    }
+    let mut timestamp = days_from_civil(year.into(), month, day) * SECONDS_PER_DAY;
+
+    if let Some(time) = time {
+        let (clock, offset) = match time.find(['Z', '+', '-']) {
+            Some(at) => time.split_at(at),
+            None => (time, ""),
+        };
+        let (clock, fraction) = match clock.split_once('.') {
+            Some((clock, fraction)) => (clock, Some(fraction)),
+            None => (clock, None),
+        };
+        if fraction.is_some_and(|f| f.is_empty() || !f.bytes().all(|b| b.is_ascii_digit())) {
+            return None;
+        }
+        let mut fields = clock.split(':');
+        let hour = digits(fields.next()?, 2)?;
+        let minute = digits(fields.next()?, 2)?;
+        let second = fields.next().map_or(Some(0), |s| digits(s, 2))?;
+        if fields.next().is_some() || hour > 23 || minute > 59 || second > 59 || (fraction.is_some() && clock.len() != 8) {
+            return None;
+        }
+        timestamp += i64::from(hour * 3600 + minute * 60 + second);
+
+        if !offset.is_empty() && offset != "Z" {
+            let (sign, zone) = offset.split_at(1);
+            let (hours, minutes) = zone.split_once(':')?;
+            let (hours, minutes) = (digits(hours, 2)?, digits(minutes, 2)?);
+            if hours > 14 || minutes > 59 {
+                return None;
+            }
+            let shift = i64::from(hours * 3600 + minutes * 60);
+            // Local time is ahead of UTC by a positive offset
+            timestamp += if sign == "+" { -shift } else { shift };
+        }
+    }
+    Some(timestamp)
+}
+
+// Exactly `len` ASCII digits
+fn digits(s: &str, len: usize) -> Option<u32> {
+    if s.len() == len && s.bytes().all(|b| b.is_ascii_digit()) {
+        s.parse().ok()
+    } else {
+        None
+    }
+}
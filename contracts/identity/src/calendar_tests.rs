@@ -0,0 +1,69 @@
+mod test {
+
+    use crate::calendar::{add_months, civil_from_days, parse_timestamp, YearMonth};
+
+    #[test]
+    fn test_civil_from_days_handles_leap_years() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+        assert_eq!(civil_from_days(19_783), (2024, 3, 1));
+        // 2100 is not a leap year
+        assert_eq!(civil_from_days(47_541), (2100, 3, 1));
+        assert_eq!(civil_from_days(47_540), (2100, 2, 28));
+    }
+
+    #[test]
+    fn test_year_month_applies_offset_and_formats() {
+        // 2024-03-01T02:00:00Z
+        let timestamp = 1_709_258_400;
+        assert_eq!(YearMonth::of(timestamp, 0), YearMonth { year: 2024, month: 3 });
+        assert_eq!(YearMonth::of(timestamp, -180), YearMonth { year: 2024, month: 2 });
+        assert_eq!(YearMonth::of(0, -60).to_string(), "1969-12");
+
+        let january = YearMonth { year: 2024, month: 1 };
+        assert_eq!(january.previous().to_string(), "2023-12");
+        assert_eq!(january.quarter(), 1);
+        assert_eq!(YearMonth { year: 2024, month: 10 }.quarter(), 4);
+    }
+
+    #[test]
+    fn test_parse_timestamp_accepts_unix_seconds_and_iso_8601() {
+        assert_eq!(parse_timestamp("1709258400"), Some(1_709_258_400));
+        assert_eq!(parse_timestamp("2024-03-01"), Some(1_709_251_200));
+        assert_eq!(parse_timestamp("2024-03-01T02:00"), Some(1_709_258_400));
+        assert_eq!(parse_timestamp("2024-03-01T02:00:00Z"), Some(1_709_258_400));
+        assert_eq!(parse_timestamp("2024-03-01T02:00:00.750Z"), Some(1_709_258_400));
+        assert_eq!(parse_timestamp("2024-03-01T04:30:00+02:30"), Some(1_709_258_400));
+        assert_eq!(parse_timestamp("2024-02-29T21:00:00-05:00"), Some(1_709_258_400));
+        assert_eq!(parse_timestamp("1960-06-15"), Some(-301_276_800));
+
+        for invalid in [
+            "",
+            "-5",
+            "2023-02-29",
+            "2024-13-01",
+            "2024-3-01",
+            "2024-03-01T24:00",
+            "2024-03-01T02:00:60",
+            "2024-03-01T02:00.5",
+            "2024-03-01T02:00:00.",
+            "2024-03-01T02:00:00+2:00",
+            "2024-03-01 02:00",
+            "01/03/2024",
+        ] {
+            assert_eq!(parse_timestamp(invalid), None, "parsed '{}'", invalid);
+        }
+    }
+
+    #[test]
+    fn test_add_months_clamps_to_month_end() {
+        // 2024-01-31T12:00:00Z
+        let timestamp = 1_706_702_400;
+        assert_eq!(add_months(timestamp, 1), parse_timestamp("2024-02-29T12:00:00Z").unwrap());
+        assert_eq!(add_months(timestamp, 13), parse_timestamp("2025-02-28T12:00:00Z").unwrap());
+        assert_eq!(add_months(timestamp, -2), parse_timestamp("2023-11-30T12:00:00Z").unwrap());
+        assert_eq!(add_months(timestamp, -18 * 12), parse_timestamp("2006-01-31T12:00:00Z").unwrap());
+    }
+}
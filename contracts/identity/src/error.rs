@@ -1,6 +1,8 @@
 use cosmwasm_std::StdError;
 use thiserror::Error;
 
+use crate::models::{FieldError, LoanStatus};
+
 #[derive(Error, Debug, PartialEq)]
 pub enum ContractError {
     #[error("{0}")]
@@ -24,21 +26,147 @@ pub enum ContractError {
     #[error("Field '{field_name}' should be a number")]
     InvalidNumberField { field_name: String },
 
+    #[error("Field '{field_name}' is out of range")]
+    FieldOutOfRange { field_name: String },
+
+    #[error("Field '{field_name}' allows at most {precision} decimal places")]
+    TooManyDecimalPlaces { field_name: String, precision: u32 },
+
+    #[error("Bound '{bound}' of field '{field_name}' is not a valid value for the field")]
+    InvalidFieldBound { field_name: String, bound: String },
+
+    #[error("Minimum of field '{field_name}' is above its maximum")]
+    InvalidFieldRange { field_name: String },
+
+    #[error("Format '{format}' of field '{field_name}' is not a valid regular expression")]
+    InvalidFieldFormat { field_name: String, format: String },
+
+    #[error("Field '{field_name}' must be one of its options")]
+    NotAnOption { field_name: String },
+
+    #[error("Field '{field_name}' is not a valid address")]
+    InvalidAddress { field_name: String },
+
+    #[error("Field '{field_name}' should be an amount followed by a denom")]
+    InvalidCoin { field_name: String },
+
+    #[error("Denom '{denom}' is not allowed for field '{field_name}'")]
+    DenomNotAllowed { field_name: String, denom: String },
+
+    #[error("Field '{field_name}' needs at least one option")]
+    EmptyEnumOptions { field_name: String },
+
+    #[error("Option '{option}' of field '{field_name}' is listed more than once")]
+    DuplicateEnumOption { field_name: String, option: String },
+
+    #[error("Denom '{denom}' of field '{field_name}' is not a valid denom")]
+    InvalidDenom { field_name: String, denom: String },
+
+    #[error("Bound of {bps} basis points on field '{field_name}' is above 10000")]
+    InvalidBasisPoints { field_name: String, bps: u32 },
+
+    #[error("Field '{field_name}' should be {expected}")]
+    WrongValueType { field_name: String, expected: String },
+
+    #[error("Field '{field_name}' has too few or too many items")]
+    ItemCountOutOfRange { field_name: String },
+
+    #[error("Field '{field_name}' is nested too deeply")]
+    NestingTooDeep { field_name: String },
+
+    #[error("Rule '{rule_id}' is invalid: {reason}")]
+    InvalidRule { rule_id: String, reason: String },
+
+    #[error("Stored rule '{rule_id}' cannot be evaluated: {reason}")]
+    StoredRuleInvalid { rule_id: String, reason: String },
+
+    #[error("Rule ID '{rule_id}' is used more than once")]
+    DuplicateRuleId { rule_id: String },
+
+    #[error("Templates can have at most {max} rules")]
+    TooManyRules { max: u32 },
+
+    #[error("Rule '{rule_id}' failed: {message}")]
+    RuleViolated { rule_id: String, message: String },
+
+    #[error("Field names cannot be empty")]
+    EmptyFieldName {},
+
+    #[error("Precision {precision} of field '{field_name}' is above the 18 decimal places supported")]
+    InvalidPrecision { field_name: String, precision: u32 },
+
     #[error("Field '{field_name}' does not match format")]
     InvalidFormat { field_name: String },
 
+    #[error("Loan values failed validation: {}", errors.iter().map(|e| e.message.as_str()).collect::<Vec<_>>().join("; "))]
+    ValidationFailed { errors: Vec<FieldError> },
+
+    #[error("Field '{field_name}' is not defined by the template")]
+    UnknownField { field_name: String },
+
     #[error("Field '{field_name}' is missing")]
     MissingField { field_name: String },
 
+    #[error("Field '{field_name}' cannot be changed once the loan is created")]
+    FieldNotEditable { field_name: String },
+
+    #[error("Loan '{loan_id}' cannot be amended once it is {status}")]
+    LoanNotAmendable { loan_id: String, status: LoanStatus },
+
+    #[error("The amendment does not change any value")]
+    NothingToAmend {},
+
     #[error("Template not found: {template_id}")]
     TemplateNotFound { template_id: String },
 
     #[error("Template '{template_id}' has already been reviewed")]
     TemplateAlreadyReviewed { template_id: String },
 
+    #[error("Template '{template_id}' is under review and cannot be edited")]
+    TemplateUnderReview { template_id: String },
+
     #[error("Template '{template_id}' already exists for this user")]
     TemplateAlreadyExists { template_id: String },
 
     #[error("Invalid loan request, Fields does not match with the fields in template")]
-    InvalidLoanRequest{}
+    InvalidLoanRequest{},
+
+    #[error("Loan cannot move from {from} to {to}")]
+    InvalidTransition { from: LoanStatus, to: LoanStatus },
+
+    #[error("Loan '{loan_id}' is not assigned to this reviewer")]
+    NotAssignedReviewer { loan_id: String },
+
+    #[error("Reviewers cannot decide on loans they submitted")]
+    SelfReviewNotAllowed {},
+
+    #[error("Loan '{loan_id}' is already assigned to {reviewer}")]
+    LoanAlreadyAssigned { loan_id: String, reviewer: String },
+
+    #[error("Loan '{loan_id}' cannot change reviewer once it is {status}")]
+    AssignmentClosed { loan_id: String, status: LoanStatus },
+
+    #[error("Address '{address}' does not hold the {role} role")]
+    MissingRole { address: String, role: String },
+
+    #[error("The contract admin cannot lose the Admin role")]
+    CannotRevokeContractAdmin {},
+
+    #[error("No admin transfer is pending")]
+    NoPendingAdminTransfer {},
+
+    #[error("Only the proposed admin can accept the transfer")]
+    NotPendingAdmin {},
+
+    #[error("The admin transfer has expired")]
+    AdminTransferExpired {},
+
+    #[error("Cannot migrate from contract '{found}', expected '{expected}'")]
+    InvalidContractName { expected: String, found: String },
+
+    #[error("Cannot migrate from version {stored} down to {current}")]
+    CannotDowngrade { stored: String, current: String },
+
+    #[error("An admin address is required to migrate this deployment")]
+    MigrationAdminRequired {},
 }
@@ -17,52 +17,224 @@ pub fn execute(
             user_id,
             loan_requests,
         } => exec::create_loan(deps, env, info, user_id, loan_requests),
-        ExecuteMsg::AssignLoansToReviewer { reviewer, loans } => {
-            exec::assign_loans_to_reviewer(deps, info, reviewer, loans)
+        ExecuteMsg::AssignLoans { reviewer, loans } => exec::assign_loans(deps, info, reviewer, loans),
+        ExecuteMsg::UnassignLoans { reviewer, loans } => {
+            exec::unassign_loans(deps, info, reviewer, loans)
         }
+        ExecuteMsg::ReassignLoan {
+            user_id,
+            loan_id,
+            from,
+            to,
+        } => exec::reassign_loan(deps, info, user_id, loan_id, from, to),
         ExecuteMsg::UpdateLoanReviewStatus {
             user_id,
             loan_id,
             new_status,
-        } => exec::update_loan_review_status(deps, env, user_id, loan_id, new_status),
+            reason,
+        } => exec::update_loan_review_status(deps, env, info, user_id, loan_id, new_status, reason),
+        ExecuteMsg::AmendLoan { loan_id, changes } => exec::amend_loan(deps, env, info, loan_id, changes),
         ExecuteMsg::CreateLoanTemplate {
             template_id,
             name,
             fields,
-        } => exec::create_loan_template(deps, env, info, template_id, name, fields),
+            rules,
+        } => exec::create_loan_template(deps, env, info, template_id, name, fields, rules),
+        ExecuteMsg::UpdateLoanTemplate {
+            template_id,
+            name,
+            fields,
+            rules,
+        } => exec::update_loan_template(deps, env, info, template_id, name, fields, rules),
         ExecuteMsg::SubmitTemplateForReview {
             template_id,
             reviewer,
         } => exec::submit_template_for_review(deps, env, info, template_id, reviewer),
         ExecuteMsg::ReviewTemplate {
+            user_id,
             template_id,
             approve,
-        } => exec::review_template(deps, env, info, template_id, approve),
+            comment,
+        } => exec::review_template(deps, env, info, user_id, template_id, approve, comment),
+        ExecuteMsg::GrantRole { role, address } => exec::grant_role(deps, info, role, address),
+        ExecuteMsg::RevokeRole { role, address } => exec::revoke_role(deps, info, role, address),
+        ExecuteMsg::ProposeAdminTransfer { new_admin, expiry } => {
+            exec::propose_admin_transfer(deps, env, info, new_admin, expiry)
+        }
+        ExecuteMsg::AcceptAdminTransfer {} => exec::accept_admin_transfer(deps, env, info),
+        ExecuteMsg::CancelAdminTransfer {} => exec::cancel_admin_transfer(deps, info),
+        ExecuteMsg::RenounceAdmin {} => exec::renounce_admin(deps, info),
+        ExecuteMsg::RecomputeStatistics {} => exec::recompute_statistics(deps, info),
     }
 }
 
+#[allow(clippy::module_inception)]
 mod exec {
     use std::collections::HashMap;
 
-    use cosmwasm_std::{DepsMut, Env, MessageInfo, Response, StdError, StdResult};
-    use uuid::Uuid;
+    use cosmwasm_std::{Addr, DepsMut, Env, MessageInfo, Order, Response, StdError, StdResult, Storage};
+    use cw_utils::Expiration;
 
     use crate::{
         error::ContractError,
-        models::{FieldType, LoanData, LoanRequest, LoanTemplate, ReviewStatus, ReviewTuple},
-        states::{LOAN_STORAGE, REVIEWER_ASSIGNMENTS, TEMPLATE_REVIEWERS, USER_TEMPLATES},
+        models::{AdminTransfer, Config, FieldChange, FieldType, FieldValue, LoanData, LoanRequest, LoanRevision, LoanStatus, LoanTemplate, ReviewStatus, ReviewTuple, Role, StatusTransition, TemplateReview, ValidationRule},
+        roles, stats, templates, validation,
+        states::{loan_key, ADMIN_TRANSFER, CONFIG, LOAN_REVISIONS, LOAN_STORAGE, NEXT_LOAN_ID, TEMPLATE_REVIEWERS, TEMPLATE_VERSIONS, USER_TEMPLATES},
     };
 
+    pub fn grant_role(
+        deps: DepsMut,
+        info: MessageInfo,
+        role: Role,
+        address: String,
+    ) -> Result<Response, ContractError> {
+        roles::ensure_role(deps.storage, &info.sender, &[Role::Admin])?;
+
+        let addr = deps.api.addr_validate(&address)?;
+        roles::grant(deps.storage, &addr, role)?;
+
+        Ok(Response::new()
+            .add_attribute("method", "grant_role")
+            .add_attribute("role", role.to_string())
+            .add_attribute("address", addr))
+    }
+
+    pub fn revoke_role(
+        deps: DepsMut,
+        info: MessageInfo,
+        role: Role,
+        address: String,
+    ) -> Result<Response, ContractError> {
+        roles::ensure_role(deps.storage, &info.sender, &[Role::Admin])?;
+
+        let addr = deps.api.addr_validate(&address)?;
+        // The configured admin must never be locked out of the registry
+        if role == Role::Admin && CONFIG.load(deps.storage)?.admin.as_ref() == Some(&addr) {
+            return Err(ContractError::CannotRevokeContractAdmin {});
+        }
+        roles::revoke(deps.storage, &addr, role);
+
+        Ok(Response::new()
+            .add_attribute("method", "revoke_role")
+            .add_attribute("role", role.to_string())
+            .add_attribute("address", addr))
+    }
+
+    // Only the configured admin (not every Admin role holder) may rotate ownership
+    fn ensure_contract_admin(storage: &dyn Storage, info: &MessageInfo) -> Result<Config, ContractError> {
+        let config = CONFIG.load(storage)?;
+        if config.admin.as_ref() != Some(&info.sender) {
+            return Err(ContractError::Unauthorized {});
+        }
+        Ok(config)
+    }
+
+    pub fn propose_admin_transfer(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        new_admin: String,
+        expiry: Option<Expiration>,
+    ) -> Result<Response, ContractError> {
+        ensure_contract_admin(deps.storage, &info)?;
+
+        let new_admin = deps.api.addr_validate(&new_admin)?;
+        if expiry.is_some_and(|e| e.is_expired(&env.block)) {
+            return Err(ContractError::AdminTransferExpired {});
+        }
+
+        // A new proposal replaces any earlier one
+        ADMIN_TRANSFER.save(
+            deps.storage,
+            &AdminTransfer {
+                new_admin: new_admin.clone(),
+                expiry,
+            },
+        )?;
+
+        Ok(Response::new()
+            .add_attribute("method", "propose_admin_transfer")
+            .add_attribute("new_admin", new_admin))
+    }
+
+    pub fn accept_admin_transfer(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+    ) -> Result<Response, ContractError> {
+        let transfer = ADMIN_TRANSFER
+            .may_load(deps.storage)?
+            .ok_or(ContractError::NoPendingAdminTransfer {})?;
+        if transfer.new_admin != info.sender {
+            return Err(ContractError::NotPendingAdmin {});
+        }
+        if transfer.expiry.is_some_and(|e| e.is_expired(&env.block)) {
+            return Err(ContractError::AdminTransferExpired {});
+        }
+
+        // Move the Admin role along with the ownership
+        let mut config = CONFIG.load(deps.storage)?;
+        if let Some(old_admin) = &config.admin {
+            roles::revoke(deps.storage, old_admin, Role::Admin);
+        }
+        roles::grant(deps.storage, &transfer.new_admin, Role::Admin)?;
+        config.admin = Some(transfer.new_admin.clone());
+        CONFIG.save(deps.storage, &config)?;
+        ADMIN_TRANSFER.remove(deps.storage);
+
+        Ok(Response::new()
+            .add_attribute("method", "accept_admin_transfer")
+            .add_attribute("admin", transfer.new_admin))
+    }
+
+    pub fn cancel_admin_transfer(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+        ensure_contract_admin(deps.storage, &info)?;
+        if !ADMIN_TRANSFER.exists(deps.storage) {
+            return Err(ContractError::NoPendingAdminTransfer {});
+        }
+        ADMIN_TRANSFER.remove(deps.storage);
+
+        Ok(Response::new().add_attribute("method", "cancel_admin_transfer"))
+    }
+
+    pub fn renounce_admin(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+        let mut config = ensure_contract_admin(deps.storage, &info)?;
+
+        roles::revoke(deps.storage, &info.sender, Role::Admin);
+        config.admin = None;
+        CONFIG.save(deps.storage, &config)?;
+        ADMIN_TRANSFER.remove(deps.storage);
+
+        Ok(Response::new()
+            .add_attribute("method", "renounce_admin")
+            .add_attribute("previous_admin", info.sender))
+    }
+
+    // Repairs the running loan counters should they ever drift from the stored loans
+    pub fn recompute_statistics(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+        roles::ensure_role(deps.storage, &info.sender, &[Role::Admin])?;
+
+        let loans = stats::recompute(deps.storage)?;
+
+        Ok(Response::new()
+            .add_attribute("method", "recompute_statistics")
+            .add_attribute("loans", loans.to_string()))
+    }
+
     pub fn create_loan_template(
         deps: DepsMut,
-        _env: Env,
+        env: Env,
         info: MessageInfo,
         template_id: String,
         name: String,
         fields: HashMap<String, FieldType>, // Field definitions for the template
+        rules: Vec<ValidationRule>,         // Cross-field checks over the fields
     ) -> Result<Response, ContractError> {
+        roles::ensure_role(deps.storage, &info.sender, &[Role::LoanOfficer])?;
         let user_id = info.sender.to_string(); // Identify the user creating the template
 
+        validation::validate_template_fields(deps.api, env.block.time.seconds() as i64, &fields, &rules)?;
+
         // Ensure that the template ID doesn't already exist for this user
         if USER_TEMPLATES.has(deps.storage, (&user_id, &template_id)) {
             return Err(ContractError::TemplateAlreadyExists {
@@ -78,6 +250,8 @@ mod exec {
             submitter: user_id.clone(),
             reviewer: None,                       // No reviewer assigned yet
             review_status: ReviewStatus::Pending, // Initially pending review
+            rules,
+            version: 1,
         };
 
         // Store the loan template in user-specific storage
@@ -91,6 +265,56 @@ mod exec {
             .add_attribute("status", "pending"))
     }
 
+    // Edits a draft in place; an approved template stays as it is and gets a new draft version,
+    // while a rejected one is revised under its own number as it was never published
+    pub fn update_loan_template(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        template_id: String,
+        name: String,
+        fields: HashMap<String, FieldType>,
+        rules: Vec<ValidationRule>,
+    ) -> Result<Response, ContractError> {
+        roles::ensure_role(deps.storage, &info.sender, &[Role::LoanOfficer])?;
+        let user_id = info.sender.to_string();
+
+        validation::validate_template_fields(deps.api, env.block.time.seconds() as i64, &fields, &rules)?;
+
+        let mut template = USER_TEMPLATES
+            .may_load(deps.storage, (&user_id, &template_id))?
+            .ok_or_else(|| ContractError::TemplateNotFound {
+                template_id: template_id.clone(),
+            })?;
+        match template.review_status {
+            ReviewStatus::Approved => {
+                template.version += 1;
+                template.reviewer = None;
+                template.review_status = ReviewStatus::Pending;
+            }
+            // The reviewer must see the version they are deciding on
+            ReviewStatus::Pending if template.reviewer.is_some() => {
+                return Err(ContractError::TemplateUnderReview { template_id });
+            }
+            ReviewStatus::Pending => {}
+            ReviewStatus::Rejected => {
+                template.reviewer = None;
+                template.review_status = ReviewStatus::Pending;
+            }
+        }
+        template.name = name;
+        template.fields = fields;
+        template.rules = rules;
+
+        USER_TEMPLATES.save(deps.storage, (&user_id, &template_id), &template)?;
+
+        Ok(Response::new()
+            .add_attribute("method", "update_loan_template")
+            .add_attribute("template_id", template_id)
+            .add_attribute("version", template.version.to_string())
+            .add_attribute("status", "pending"))
+    }
+
     pub fn submit_template_for_review(
         deps: DepsMut,
         _env: Env,
@@ -98,16 +322,22 @@ mod exec {
         template_id: String, // The template ID to submit for review
         reviewer: String,    // The ID of the reviewer to assign
     ) -> Result<Response, ContractError> {
+        roles::ensure_role(deps.storage, &info.sender, &[Role::LoanOfficer])?;
         let user_id = info.sender.to_string();
 
+        // Only registered reviewers may be assigned a template
+        let reviewer_addr = deps.api.addr_validate(&reviewer)?;
+        roles::ensure_member(deps.storage, &reviewer_addr, Role::Reviewer)?;
+        let reviewer = reviewer_addr.to_string();
+
         // Load the template from the user's templates
         USER_TEMPLATES.update(deps.storage, (&user_id, &template_id), |maybe_template| {
             let mut template = maybe_template.ok_or_else(|| ContractError::TemplateNotFound {
                 template_id: template_id.clone(),
             })?;
 
-            // Ensure the template has not been reviewed already
-            if template.review_status != ReviewStatus::Pending {
+            // Approved versions are final; rejected ones may go back for another review
+            if template.review_status == ReviewStatus::Approved {
                 return Err(ContractError::TemplateAlreadyReviewed {
                     template_id: template_id.clone(),
                 });
@@ -126,7 +356,7 @@ mod exec {
             reviewer: reviewer.clone(),
             creater: user_id.clone(),
         };
-        TEMPLATE_REVIEWERS.save(deps.storage, &template_id, &tuple)?;
+        TEMPLATE_REVIEWERS.save(deps.storage, (&user_id, &template_id), &tuple)?;
 
         // Return success response
         Ok(Response::new()
@@ -136,23 +366,28 @@ mod exec {
             .add_attribute("status", "pending"))
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn review_template(
         deps: DepsMut,
-        _env: Env,
+        env: Env,
         info: MessageInfo,
-        template_id: String, // The template ID to review
-        approve: bool,       // True for approve, False for reject
+        user_id: String,         // The template's owner
+        template_id: String,     // The template ID to review
+        approve: bool,           // True for approve, False for reject
+        comment: Option<String>, // Reviewer's note on the decision
     ) -> Result<Response, ContractError> {
+        roles::ensure_role(deps.storage, &info.sender, &[Role::Reviewer])?;
+
         // Check that the template has been assigned to the reviewer
-        let tuple = TEMPLATE_REVIEWERS.load(deps.storage, &template_id)?;
-        if tuple.reviewer != info.sender.to_string() {
+        let tuple = TEMPLATE_REVIEWERS.load(deps.storage, (&user_id, &template_id))?;
+        if tuple.reviewer != info.sender.as_str() {
             return Err(ContractError::Unauthorized {});
         }
 
         // Load the template and update its review status
-        USER_TEMPLATES.update(
+        let template = USER_TEMPLATES.update(
             deps.storage,
-            (&tuple.creater, &template_id),
+            (&user_id, &template_id),
             |maybe_template| -> StdResult<_> {
                 let mut template =
                     maybe_template.ok_or_else(|| StdError::generic_err("Template not found"))?;
@@ -169,14 +404,29 @@ mod exec {
             },
         )?;
 
+        // Approved versions are kept for the loans created against them
+        if approve {
+            templates::publish(deps.storage, &user_id, &template)?;
+        }
+        let review = TemplateReview {
+            review: 0, // Numbered by `record_review`
+            version: template.version,
+            reviewer: info.sender.clone(),
+            decision: template.review_status.clone(),
+            comment,
+            timestamp: env.block.time.seconds(),
+        };
+        templates::record_review(deps.storage, &user_id, &template_id, review)?;
+
         // Remove the reviewer assignment as the review is complete
-        TEMPLATE_REVIEWERS.remove(deps.storage, &template_id);
+        TEMPLATE_REVIEWERS.remove(deps.storage, (&user_id, &template_id))?;
 
         // Return a success response
         let status = if approve { "approved" } else { "rejected" };
         Ok(Response::new()
             .add_attribute("method", "review_template")
             .add_attribute("template_id", template_id)
+            .add_attribute("version", template.version.to_string())
             .add_attribute("status", status))
     }
 
@@ -184,135 +434,59 @@ mod exec {
     pub fn create_loan(
         deps: DepsMut,
         env: Env,
-        _info: MessageInfo,
+        info: MessageInfo,
         user_id: String,
         loan_requests: Vec<LoanRequest>,
     ) -> Result<Response, ContractError> {
+        roles::ensure_role(deps.storage, &info.sender, &[Role::LoanOfficer])?;
         let mut response = Response::new();
 
         for loan_request in loan_requests {
             let template_id = &loan_request.template_id;
-            let values = loan_request.values;
-
-            // Load the template
-            let template =
-                USER_TEMPLATES.load(deps.storage, (&user_id.as_str(), template_id.as_str()))?;
+            let mut values = loan_request.values;
+            let status = if loan_request.draft {
+                LoanStatus::Draft
+            } else {
+                LoanStatus::Submitted
+            };
 
-            // Ensure the template is approved before creating the loan
-            if template.review_status != ReviewStatus::Approved {
-                return Err(ContractError::TemplateNotApproved {
+            // Load the latest approved version; a newer draft does not stop loans on it
+            USER_TEMPLATES.load(deps.storage, (user_id.as_str(), template_id.as_str()))?;
+            let template = templates::latest_published(deps.storage, &user_id, template_id)?
+                .ok_or_else(|| ContractError::TemplateNotApproved {
                     template_id: template_id.clone(),
-                });
-            }
-            //Ensure that loan request contains fields that exist in the template
-            if template.fields.len() != values.len() {
-                return Err(ContractError::InvalidLoanRequest {});
-            }
+                })?;
+            // Fill in defaults, then validate the values, nested ones included, against the template
+            validation::apply_defaults(&template.fields, &mut values);
+            validation::validate_loan(deps.api, env.block.time.seconds() as i64, &template, &values)?;
 
-            // Validate the fields in the loan match the template
-            for (field_name, field_type) in &template.fields {
-                if let Some(value) = values.get(field_name) {
-                    // Validate field types and constraints
-                    match field_type {
-                        FieldType::String { format, .. } => {
-                            if let Some(f) = format {
-                                let re = regex_lite::Regex::new(f).map_err(|_| {
-                                    StdError::generic_err(format!(
-                                        "Invalid format for field '{}'",
-                                        field_name
-                                    ))
-                                })?;
-                                if !re.is_match(value) {
-                                    return Err(ContractError::InvalidFormat {
-                                        field_name: field_name.clone(),
-                                    });
-                                }
-                            }
-                        }
-                        FieldType::Number {
-                            min_value,
-                            max_value,
-                            ..
-                        } => {
-                            let parsed_value = value.parse::<f64>().map_err(|_| {
-                                StdError::generic_err(format!(
-                                    "Field '{}' should be a number",
-                                    field_name
-                                ))
-                            })?;
-                            if let Some(min) = min_value {
-                                if parsed_value < *&min.parse::<f64>().unwrap() {
-                                    return Err(ContractError::InvalidNumberField {
-                                        field_name: field_name.clone(),
-                                    });
-                                }
-                            }
-                            if let Some(max) = max_value {
-                                if parsed_value > *&max.parse::<f64>().unwrap() {
-                                    return Err(ContractError::InvalidNumberField {
-                                        field_name: field_name.clone(),
-                                    });
-                                }
-                            }
-                        }
-                        FieldType::Boolean { .. } => {
-                            if value != "true" && value != "false" {
-                                return Err(ContractError::InvalidFormat {
-                                    field_name: field_name.clone(),
-                                });
-                            }
-                        }
-                        FieldType::Date { format, .. } => {
-                            if let Some(f) = format {
-                                let re = regex_lite::Regex::new(f).map_err(|_| {
-                                    StdError::generic_err(format!(
-                                        "Invalid format for field '{}'",
-                                        field_name
-                                    ))
-                                })?;
-                                if !re.is_match(value) {
-                                    return Err(ContractError::InvalidFormat {
-                                        field_name: field_name.clone(),
-                                    });
-                                }
-                            } else {
-                                if value.parse::<u64>().is_err() {
-                                    return Err(ContractError::InvalidFormat {
-                                        field_name: field_name.clone(),
-                                    });
-                                }
-                            }
-                        }
-                    }
-                } else {
-                    return Err(ContractError::MissingField {
-                        field_name: field_name.clone(),
-                    });
-                }
-            }
-
-            // Generate a unique loan ID using the UUID crate
-            let loan_id = Uuid::new_v4().to_string();
+            // Take the next ID from the global counter so every validator agrees on it
+            let next_id = NEXT_LOAN_ID.load(deps.storage)?;
+            NEXT_LOAN_ID.save(deps.storage, &(next_id + 1))?;
+            let loan_id = next_id.to_string();
 
             // Create the loan data
             let loan = LoanData {
                 loan_id: loan_id.clone(),
                 template_id: template_id.clone(),
+                template_owner: user_id.clone(),
+                template_version: template.version,
                 values,
-                review_status: ReviewStatus::Pending,
+                status,
                 creation_date: env.block.time.seconds(),
                 approval_date: None,
                 rejection_date: None,
+                status_history: vec![StatusTransition {
+                    status,
+                    timestamp: env.block.time.seconds(),
+                }],
+                decided_by: None,
+                decision_reason: None,
+                reviewer: None,
             };
 
             // Save the loan to storage (use a composite key of user ID and loan ID)
-            LOAN_STORAGE
-                .save(
-                    deps.storage,
-                    (&_info.sender.to_string(), &loan.loan_id),
-                    &loan,
-                )
-                .map_err(ContractError::from)?;
+            stats::save_loan(deps.storage, (info.sender.as_str(), &loan_key(&loan.loan_id)), &loan)?;
 
             // Add success attribute for each loan
             response = response.add_attribute("created_loan_id", loan_id);
@@ -321,49 +495,292 @@ mod exec {
         Ok(response.add_attribute("method", "create_loans"))
     }
 
-    // Function to assign multiple loans to a reviewer
-    pub fn assign_loans_to_reviewer(
+    // Changes editable values of a loan still waiting for a decision, keeping the old ones
+    pub fn amend_loan(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        loan_id: String,
+        changes: HashMap<String, Option<FieldValue>>,
+    ) -> Result<Response, ContractError> {
+        roles::ensure_role(deps.storage, &info.sender, &[Role::LoanOfficer])?;
+        let user_id = info.sender.to_string();
+        let key = loan_key(&loan_id);
+
+        let mut loan = LOAN_STORAGE
+            .may_load(deps.storage, (&user_id, &key))?
+            .ok_or(ContractError::LoanNotFound {})?;
+        if loan.status != LoanStatus::Draft && !loan.status.is_awaiting_decision() {
+            return Err(ContractError::LoanNotAmendable {
+                loan_id,
+                status: loan.status,
+            });
+        }
+        // Amendments are checked against the version the loan was created with
+        let template = TEMPLATE_VERSIONS
+            .may_load(
+                deps.storage,
+                (&loan.template_owner, &loan.template_id, loan.template_version),
+            )?
+            .ok_or_else(|| ContractError::TemplateNotFound {
+                template_id: loan.template_id.clone(),
+            })?;
+
+        // Checked in name order so the same request always reports the same field
+        let mut changes: Vec<(String, Option<FieldValue>)> = changes.into_iter().collect();
+        changes.sort_by(|a, b| a.0.cmp(&b.0));
+        let names: Vec<String> = changes.iter().map(|(name, _)| name.clone()).collect();
+        let mut values = loan.values.clone();
+        for (name, value) in changes {
+            let field_type = template
+                .fields
+                .get(&name)
+                .ok_or_else(|| ContractError::UnknownField { field_name: name.clone() })?;
+            if !field_type.is_editable() {
+                return Err(ContractError::FieldNotEditable { field_name: name });
+            }
+            match value {
+                Some(value) => values.insert(name, value),
+                None => values.remove(&name),
+            };
+        }
+
+        // A cleared field falls back to its default, as it would on a new loan
+        validation::apply_defaults(&template.fields, &mut values);
+        let changes: Vec<FieldChange> = names
+            .into_iter()
+            .filter(|name| loan.values.get(name) != values.get(name))
+            .map(|name| FieldChange {
+                before: loan.values.get(&name).cloned(),
+                after: values.get(&name).cloned(),
+                field: name,
+            })
+            .collect();
+        if changes.is_empty() {
+            return Err(ContractError::NothingToAmend {});
+        }
+        validation::validate_loan(deps.api, env.block.time.seconds() as i64, &template, &values)?;
+
+        // Revisions are numbered from 1 in the order they were made
+        let revision = LOAN_REVISIONS
+            .prefix(&key)
+            .keys(deps.storage, None, None, Order::Descending)
+            .next()
+            .transpose()?
+            .map_or(1, |last| last + 1);
+        let previous = LoanRevision {
+            revision,
+            values: std::mem::replace(&mut loan.values, values),
+            changes,
+            amended_by: info.sender.clone(),
+            timestamp: env.block.time.seconds(),
+        };
+        LOAN_REVISIONS.save(deps.storage, (&key, revision), &previous)?;
+        stats::save_loan(deps.storage, (&user_id, &key), &loan)?;
+
+        Ok(Response::new()
+            .add_attribute("method", "amend_loan")
+            .add_attribute("loan_id", loan_id)
+            .add_attribute("revision", revision.to_string()))
+    }
+
+    // Assigns a loan to the reviewer; the reviewer index follows the loan
+    fn add_assignment(
+        storage: &mut dyn Storage,
+        reviewer: &Addr,
+        user_id: &str,
+        loan_id: &str,
+    ) -> Result<(), ContractError> {
+        let key = loan_key(loan_id);
+        let mut loan = LOAN_STORAGE
+            .may_load(storage, (user_id, &key))?
+            .ok_or(ContractError::LoanNotFound {})?;
+        ensure_assignment_open(&loan, loan_id)?;
+
+        // A loan has at most one active reviewer
+        if let Some(current) = &loan.reviewer {
+            if current == reviewer {
+                return Ok(());
+            }
+            return Err(ContractError::LoanAlreadyAssigned {
+                loan_id: loan_id.to_string(),
+                reviewer: current.to_string(),
+            });
+        }
+
+        loan.reviewer = Some(reviewer.clone());
+        stats::save_loan(storage, (user_id, &key), &loan)?;
+        Ok(())
+    }
+
+    // Clears the loan's reviewer, which must be the given one
+    fn remove_assignment(
+        storage: &mut dyn Storage,
+        reviewer: &Addr,
+        user_id: &str,
+        loan_id: &str,
+    ) -> Result<(), ContractError> {
+        let not_assigned = || ContractError::NotAssignedReviewer {
+            loan_id: loan_id.to_string(),
+        };
+        let key = loan_key(loan_id);
+        let mut loan = LOAN_STORAGE
+            .may_load(storage, (user_id, &key))?
+            .ok_or_else(not_assigned)?;
+        ensure_assignment_open(&loan, loan_id)?;
+        if loan.reviewer.as_ref() != Some(reviewer) {
+            return Err(not_assigned());
+        }
+
+        loan.reviewer = None;
+        stats::save_loan(storage, (user_id, &key), &loan)?;
+        Ok(())
+    }
+
+    // Who reviewed a loan is settled once it leaves review
+    fn ensure_assignment_open(loan: &LoanData, loan_id: &str) -> Result<(), ContractError> {
+        if loan.status != LoanStatus::Draft && !loan.status.is_awaiting_decision() {
+            return Err(ContractError::AssignmentClosed {
+                loan_id: loan_id.to_string(),
+                status: loan.status,
+            });
+        }
+        Ok(())
+    }
+
+    // Function to add loans to a reviewer's existing assignments
+    pub fn assign_loans(
+        deps: DepsMut,
+        info: MessageInfo,
+        reviewer: String,
+        loans: Vec<(String, String)>,
+    ) -> Result<Response, ContractError> {
+        roles::ensure_role(deps.storage, &info.sender, &[Role::Admin])?;
+
+        let reviewer_addr = deps.api.addr_validate(reviewer.as_str())?;
+        roles::ensure_member(deps.storage, &reviewer_addr, Role::Reviewer)?;
+
+        for (user_id, loan_id) in &loans {
+            add_assignment(deps.storage, &reviewer_addr, user_id, loan_id)?;
+        }
+
+        Ok(Response::new()
+            .add_attribute("method", "assign_loans")
+            .add_attribute("reviewer", reviewer.as_str())
+            .add_attribute("count", loans.len().to_string()))
+    }
+
+    // Function to remove loans from a reviewer's assignments
+    pub fn unassign_loans(
         deps: DepsMut,
-        _info: MessageInfo,
+        info: MessageInfo,
         reviewer: String,
         loans: Vec<(String, String)>,
     ) -> Result<Response, ContractError> {
-        let reviewer_addr = deps.api.addr_validate(&reviewer.as_str())?;
+        roles::ensure_role(deps.storage, &info.sender, &[Role::Admin])?;
 
-        // Save the list of loans assigned to this reviewer
-        REVIEWER_ASSIGNMENTS.save(deps.storage, &reviewer_addr, &loans)?;
+        let reviewer_addr = deps.api.addr_validate(reviewer.as_str())?;
+        for (user_id, loan_id) in &loans {
+            remove_assignment(deps.storage, &reviewer_addr, user_id, loan_id)?;
+        }
 
         Ok(Response::new()
-            .add_attribute("method", "assign_loans_to_reviewer")
-            .add_attribute("reviewer", reviewer.as_str()))
+            .add_attribute("method", "unassign_loans")
+            .add_attribute("reviewer", reviewer.as_str())
+            .add_attribute("count", loans.len().to_string()))
+    }
+
+    // Function to move a single loan from one reviewer to another
+    pub fn reassign_loan(
+        deps: DepsMut,
+        info: MessageInfo,
+        user_id: String,
+        loan_id: String,
+        from: String,
+        to: String,
+    ) -> Result<Response, ContractError> {
+        roles::ensure_role(deps.storage, &info.sender, &[Role::Admin])?;
+
+        let from_addr = deps.api.addr_validate(&from)?;
+        let to_addr = deps.api.addr_validate(&to)?;
+        roles::ensure_member(deps.storage, &to_addr, Role::Reviewer)?;
+
+        remove_assignment(deps.storage, &from_addr, &user_id, &loan_id)?;
+        add_assignment(deps.storage, &to_addr, &user_id, &loan_id)?;
+
+        Ok(Response::new()
+            .add_attribute("method", "reassign_loan")
+            .add_attribute("loan_id", loan_id)
+            .add_attribute("from", from_addr)
+            .add_attribute("to", to_addr))
     }
 
     // Function to update the review status of a loan and set appropriate dates
+    #[allow(clippy::too_many_arguments)]
     pub fn update_loan_review_status(
         deps: DepsMut,
         env: Env,
+        info: MessageInfo,
         user_id: String,
         loan_id: String,
-        new_status: ReviewStatus,
+        new_status: LoanStatus,
+        reason: Option<String>,
     ) -> Result<Response, ContractError> {
-        LOAN_STORAGE.update(
-            deps.storage,
-            (&user_id, &loan_id),
-            |maybe_loan: Option<LoanData>| -> StdResult<_> {
-                // If loan not found, return a StdError instead of ContractError
-                let mut loan = maybe_loan.ok_or_else(|| StdError::generic_err("Loan not found"))?;
-                loan.review_status = new_status.clone();
-
-                // Set the approval or rejection date based on the new status
-                match new_status {
-                    ReviewStatus::Approved => loan.approval_date = Some(env.block.time.seconds()),
-                    ReviewStatus::Rejected => loan.rejection_date = Some(env.block.time.seconds()),
-                    _ => {}
-                }
-
-                Ok(loan)
-            },
-        )?;
+        roles::ensure_role(deps.storage, &info.sender, &[new_status.transition_role()])?;
+
+        let key = loan_key(&loan_id);
+        let mut loan = LOAN_STORAGE
+            .may_load(deps.storage, (&user_id, &key))?
+            .ok_or(ContractError::LoanNotFound {})?;
+
+        if new_status.transition_role() == Role::Reviewer {
+            // Nobody reviews a loan they submitted themselves, admins included
+            if info.sender.as_str() == user_id {
+                return Err(ContractError::SelfReviewNotAllowed {});
+            }
+
+            // Review steps belong to the assigned reviewer unless an admin steps in
+            if !roles::has_role(deps.storage, &info.sender, Role::Admin)
+                && loan.reviewer.as_ref() != Some(&info.sender)
+            {
+                return Err(ContractError::NotAssignedReviewer {
+                    loan_id: loan_id.clone(),
+                });
+            }
+        } else if info.sender.as_str() != user_id
+            && !roles::has_role(deps.storage, &info.sender, Role::Admin)
+        {
+            // Every other step belongs to the officer who created the loan unless an admin steps in
+            return Err(ContractError::Unauthorized {});
+        }
+
+        // Only moves listed in the transition table are accepted
+        if !loan.status.can_transition_to(new_status) {
+            return Err(ContractError::InvalidTransition {
+                from: loan.status,
+                to: new_status,
+            });
+        }
+
+        let now = env.block.time.seconds();
+        loan.status = new_status;
+        loan.status_history.push(StatusTransition {
+            status: new_status,
+            timestamp: now,
+        });
+
+        // Set the approval or rejection date based on the new status
+        match new_status {
+            LoanStatus::Approved => loan.approval_date = Some(now),
+            LoanStatus::Rejected => loan.rejection_date = Some(now),
+            _ => {}
+        }
+        if matches!(new_status, LoanStatus::Approved | LoanStatus::Rejected) {
+            loan.decided_by = Some(info.sender.clone());
+            loan.decision_reason = reason;
+        }
+
+        stats::save_loan(deps.storage, (&user_id, &key), &loan)?;
 
         Ok(Response::new()
             .add_attribute("method", "update_loan_review_status")
@@ -4,7 +4,7 @@ mod tests {
     use cosmwasm_std::{ from_json, Addr};
 
     use crate::exec::execute;
-    use crate::models::IdentityMetadata;
+    use crate::models::{IdentityMetadata, Page};
     use crate::msg::{ExecuteMsg, QueryMsg};
     use crate::query::query;
     use crate::states::IDENTITIES;
@@ -127,9 +127,15 @@ mod tests {
         let _ = execute(deps.as_mut(), env.clone(), info2.clone(), msg2)
             .unwrap();
 
-        let query_message = QueryMsg::UserInfoAll { };
+        let query_message = QueryMsg::UserInfoAll {
+            start_after: None,
+            limit: None,
+            order: None,
+        };
         let query_response = query(deps.as_ref(), env.clone(), query_message).unwrap();
-        let res: Vec<(Addr, IdentityMetadata)> = from_json(&query_response).unwrap();
+        let page: Page<(Addr, IdentityMetadata), String> = from_json(&query_response).unwrap();
+        assert_eq!(page.next_start_after, None);
+        let res = page.items;
 
         assert_eq!(res.len(), 2);
         assert_eq!(res[0], (info1.sender.clone(), metadata1));
@@ -1,13 +1,42 @@
-use cosmwasm_std::{entry_point, DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{entry_point, DepsMut, Env, MessageInfo, Response};
 
-use crate::models::InstantiateMsg;
+use crate::{
+    error::ContractError,
+    models::{Config, Role},
+    migrate::{CONTRACT_NAME, CONTRACT_VERSION},
+    msg::InstantiateMsg,
+    roles,
+    states::{CONFIG, NEXT_LOAN_ID},
+};
 
 #[entry_point]
 pub fn instantiate(
-    _deps: DepsMut,
+    deps: DepsMut,
     _env: Env,
     _info: MessageInfo,
-    _msg: InstantiateMsg,
-) -> StdResult<Response> {
-    Ok(Response::default())
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let admin = deps.api.addr_validate(&msg.admin)?;
+    CONFIG.save(deps.storage, &Config { admin: Some(admin.clone()) })?;
+    roles::grant(deps.storage, &admin, Role::Admin)?;
+    NEXT_LOAN_ID.save(deps.storage, &1)?;
+
+    // Seed the role registry with the initial members
+    let initial_roles = [
+        (Role::Reviewer, msg.reviewers),
+        (Role::LoanOfficer, msg.loan_officers),
+        (Role::Auditor, msg.auditors),
+    ];
+    for (role, addresses) in initial_roles {
+        for address in addresses {
+            let addr = deps.api.addr_validate(&address)?;
+            roles::grant(deps.storage, &addr, role)?;
+        }
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "instantiate")
+        .add_attribute("admin", admin))
 }
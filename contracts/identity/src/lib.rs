@@ -1,11 +1,19 @@
 pub mod error;
+mod calendar;
+mod countries;
 pub mod exec;
 mod models;
 pub mod msg;
 pub mod query;
 pub mod states;
 pub mod instantiate;
+pub mod migrate;
 pub mod identity;
+pub mod roles;
+pub mod rules;
+pub mod stats;
+pub mod templates;
+pub mod validation;
 
 #[cfg(test)]
 pub mod identity_tests;
@@ -15,3 +23,24 @@ mod test_pub_privatekey;
 #[cfg(test)]
 pub mod loan_tests;
 
+#[cfg(test)]
+mod role_tests;
+
+#[cfg(test)]
+mod migrate_tests;
+
+
+#[cfg(test)]
+mod schema_tests;
+
+#[cfg(test)]
+mod calendar_tests;
+
+#[cfg(test)]
+mod validation_tests;
+
+#[cfg(test)]
+mod rules_tests;
+
+#[cfg(test)]
+mod test_helpers;
@@ -1,20 +1,100 @@
 mod test {
 
-    use crate::models::{AllReviewerStatistics, FieldType, LoanData, LoanRequest, LoanStatistics, ReviewStatus};
+    use crate::models::{AllReviewerStatistics, Comparison, DateField, DateRange, FieldChange, FieldType, FieldValue, Granularity, GroupBy, LoanCounters, LoanData, LoanFilter, LoanRequest, LoanRevision, LoanTimeSeries, NumericPredicate, LoanStatistics, LoanStatus, LoanSummary, LoanTemplate, Page, ReviewStatus, SortOrder, TemplateReview, TimeSeriesBucket};
     use crate::msg::{ExecuteMsg, QueryMsg};
     use crate::query::query;
-    use crate::states::{LOAN_STORAGE, REVIEWER_ASSIGNMENTS, TEMPLATE_REVIEWERS, USER_TEMPLATES};
+    use crate::stats;
+    use crate::states::{loan_key, LOAN_REVISIONS, LOAN_STORAGE, STATUS_COUNTS, TEMPLATE_REVIEWERS, USER_TEMPLATES};
 
     use crate::exec::execute;
+    use crate::error::ContractError;
+    use crate::test_helpers::{addr, approved_template, setup, Deps};
     use cosmwasm_std::testing::{message_info, mock_dependencies, mock_env};
-    use cosmwasm_std::{attr, from_json, Addr};
+    use cosmwasm_std::{attr, from_json, Addr, DepsMut, MessageInfo, Decimal, Order, StdResult, Timestamp};
     use std::collections::HashMap;
 
+    // Creates and approves `template1` for `creator` with a single numeric field
+    fn create_approved_template(deps: &mut Deps) {
+        let amount = FieldType::Number {
+            is_editable: false,
+            required: true,
+            default: None,
+            min_value: Some("50000.0".to_string()),
+            max_value: Some("1000000.0".to_string()),
+        };
+        approved_template(deps, "template1", [("amount", amount)], vec![]);
+    }
+
+    // Creates a loan for `creator` on `template1` and returns its ID
+    fn create_test_loan(deps: &mut Deps, draft: bool) -> String {
+        let loan_request = LoanRequest {
+            template_id: "template1".to_string(),
+            values: HashMap::from([("amount".to_string(), "60000".into())]),
+            draft,
+        };
+        let msg = ExecuteMsg::CreateLoan {
+            user_id: addr("creator").to_string(),
+            loan_requests: vec![loan_request],
+        };
+        let res = execute(deps.as_mut(), mock_env(), message_info(&addr("creator"), &[]), msg).unwrap();
+        res.attributes[0].value.clone()
+    }
+
+    fn transition(
+        deps: &mut Deps,
+        sender: &str,
+        loan_id: &str,
+        new_status: LoanStatus,
+    ) -> Result<cosmwasm_std::Response, ContractError> {
+        let msg = ExecuteMsg::UpdateLoanReviewStatus {
+            user_id: addr("creator").to_string(),
+            loan_id: loan_id.to_string(),
+            new_status,
+            reason: None,
+        };
+        execute(deps.as_mut(), mock_env(), message_info(&addr(sender), &[]), msg)
+    }
+
+    fn assign_loan(deps: DepsMut, reviewer: &str, user_id: String, loan_id: String) {
+        let msg = ExecuteMsg::AssignLoans {
+            reviewer: addr(reviewer).to_string(),
+            loans: vec![(user_id, loan_id)],
+        };
+        execute(deps, mock_env(), message_info(&addr("admin"), &[]), msg).unwrap();
+    }
+
+    fn loans_for_reviewer(
+        deps: &Deps,
+        reviewer: &Addr,
+    ) -> Vec<(String, String)> {
+        let msg = QueryMsg::GetLoansForReviewer {
+            reviewer: reviewer.to_string(),
+            include_values: None,
+            start_after: None,
+            limit: None,
+            order: None,
+        };
+        let page: Page<LoanSummary, (String, String)> =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        page.items.into_iter().map(|summary| (summary.owner, summary.loan_id)).collect()
+    }
+
+    // Moves a submitted loan into review so that it can be approved or rejected
+    fn start_review(deps: DepsMut, reviewer_info: &MessageInfo, user_id: String, loan_id: String) {
+        let msg = ExecuteMsg::UpdateLoanReviewStatus {
+            user_id,
+            loan_id,
+            new_status: LoanStatus::UnderReview,
+            reason: None,
+        };
+        execute(deps, mock_env(), reviewer_info.clone(), msg).unwrap();
+    }
+
     #[test]
     fn test_create_loan_template() {
-        let mut deps = mock_dependencies();
+        let mut deps = setup();
 
-        let info = message_info(&Addr::unchecked("creator"), &[]);
+        let info = message_info(&addr("creator"), &[]);
         let env = mock_env();
 
         // Define the fields for the template
@@ -23,6 +103,8 @@ mod test {
             "amount".to_string(),
             FieldType::Number {
                 is_editable: false,
+                required: true,
+                default: None,
                 min_value: Some("50000.0".to_string()),
                 max_value: Some("1000000.0".to_string()),
             },
@@ -31,6 +113,8 @@ mod test {
             "borrower".to_string(),
             FieldType::String {
                 is_editable: true,
+                required: true,
+                default: None,
                 format: None,
                 min_value: None,
                 max_value: None,
@@ -40,6 +124,8 @@ mod test {
             "interest_rate".to_string(),
             FieldType::Number {
                 is_editable: true,
+                required: true,
+                default: None,
                 min_value: Some("0.5".to_string()),
                 max_value: Some("5.0".to_string()),
             },
@@ -50,6 +136,7 @@ mod test {
             template_id: "template1".to_string(),
             name: "Home Loan".to_string(),
             fields,
+            rules: vec![],
         };
         let res = execute(deps.as_mut(), env, info.clone(), msg).unwrap();
         assert_eq!(
@@ -57,25 +144,25 @@ mod test {
             vec![
                 attr("method", "create_loan_template"),
                 attr("template_id", "template1"),
-                attr("submitter", "creator"),
+                attr("submitter", addr("creator")),
                 attr("status", "pending"),
             ]
         );
 
         // Ensure the template is stored correctly under the user-specific key
         let stored_template = USER_TEMPLATES
-            .load(&deps.storage, (&info.sender.to_string(), "template1"))
+            .load(&deps.storage, (info.sender.as_str(), "template1"))
             .unwrap();
         assert_eq!(stored_template.name, "Home Loan");
-        assert_eq!(stored_template.submitter, "creator");
+        assert_eq!(stored_template.submitter, addr("creator").as_str());
         assert_eq!(stored_template.review_status, ReviewStatus::Pending);
     }
 
     #[test]
     fn test_submit_template_for_review() {
-        let mut deps = mock_dependencies();
+        let mut deps = setup();
 
-        let info = message_info(&Addr::unchecked("creator"), &[]);
+        let info = message_info(&addr("creator"), &[]);
         let env = mock_env();
 
         // Create the loan template first
@@ -84,6 +171,8 @@ mod test {
             "amount".to_string(),
             FieldType::Number {
                 is_editable: false,
+                required: true,
+                default: None,
                 min_value: Some("50000.0".to_string()),
                 max_value: Some("1000000.0".to_string()),
             },
@@ -92,6 +181,8 @@ mod test {
             "borrower".to_string(),
             FieldType::String {
                 is_editable: true,
+                required: true,
+                default: None,
                 format: None,
                 min_value: None,
                 max_value: None,
@@ -103,13 +194,14 @@ mod test {
             template_id: "template1".to_string(),
             name: "Home Loan".to_string(),
             fields,
+            rules: vec![],
         };
         execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
         // Now submit the template for review
         let submit_msg = ExecuteMsg::SubmitTemplateForReview {
             template_id: "template1".to_string(),
-            reviewer: "reviewer1".to_string(),
+            reviewer: addr("reviewer1").to_string(),
         };
         let res = execute(deps.as_mut(), env, info, submit_msg).unwrap();
         assert_eq!(
@@ -117,22 +209,22 @@ mod test {
             vec![
                 attr("method", "submit_template_for_review"),
                 attr("template_id", "template1"),
-                attr("reviewer", "reviewer1"),
+                attr("reviewer", addr("reviewer1")),
                 attr("status", "pending"),
             ]
         );
 
         // Ensure the reviewer assignment is stored correctly
-        let reviewer = TEMPLATE_REVIEWERS.load(&deps.storage, "template1").unwrap();
-        assert_eq!(reviewer.reviewer, "reviewer1");
+        let reviewer = TEMPLATE_REVIEWERS.load(&deps.storage, (addr("creator").as_str(), "template1")).unwrap();
+        assert_eq!(reviewer.reviewer, addr("reviewer1").as_str());
     }
 
     #[test]
     fn test_approve_template() {
-        let mut deps = mock_dependencies();
+        let mut deps = setup();
 
-        let creator_info = message_info(&Addr::unchecked("creator"), &[]);
-        let reviewer_info = message_info(&Addr::unchecked("reviewer1"), &[]);
+        let creator_info = message_info(&addr("creator"), &[]);
+        let reviewer_info = message_info(&addr("reviewer1"), &[]);
         let env = mock_env();
 
         // Create and submit the template for review
@@ -141,6 +233,8 @@ mod test {
             "amount".to_string(),
             FieldType::Number {
                 is_editable: false,
+                required: true,
+                default: None,
                 min_value: Some("50000.0".to_string()),
                 max_value: Some("1000000.0".to_string()),
             },
@@ -150,19 +244,22 @@ mod test {
             template_id: "template1".to_string(),
             name: "Home Loan".to_string(),
             fields,
+            rules: vec![],
         };
 
         execute(deps.as_mut(), env.clone(), creator_info.clone(), create_msg).unwrap();
         let submit_msg = ExecuteMsg::SubmitTemplateForReview {
             template_id: "template1".to_string(),
-            reviewer: "reviewer1".to_string(),
+            reviewer: addr("reviewer1").to_string(),
         };
         execute(deps.as_mut(), env.clone(), creator_info, submit_msg).unwrap();
 
         // Approve the template
         let review_msg = ExecuteMsg::ReviewTemplate {
+            user_id: addr("creator").to_string(),
             template_id: "template1".to_string(),
             approve: true,
+            comment: None,
         };
         let res = execute(deps.as_mut(), env.clone(), reviewer_info, review_msg).unwrap();
         assert_eq!(
@@ -170,23 +267,24 @@ mod test {
             vec![
                 attr("method", "review_template"),
                 attr("template_id", "template1"),
+                attr("version", "1"),
                 attr("status", "approved"),
             ]
         );
 
         // Ensure the template is now marked as approved
         let stored_template = USER_TEMPLATES
-            .load(&deps.storage, (&"creator".to_string(), "template1"))
+            .load(&deps.storage, (addr("creator").as_str(), "template1"))
             .unwrap();
         assert_eq!(stored_template.review_status, ReviewStatus::Approved);
     }
 
     #[test]
     fn test_create_loan_with_approved_template() {
-        let mut deps = mock_dependencies();
+        let mut deps = setup();
 
-        let creator_info = message_info(&Addr::unchecked("creator"), &[]);
-        let reviewer_info = message_info(&Addr::unchecked("reviewer1"), &[]);
+        let creator_info = message_info(&addr("creator"), &[]);
+        let reviewer_info = message_info(&addr("reviewer1"), &[]);
         let env = mock_env();
 
         // Create, submit, and approve the template
@@ -195,6 +293,8 @@ mod test {
             "amount".to_string(),
             FieldType::Number {
                 is_editable: false,
+                required: true,
+                default: None,
                 min_value: Some("50000.0".to_string()),
                 max_value: Some("1000000.0".to_string()),
             },
@@ -204,16 +304,19 @@ mod test {
             template_id: "template1".to_string(),
             name: "Home Loan".to_string(),
             fields,
+            rules: vec![],
         };
 
         let submit_msg = ExecuteMsg::SubmitTemplateForReview {
             template_id: "template1".to_string(),
-            reviewer: "reviewer1".to_string(),
+            reviewer: addr("reviewer1").to_string(),
         };
 
         let review_msg = ExecuteMsg::ReviewTemplate {
+            user_id: addr("creator").to_string(),
             template_id: "template1".to_string(),
             approve: true,
+            comment: None,
         };
 
         execute(deps.as_mut(), env.clone(), creator_info.clone(), create_msg).unwrap();
@@ -225,13 +328,14 @@ mod test {
             template_id: "template1".to_string(),
             values: {
                 let mut values = HashMap::new();
-                values.insert("amount".to_string(), "60000".to_string());
+                values.insert("amount".to_string(), "60000".into());
                 values
             },
+            draft: false,
         };
 
         let loan_create_msg = ExecuteMsg::CreateLoan {
-            user_id: "creator".to_string(),
+            user_id: addr("creator").to_string(),
             loan_requests: vec![loan_request],
         };
         let res = execute(
@@ -246,25 +350,28 @@ mod test {
         // Ensure the loan is stored correctly
         let loan_id = &res.attributes[0].value;
         let loan = LOAN_STORAGE
-            .load(&deps.storage, (&"creator".to_string(), loan_id))
+            .load(&deps.storage, (addr("creator").as_str(), &loan_key(loan_id)))
             .unwrap();
         assert_eq!(loan.template_id, "template1");
-        assert_eq!(loan.values.get("amount").unwrap(), "60000");
+        assert_eq!(loan.values["amount"].as_text().unwrap(), "60000");
     }
 
     #[test]
     fn test_assign_loans_to_reviewer() {
-        let mut deps = mock_dependencies();
+        let mut deps = setup();
+        create_approved_template(&mut deps);
+        let loan1 = create_test_loan(&mut deps, false);
+        let loan2 = create_test_loan(&mut deps, false);
 
         // Reviewer address
-        let verifier: Addr = deps.api.addr_make("reviewer1");
+        let verifier: Addr = addr("reviewer1");
 
-        let info = message_info(&Addr::unchecked("creator"), &[]);
-        let msg = ExecuteMsg::AssignLoansToReviewer {
+        let info = message_info(&addr("admin"), &[]);
+        let msg = ExecuteMsg::AssignLoans {
             reviewer: verifier.to_string(),
             loans: vec![
-                ("user1".to_string(), "loan1".to_string()),
-                ("user2".to_string(), "loan2".to_string()),
+                (addr("creator").to_string(), loan1.clone()),
+                (addr("creator").to_string(), loan2.clone()),
             ],
         };
 
@@ -275,30 +382,25 @@ mod test {
         assert_eq!(
             res.attributes,
             vec![
-                attr("method", "assign_loans_to_reviewer"),
-                attr("reviewer", verifier.to_string())
+                attr("method", "assign_loans"),
+                attr("reviewer", verifier.to_string()),
+                attr("count", "2"),
             ]
         );
 
         // Verify the loans have been assigned to the reviewer
-        let assigned_loans = REVIEWER_ASSIGNMENTS.load(&deps.storage, &verifier).unwrap();
+        let assigned_loans = loans_for_reviewer(&deps, &verifier);
         assert_eq!(assigned_loans.len(), 2);
-        assert_eq!(
-            assigned_loans[0],
-            ("user1".to_string(), "loan1".to_string())
-        );
-        assert_eq!(
-            assigned_loans[1],
-            ("user2".to_string(), "loan2".to_string())
-        );
+        assert_eq!(assigned_loans[0], (addr("creator").to_string(), loan1));
+        assert_eq!(assigned_loans[1], (addr("creator").to_string(), loan2));
     }
 
     #[test]
     fn test_update_loan_review_status_to_approved() {
-        let mut deps = mock_dependencies();
+        let mut deps = setup();
 
-        let creator_info = message_info(&Addr::unchecked("creator"), &[]);
-        let reviewer_info = message_info(&Addr::unchecked("reviewer1"), &[]);
+        let creator_info = message_info(&addr("creator"), &[]);
+        let reviewer_info = message_info(&addr("reviewer1"), &[]);
         let env = mock_env();
 
         // Step 1: Create the loan template
@@ -307,6 +409,8 @@ mod test {
             "amount".to_string(),
             FieldType::Number {
                 is_editable: false,
+                required: true,
+                default: None,
                 min_value: Some("50000.0".to_string()),
                 max_value: Some("1000000.0".to_string()),
             },
@@ -316,16 +420,19 @@ mod test {
             template_id: "template1".to_string(),
             name: "Home Loan".to_string(),
             fields,
+            rules: vec![],
         };
 
         let submit_msg = ExecuteMsg::SubmitTemplateForReview {
             template_id: "template1".to_string(),
-            reviewer: "reviewer1".to_string(),
+            reviewer: addr("reviewer1").to_string(),
         };
 
         let review_msg = ExecuteMsg::ReviewTemplate {
+            user_id: addr("creator").to_string(),
             template_id: "template1".to_string(),
             approve: true,
+            comment: None,
         };
 
         execute(deps.as_mut(), env.clone(), creator_info.clone(), create_msg).unwrap();
@@ -347,13 +454,14 @@ mod test {
             template_id: "template1".to_string(),
             values: {
                 let mut values = HashMap::new();
-                values.insert("amount".to_string(), "60000".to_string());
+                values.insert("amount".to_string(), "60000".into());
                 values
             },
+            draft: false,
         };
 
         let loan_create_msg = ExecuteMsg::CreateLoan {
-            user_id: "creator".to_string(),
+            user_id: addr("creator").to_string(),
             loan_requests: vec![loan_request],
         };
 
@@ -367,15 +475,18 @@ mod test {
         let loan_id = loan_res.attributes[0].value.clone();
 
         // Step 5: Update loan review status to Approved
+        assign_loan(deps.as_mut(), "reviewer1", addr("creator").to_string(), loan_id.clone());
+        start_review(deps.as_mut(), &reviewer_info, addr("creator").to_string(), loan_id.clone());
         let loan_update_msg = ExecuteMsg::UpdateLoanReviewStatus {
-            user_id: "creator".to_string(),
+            user_id: addr("creator").to_string(),
             loan_id: loan_id.clone(),
-            new_status: ReviewStatus::Approved,
+            new_status: LoanStatus::Approved,
+            reason: None,
         };
         let res = execute(
             deps.as_mut(),
             env.clone(),
-            creator_info.clone(),
+            reviewer_info.clone(),
             loan_update_msg,
         )
         .unwrap();
@@ -384,26 +495,26 @@ mod test {
             res.attributes,
             vec![
                 attr("method", "update_loan_review_status"),
-                attr("user_id", "creator"),
+                attr("user_id", addr("creator")),
                 attr("loan_id", loan_id.clone())
             ]
         );
 
         // Step 6: Check the updated loan's status and approval date
         let loan = LOAN_STORAGE
-            .load(&deps.storage, (&"creator".to_string(), &loan_id))
+            .load(&deps.storage, (addr("creator").as_str(), &loan_key(&loan_id)))
             .unwrap();
-        assert_eq!(loan.review_status, ReviewStatus::Approved);
+        assert_eq!(loan.status, LoanStatus::Approved);
         assert!(loan.approval_date.is_some());
         assert!(loan.rejection_date.is_none()); // No rejection date when approved
     }
 
     #[test]
     fn test_update_loan_review_status_to_rejected() {
-        let mut deps = mock_dependencies();
+        let mut deps = setup();
 
-        let creator_info = message_info(&Addr::unchecked("creator"), &[]);
-        let reviewer_info = message_info(&Addr::unchecked("reviewer1"), &[]);
+        let creator_info = message_info(&addr("creator"), &[]);
+        let reviewer_info = message_info(&addr("reviewer1"), &[]);
         let env = mock_env();
 
         // Step 1: Create the loan template
@@ -412,6 +523,8 @@ mod test {
             "amount".to_string(),
             FieldType::Number {
                 is_editable: false,
+                required: true,
+                default: None,
                 min_value: Some("50000.0".to_string()),
                 max_value: Some("1000000.0".to_string()),
             },
@@ -421,16 +534,19 @@ mod test {
             template_id: "template1".to_string(),
             name: "Home Loan".to_string(),
             fields,
+            rules: vec![],
         };
 
         let submit_msg = ExecuteMsg::SubmitTemplateForReview {
             template_id: "template1".to_string(),
-            reviewer: "reviewer1".to_string(),
+            reviewer: addr("reviewer1").to_string(),
         };
 
         let review_msg = ExecuteMsg::ReviewTemplate {
+            user_id: addr("creator").to_string(),
             template_id: "template1".to_string(),
             approve: true,
+            comment: None,
         };
 
         execute(deps.as_mut(), env.clone(), creator_info.clone(), create_msg).unwrap();
@@ -452,13 +568,14 @@ mod test {
             template_id: "template1".to_string(),
             values: {
                 let mut values = HashMap::new();
-                values.insert("amount".to_string(), "60000".to_string());
+                values.insert("amount".to_string(), "60000".into());
                 values
             },
+            draft: false,
         };
 
         let loan_create_msg = ExecuteMsg::CreateLoan {
-            user_id: "creator".to_string(),
+            user_id: addr("creator").to_string(),
             loan_requests: vec![loan_request],
         };
 
@@ -472,15 +589,18 @@ mod test {
         let loan_id = loan_res.attributes[0].value.clone();
 
         // Step 5: Update loan review status to Rejected
+        assign_loan(deps.as_mut(), "reviewer1", addr("creator").to_string(), loan_id.clone());
+        start_review(deps.as_mut(), &reviewer_info, addr("creator").to_string(), loan_id.clone());
         let loan_update_msg = ExecuteMsg::UpdateLoanReviewStatus {
-            user_id: "creator".to_string(),
+            user_id: addr("creator").to_string(),
             loan_id: loan_id.clone(),
-            new_status: ReviewStatus::Rejected,
+            new_status: LoanStatus::Rejected,
+            reason: None,
         };
         let res = execute(
             deps.as_mut(),
             env.clone(),
-            creator_info.clone(),
+            reviewer_info.clone(),
             loan_update_msg,
         )
         .unwrap();
@@ -489,26 +609,26 @@ mod test {
             res.attributes,
             vec![
                 attr("method", "update_loan_review_status"),
-                attr("user_id", "creator"),
+                attr("user_id", addr("creator")),
                 attr("loan_id", loan_id.clone())
             ]
         );
 
         // Step 6: Check the updated loan's status and rejection date
         let loan = LOAN_STORAGE
-            .load(&deps.storage, (&"creator".to_string(), &loan_id))
+            .load(&deps.storage, (addr("creator").as_str(), &loan_key(&loan_id)))
             .unwrap();
-        assert_eq!(loan.review_status, ReviewStatus::Rejected);
+        assert_eq!(loan.status, LoanStatus::Rejected);
         assert!(loan.rejection_date.is_some());
         assert!(loan.approval_date.is_none()); // No approval date when rejected
     }
 
     #[test]
     fn test_query_loan() {
-        let mut deps = mock_dependencies();
+        let mut deps = setup();
 
-        let creator_info = message_info(&Addr::unchecked("creator"), &[]);
-        let reviewer_info = message_info(&Addr::unchecked("reviewer1"), &[]);
+        let creator_info = message_info(&addr("creator"), &[]);
+        let reviewer_info = message_info(&addr("reviewer1"), &[]);
         let env = mock_env();
 
         // Step 1: Create the loan template
@@ -517,6 +637,8 @@ mod test {
             "amount".to_string(),
             FieldType::Number {
                 is_editable: false,
+                required: true,
+                default: None,
                 min_value: Some("50000.0".to_string()),
                 max_value: Some("1000000.0".to_string()),
             },
@@ -525,6 +647,8 @@ mod test {
             "borrower".to_string(),
             FieldType::String {
                 is_editable: true,
+                required: true,
+                default: None,
                 format: None,
                 min_value: None,
                 max_value: None,
@@ -535,16 +659,19 @@ mod test {
             template_id: "template1".to_string(),
             name: "Home Loan".to_string(),
             fields,
+            rules: vec![],
         };
 
         let submit_msg = ExecuteMsg::SubmitTemplateForReview {
             template_id: "template1".to_string(),
-            reviewer: "reviewer1".to_string(),
+            reviewer: addr("reviewer1").to_string(),
         };
 
         let review_msg = ExecuteMsg::ReviewTemplate {
+            user_id: addr("creator").to_string(),
             template_id: "template1".to_string(),
             approve: true,
+            comment: None,
         };
 
         execute(deps.as_mut(), env.clone(), creator_info.clone(), create_msg).unwrap();
@@ -566,14 +693,15 @@ mod test {
             template_id: "template1".to_string(),
             values: {
                 let mut values = HashMap::new();
-                values.insert("amount".to_string(), "60000".to_string());
-                values.insert("borrower".to_string(), "John Doe".to_string());
+                values.insert("amount".to_string(), "60000".into());
+                values.insert("borrower".to_string(), "John Doe".into());
                 values
             },
+            draft: false,
         };
 
         let loan_create_msg = ExecuteMsg::CreateLoan {
-            user_id: "creator".to_string(),
+            user_id: addr("creator").to_string(),
             loan_requests: vec![loan_request],
         };
 
@@ -588,7 +716,7 @@ mod test {
 
         // Step 5: Query the loan
         let loan_query_msg = QueryMsg::GetLoanDetails {
-            user_id: "creator".to_string(),
+            user_id: addr("creator").to_string(),
             loan_id: loan_id.clone(),
         };
         let loan_bin = query(deps.as_ref(), env.clone(), loan_query_msg).unwrap();
@@ -599,9 +727,9 @@ mod test {
         // Step 6: Validate the queried loan
         assert_eq!(loan.loan_id, loan_id);
         assert_eq!(loan.template_id, "template1");
-        assert_eq!(loan.values.get("amount").unwrap(), "60000");
-        assert_eq!(loan.values.get("borrower").unwrap(), "John Doe");
-        assert_eq!(loan.review_status, ReviewStatus::Pending); // Since it's not approved/rejected yet
+        assert_eq!(loan.values["amount"].as_text().unwrap(), "60000");
+        assert_eq!(loan.values["borrower"].as_text().unwrap(), "John Doe");
+        assert_eq!(loan.status, LoanStatus::Submitted); // Since it's not approved/rejected yet
         assert!(loan.approval_date.is_none());
         assert!(loan.rejection_date.is_none());
     }
@@ -612,7 +740,7 @@ mod test {
 
         // Try to query a loan that doesn't exist
         let loan_query_msg = QueryMsg::GetLoanDetails {
-            user_id: "creator".to_string(),
+            user_id: addr("creator").to_string(),
             loan_id: "nonexistent_loan".to_string(),
         };
         let res_bin = query(deps.as_ref(), mock_env().clone(), loan_query_msg);
@@ -623,10 +751,10 @@ mod test {
 
     #[test]
     fn test_query_loans_for_user() {
-        let mut deps = mock_dependencies();
+        let mut deps = setup();
 
-        let creator_info = message_info(&Addr::unchecked("creator1"), &[]);
-        let reviewer_info = message_info(&Addr::unchecked("reviewer1"), &[]);
+        let creator_info = message_info(&addr("creator1"), &[]);
+        let reviewer_info = message_info(&addr("reviewer1"), &[]);
         let env = mock_env();
 
         // Step 1: Create the loan template
@@ -635,6 +763,8 @@ mod test {
             "amount".to_string(),
             FieldType::Number {
                 is_editable: false,
+                required: true,
+                default: None,
                 min_value: Some("50000.0".to_string()),
                 max_value: Some("1000000.0".to_string()),
             },
@@ -643,6 +773,8 @@ mod test {
             "borrower".to_string(),
             FieldType::String {
                 is_editable: true,
+                required: true,
+                default: None,
                 format: None,
                 min_value: None,
                 max_value: None,
@@ -653,16 +785,19 @@ mod test {
             template_id: "template1".to_string(),
             name: "Home Loan".to_string(),
             fields,
+            rules: vec![],
         };
 
         let submit_msg = ExecuteMsg::SubmitTemplateForReview {
             template_id: "template1".to_string(),
-            reviewer: "reviewer1".to_string(),
+            reviewer: addr("reviewer1").to_string(),
         };
 
         let review_msg = ExecuteMsg::ReviewTemplate {
+            user_id: addr("creator1").to_string(),
             template_id: "template1".to_string(),
             approve: true,
+            comment: None,
         };
 
         execute(deps.as_mut(), env.clone(), creator_info.clone(), create_msg).unwrap();
@@ -684,29 +819,31 @@ mod test {
             template_id: "template1".to_string(),
             values: {
                 let mut values = HashMap::new();
-                values.insert("amount".to_string(), "60000".to_string());
-                values.insert("borrower".to_string(), "John Doe".to_string());
+                values.insert("amount".to_string(), "60000".into());
+                values.insert("borrower".to_string(), "John Doe".into());
                 values
             },
+            draft: false,
         };
         let loan_request_2 = LoanRequest {
             template_id: "template1".to_string(),
             values: {
                 let mut values = HashMap::new();
-                values.insert("amount".to_string(), "80000".to_string());
-                values.insert("borrower".to_string(), "Jane Smith".to_string());
+                values.insert("amount".to_string(), "80000".into());
+                values.insert("borrower".to_string(), "Jane Smith".into());
                 values
             },
+            draft: false,
         };
 
         // Create the two loans
         let loan_create_msg1 = ExecuteMsg::CreateLoan {
-            user_id: "creator1".to_string(),
+            user_id: addr("creator1").to_string(),
             loan_requests: vec![loan_request_1.clone()],
         };
 
         let loan_create_msg2 = ExecuteMsg::CreateLoan {
-            user_id: "creator1".to_string(),
+            user_id: addr("creator1").to_string(),
             loan_requests: vec![loan_request_2.clone()],
         };
         execute(deps.as_mut(),env.clone(),creator_info.clone(), loan_create_msg1,).unwrap();
@@ -714,12 +851,18 @@ mod test {
 
         // Step 5: Query all loans for the user
         let loan_query_msg = QueryMsg::GetLoansForUser {
-            user_id: "creator1".to_string(),
+            user_id: addr("creator1").to_string(),
+            include_values: Some(true),
+            start_after: None,
+            limit: None,
+            order: None,
         };
         let loans_bin = query(deps.as_ref(), mock_env().clone(), loan_query_msg).unwrap();
 
         //Deserialize the loans
-        let loans: Vec<LoanData> = from_json(&loans_bin).unwrap();
+        let page: Page<LoanSummary, String> = from_json(&loans_bin).unwrap();
+        assert_eq!(page.next_start_after, None);
+        let loans: Vec<LoanData> = page.items.into_iter().map(|summary| summary.loan.unwrap()).collect();
 
         // Step 6: Validate the loans
         assert_eq!(loans.len(), 2);
@@ -727,14 +870,14 @@ mod test {
         // Check the first loan
         let loan_1 = &loans[0];
         assert_eq!(loan_1.template_id, "template1");
-        assert_eq!(true, loan_1.values.get("amount").unwrap() == "60000" || loan_1.values.get("amount").unwrap() == "80000");
-        assert_eq!(true, loan_1.values.get("borrower").unwrap()== "Jane Smith" || loan_1.values.get("borrower").unwrap() == "John Doe");
+        assert!(loan_1.values["amount"].as_text().unwrap() == "60000" || loan_1.values["amount"].as_text().unwrap() == "80000");
+        assert!(loan_1.values["borrower"].as_text().unwrap()== "Jane Smith" || loan_1.values["borrower"].as_text().unwrap() == "John Doe");
 
         // Check the second loan
         let loan_2 = &loans[1];
         assert_eq!(loan_2.template_id, "template1");
-        assert_eq!(true, loan_1.values.get("amount").unwrap() == "60000" || loan_1.values.get("amount").unwrap() == "80000");
-        assert_eq!(true, loan_1.values.get("borrower").unwrap()== "Jane Smith" || loan_1.values.get("borrower").unwrap() == "John Doe");
+        assert!(loan_1.values["amount"].as_text().unwrap() == "60000" || loan_1.values["amount"].as_text().unwrap() == "80000");
+        assert!(loan_1.values["borrower"].as_text().unwrap()== "Jane Smith" || loan_1.values["borrower"].as_text().unwrap() == "John Doe");
     }
 
     #[test]
@@ -743,21 +886,27 @@ mod test {
 
         // Query a user with no loans
         let loan_query_msg = QueryMsg::GetLoansForUser {
-            user_id: "creator".to_string(),
+            user_id: addr("creator").to_string(),
+            include_values: None,
+            start_after: None,
+            limit: None,
+            order: None,
         };
         let loans = query(deps.as_ref(), mock_env().clone(), loan_query_msg).unwrap();
 
         // Deserialize the loans
-        let loans: Vec<LoanData> = from_json(&loans).unwrap();
+        let loans: Page<LoanSummary, String> = from_json(&loans).unwrap();
 
-        // Ensure the result is an empty vector
-        assert!(loans.is_empty());
+        // Ensure the result is an empty page
+        assert!(loans.items.is_empty());
+        assert_eq!(loans.next_start_after, None);
     }
     #[test]
     fn test_query_loan_statistics() {
-        let mut deps = mock_dependencies();
-        let creator_info = message_info(&Addr::unchecked("creator"), &[]);
-        let reviewer_info = message_info(&Addr::unchecked("reviewer1"), &[]);
+        let mut deps = setup();
+        let admin_info = message_info(&addr("admin"), &[]);
+        let creator_info = message_info(&addr("creator"), &[]);
+        let reviewer_info = message_info(&addr("reviewer1"), &[]);
 
         let env = mock_env();
 
@@ -767,6 +916,8 @@ mod test {
             "amount".to_string(),
             FieldType::Number {
                 is_editable: false,
+                required: true,
+                default: None,
                 min_value: Some("50000.0".to_string()),
                 max_value: Some("1000000.0".to_string()),
             },
@@ -775,6 +926,8 @@ mod test {
             "borrower".to_string(),
             FieldType::String {
                 is_editable: false,
+                required: true,
+                default: None,
                 format: None,
                 min_value: None,
                 max_value: None,
@@ -785,16 +938,19 @@ mod test {
             template_id: "template1".to_string(),
             name: "Home Loan".to_string(),
             fields: fields.clone(),
+            rules: vec![],
         };
 
         let submit_msg = ExecuteMsg::SubmitTemplateForReview {
             template_id: "template1".to_string(),
-            reviewer: "reviewer1".to_string(),
+            reviewer: addr("reviewer1").to_string(),
         };
 
         let review_msg = ExecuteMsg::ReviewTemplate {
+            user_id: addr("creator").to_string(),
             template_id: "template1".to_string(),
             approve: true,
+            comment: None,
         };
 
         execute(deps.as_mut(), env.clone(), creator_info.clone(), create_msg).unwrap();
@@ -814,14 +970,15 @@ mod test {
             template_id: "template1".to_string(),
             values: {
                 let mut values = HashMap::new();
-                values.insert("amount".to_string(), "60000".to_string());
-                values.insert("borrower".to_string(), "John Doe".to_string());
+                values.insert("amount".to_string(), "60000".into());
+                values.insert("borrower".to_string(), "John Doe".into());
                 values
             },
+            draft: false,
         };
 
         let loan_create_msg1 = ExecuteMsg::CreateLoan {
-            user_id: "creator".to_string(),
+            user_id: addr("creator").to_string(),
             loan_requests: vec![loan_request_1.clone()],
         };
 
@@ -836,7 +993,7 @@ mod test {
 
         // Loan 2:
         let loan_create_msg2 = ExecuteMsg::CreateLoan {
-            user_id: "creator".to_string(),
+            user_id: addr("creator").to_string(),
             loan_requests: vec![loan_request_1.clone()],
         };
         let loan_res2 = execute(
@@ -849,7 +1006,7 @@ mod test {
 
         // Loan 3:
         let loan_create_msg3 = ExecuteMsg::CreateLoan {
-            user_id: "creator".to_string(),
+            user_id: addr("creator").to_string(),
             loan_requests: vec![loan_request_1.clone()],
         };
         let loan_res3 = execute(
@@ -861,20 +1018,20 @@ mod test {
         .unwrap();
 
         // assign loans to reviewer
-        let verifier: Addr = deps.api.addr_make("reviewer1");
-        let assign_to_reviewer_msg = ExecuteMsg::AssignLoansToReviewer {
+        let verifier: Addr = addr("reviewer1");
+        let assign_to_reviewer_msg = ExecuteMsg::AssignLoans {
             reviewer: verifier.to_string(),
             loans: vec![
                 (
-                    "creator".to_string(),
+                    addr("creator").to_string(),
                     loan_res1.attributes[0].value.to_string(),
                 ),
                 (
-                    "creator".to_string(),
+                    addr("creator").to_string(),
                     loan_res2.attributes[0].value.to_string(),
                 ),
                 (
-                    "creator".to_string(),
+                    addr("creator").to_string(),
                     loan_res3.attributes[0].value.to_string(),
                 ),
             ],
@@ -883,44 +1040,49 @@ mod test {
         let _res = execute(
             deps.as_mut(),
             env.clone(),
-            creator_info.clone(),
+            admin_info.clone(),
             assign_to_reviewer_msg,
         )
         .unwrap();
 
         // Loan 2: Approved
+        start_review(deps.as_mut(), &reviewer_info, addr("creator").to_string(), loan_res2.attributes[0].value.clone());
         let loan_update_msg2 = ExecuteMsg::UpdateLoanReviewStatus {
-            user_id: "creator".to_string(),
+            user_id: addr("creator").to_string(),
             loan_id: loan_res2.attributes[0].value.clone(),
-            new_status: ReviewStatus::Approved,
+            new_status: LoanStatus::Approved,
+            reason: None,
         };
         execute(
             deps.as_mut(),
             env.clone(),
-            creator_info.clone(),
+            reviewer_info.clone(),
             loan_update_msg2,
         )
         .unwrap();
 
         // Loan 3: Rejected
+        start_review(deps.as_mut(), &reviewer_info, addr("creator").to_string(), loan_res3.attributes[0].value.clone());
         let loan_update_msg3 = ExecuteMsg::UpdateLoanReviewStatus {
-            user_id: "creator".to_string(),
+            user_id: addr("creator").to_string(),
             loan_id: loan_res3.attributes[0].value.clone(),
-            new_status: ReviewStatus::Rejected,
+            new_status: LoanStatus::Rejected,
+            reason: None,
         };
         execute(
             deps.as_mut(),
             env.clone(),
-            creator_info.clone(),
+            reviewer_info.clone(),
             loan_update_msg3,
         )
         .unwrap();
 
         // Step 4: Query loan statistics for the user
         // Reviewer address
-        let verifier: Addr = deps.api.addr_make("reviewer1");
+        let verifier: Addr = addr("reviewer1");
         let loan_query_msg = QueryMsg::GetLoanStatistics {
             reviewer: Some(verifier.to_string()),
+            utc_offset_minutes: None,
         };
         let stats_bin = query(deps.as_ref(), mock_env().clone(), loan_query_msg).unwrap();
 
@@ -934,11 +1096,12 @@ mod test {
 
     #[test]
     fn test_query_all_reviewer_loan_statistics() {
-        let mut deps = mock_dependencies();
-        let creator1_info = message_info(&Addr::unchecked("creator1"), &[]);
-        let creator2_info = message_info(&Addr::unchecked("creator2"), &[]);
-        let reviewer1_info = message_info(&Addr::unchecked("reviewer1"), &[]);
-        let reviewer2_info = message_info(&Addr::unchecked("reviewer2"), &[]);
+        let mut deps = setup();
+        let admin_info = message_info(&addr("admin"), &[]);
+        let creator1_info = message_info(&addr("creator1"), &[]);
+        let creator2_info = message_info(&addr("creator2"), &[]);
+        let reviewer1_info = message_info(&addr("reviewer1"), &[]);
+        let reviewer2_info = message_info(&addr("reviewer2"), &[]);
 
         let env = mock_env();
 
@@ -948,6 +1111,8 @@ mod test {
             "amount".to_string(),
             FieldType::Number {
                 is_editable: false,
+                required: true,
+                default: None,
                 min_value: Some("50000.0".to_string()),
                 max_value: Some("1000000.0".to_string()),
             },
@@ -957,6 +1122,8 @@ mod test {
             "borrower".to_string(),
             FieldType::String {
                 is_editable: false,
+                required: true,
+                default: None,
                 format: None,
                 min_value: None,
                 max_value: None,
@@ -967,16 +1134,19 @@ mod test {
             template_id: "template1".to_string(),
             name: "Home Loan".to_string(),
             fields: fields.clone(),
+            rules: vec![],
         };
 
         let submit_msg = ExecuteMsg::SubmitTemplateForReview {
             template_id: "template1".to_string(),
-            reviewer: "reviewer1".to_string(),
+            reviewer: addr("reviewer1").to_string(),
         };
 
         let review_msg = ExecuteMsg::ReviewTemplate {
+            user_id: addr("creator1").to_string(),
             template_id: "template1".to_string(),
             approve: true,
+            comment: None,
         };
 
         execute(
@@ -1011,6 +1181,8 @@ mod test {
             "borrower2".to_string(),
             FieldType::String {
                 is_editable: false,
+                required: true,
+                default: None,
                 format: None,
                 min_value: None,
                 max_value: None,
@@ -1021,16 +1193,19 @@ mod test {
             template_id: "template2".to_string(),
             name: "Home Loan 2".to_string(),
             fields: fields2.clone(),
+            rules: vec![],
         };
 
         let submit_template_msg1 = ExecuteMsg::SubmitTemplateForReview {
             template_id: "template2".to_string(),
-            reviewer: "reviewer2".to_string(),
+            reviewer: addr("reviewer2").to_string(),
         };
 
         let review_template_msg1 = ExecuteMsg::ReviewTemplate {
+            user_id: addr("creator2").to_string(),
             template_id: "template2".to_string(),
             approve: true,
+            comment: None,
         };
 
         execute(
@@ -1062,14 +1237,15 @@ mod test {
             template_id: "template1".to_string(),
             values: {
                 let mut values = HashMap::new();
-                values.insert("amount".to_string(), "60000".to_string());
-                values.insert("borrower".to_string(), "John Doe".to_string());
+                values.insert("amount".to_string(), "60000".into());
+                values.insert("borrower".to_string(), "John Doe".into());
                 values
             },
+            draft: false,
         };
 
         let loan_create_msg1 = ExecuteMsg::CreateLoan {
-            user_id: "creator1".to_string(),
+            user_id: addr("creator1").to_string(),
             loan_requests: vec![loan_request_1.clone()],
         };
 
@@ -1084,7 +1260,7 @@ mod test {
 
         // Loan 2:
         let loan_create_msg2 = ExecuteMsg::CreateLoan {
-            user_id: "creator1".to_string(),
+            user_id: addr("creator1").to_string(),
             loan_requests: vec![loan_request_1.clone()],
         };
         let loan_res2 = execute(
@@ -1097,7 +1273,7 @@ mod test {
 
         // Loan 3:
         let loan_create_msg3 = ExecuteMsg::CreateLoan {
-            user_id: "creator1".to_string(),
+            user_id: addr("creator1").to_string(),
             loan_requests: vec![loan_request_1.clone()],
         };
         let loan_res3 = execute(
@@ -1109,20 +1285,20 @@ mod test {
         .unwrap();
 
         // assign loans to reviewer
-        let verifier: Addr = deps.api.addr_make("reviewer1");
-        let assign_to_reviewer_msg = ExecuteMsg::AssignLoansToReviewer {
+        let verifier: Addr = addr("reviewer1");
+        let assign_to_reviewer_msg = ExecuteMsg::AssignLoans {
             reviewer: verifier.to_string(),
             loans: vec![
                 (
-                    "creator1".to_string(),
+                    addr("creator1").to_string(),
                     loan_res1.attributes[0].value.to_string(),
                 ),
                 (
-                    "creator1".to_string(),
+                    addr("creator1").to_string(),
                     loan_res2.attributes[0].value.to_string(),
                 ),
                 (
-                    "creator1".to_string(),
+                    addr("creator1").to_string(),
                     loan_res3.attributes[0].value.to_string(),
                 ),
             ],
@@ -1131,35 +1307,39 @@ mod test {
         let _res = execute(
             deps.as_mut(),
             env.clone(),
-            creator1_info.clone(),
+            admin_info.clone(),
             assign_to_reviewer_msg,
         )
         .unwrap();
 
         // Loan 2: Approved
+        start_review(deps.as_mut(), &reviewer1_info, addr("creator1").to_string(), loan_res2.attributes[0].value.clone());
         let loan_update_msg2 = ExecuteMsg::UpdateLoanReviewStatus {
-            user_id: "creator1".to_string(),
+            user_id: addr("creator1").to_string(),
             loan_id: loan_res2.attributes[0].value.clone(),
-            new_status: ReviewStatus::Approved,
+            new_status: LoanStatus::Approved,
+            reason: None,
         };
         execute(
             deps.as_mut(),
             env.clone(),
-            creator1_info.clone(),
+            reviewer1_info.clone(),
             loan_update_msg2,
         )
         .unwrap();
 
         // Loan 3: Rejected
+        start_review(deps.as_mut(), &reviewer1_info, addr("creator1").to_string(), loan_res3.attributes[0].value.clone());
         let loan_update_msg3 = ExecuteMsg::UpdateLoanReviewStatus {
-            user_id: "creator1".to_string(),
+            user_id: addr("creator1").to_string(),
             loan_id: loan_res3.attributes[0].value.clone(),
-            new_status: ReviewStatus::Rejected,
+            new_status: LoanStatus::Rejected,
+            reason: None,
         };
         execute(
             deps.as_mut(),
             env.clone(),
-            creator1_info.clone(),
+            reviewer1_info.clone(),
             loan_update_msg3,
         )
         .unwrap();
@@ -1170,14 +1350,15 @@ mod test {
             template_id: "template2".to_string(),
             values: {
                 let mut values = HashMap::new();
-                values.insert("amount".to_string(), "60000".to_string());
-                values.insert("borrower".to_string(), "John Doe".to_string());
-                values.insert("borrower2".to_string(), "Frank Doe".to_string());
+                values.insert("amount".to_string(), "60000".into());
+                values.insert("borrower".to_string(), "John Doe".into());
+                values.insert("borrower2".to_string(), "Frank Doe".into());
                 values
             },
+            draft: false,
         };
         let loan_create_msg4 = ExecuteMsg::CreateLoan {
-            user_id: "creator2".to_string(),
+            user_id: addr("creator2").to_string(),
             loan_requests: vec![loan_request_4.clone()],
         };
         let loan_res4 = execute(
@@ -1189,11 +1370,11 @@ mod test {
         .unwrap();
 
         // assign loan 4 to reviewer
-        let verifier2: Addr = deps.api.addr_make("reviewer2");
-        let assign_to_reviewer2_msg1 = ExecuteMsg::AssignLoansToReviewer {
+        let verifier2: Addr = addr("reviewer2");
+        let assign_to_reviewer2_msg1 = ExecuteMsg::AssignLoans {
             reviewer: verifier2.to_string(),
             loans: vec![(
-                "creator2".to_string(),
+                addr("creator2").to_string(),
                 loan_res4.attributes[0].value.to_string(),
             )],
         };
@@ -1201,7 +1382,7 @@ mod test {
         execute(
             deps.as_mut(),
             env.clone(),
-            creator2_info.clone(),
+            admin_info.clone(),
             assign_to_reviewer2_msg1,
         )
         .unwrap();
@@ -1219,4 +1400,1452 @@ mod test {
         assert_eq!(stats.total_approved, 1);
         assert_eq!(stats.total_rejected, 1);
     }
+
+    #[test]
+    fn test_loan_lifecycle_records_each_transition() {
+        let mut deps = setup();
+        create_approved_template(&mut deps);
+        let loan_id = create_test_loan(&mut deps, false);
+        assign_loan(deps.as_mut(), "reviewer1", addr("creator").to_string(), loan_id.clone());
+
+        transition(&mut deps, "reviewer1", &loan_id, LoanStatus::UnderReview).unwrap();
+        transition(&mut deps, "reviewer1", &loan_id, LoanStatus::Approved).unwrap();
+        transition(&mut deps, "creator", &loan_id, LoanStatus::Funded).unwrap();
+        transition(&mut deps, "creator", &loan_id, LoanStatus::Repaying).unwrap();
+        transition(&mut deps, "creator", &loan_id, LoanStatus::Closed).unwrap();
+
+        let loan = LOAN_STORAGE.load(&deps.storage, (addr("creator").as_str(), &loan_key(&loan_id))).unwrap();
+        assert_eq!(loan.status, LoanStatus::Closed);
+        let statuses: Vec<LoanStatus> = loan.status_history.iter().map(|t| t.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                LoanStatus::Submitted,
+                LoanStatus::UnderReview,
+                LoanStatus::Approved,
+                LoanStatus::Funded,
+                LoanStatus::Repaying,
+                LoanStatus::Closed,
+            ]
+        );
+        assert!(loan.status_history.iter().all(|t| t.timestamp == mock_env().block.time.seconds()));
+    }
+
+    #[test]
+    fn test_rejected_loan_cannot_be_approved() {
+        let mut deps = setup();
+        create_approved_template(&mut deps);
+        let loan_id = create_test_loan(&mut deps, false);
+        assign_loan(deps.as_mut(), "reviewer1", addr("creator").to_string(), loan_id.clone());
+
+        transition(&mut deps, "reviewer1", &loan_id, LoanStatus::UnderReview).unwrap();
+        transition(&mut deps, "reviewer1", &loan_id, LoanStatus::Rejected).unwrap();
+        let err = transition(&mut deps, "reviewer1", &loan_id, LoanStatus::Approved).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidTransition {
+                from: LoanStatus::Rejected,
+                to: LoanStatus::Approved,
+            }
+        );
+
+        let loan = LOAN_STORAGE.load(&deps.storage, (addr("creator").as_str(), &loan_key(&loan_id))).unwrap();
+        assert!(loan.approval_date.is_none());
+        assert!(loan.rejection_date.is_some());
+    }
+
+    #[test]
+    fn test_draft_loan_must_be_submitted_before_review() {
+        let mut deps = setup();
+        create_approved_template(&mut deps);
+        let loan_id = create_test_loan(&mut deps, true);
+        assign_loan(deps.as_mut(), "reviewer1", addr("creator").to_string(), loan_id.clone());
+
+        let err = transition(&mut deps, "reviewer1", &loan_id, LoanStatus::UnderReview).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidTransition {
+                from: LoanStatus::Draft,
+                to: LoanStatus::UnderReview,
+            }
+        );
+
+        transition(&mut deps, "creator", &loan_id, LoanStatus::Submitted).unwrap();
+        transition(&mut deps, "reviewer1", &loan_id, LoanStatus::UnderReview).unwrap();
+    }
+
+    #[test]
+    fn test_review_steps_require_reviewer_role() {
+        let mut deps = setup();
+        create_approved_template(&mut deps);
+        let loan_id = create_test_loan(&mut deps, false);
+
+        let err = transition(&mut deps, "creator", &loan_id, LoanStatus::UnderReview).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let err = transition(&mut deps, "reviewer1", "missing", LoanStatus::UnderReview)
+            .unwrap_err();
+        assert_eq!(err, ContractError::LoanNotFound {});
+    }
+
+    #[test]
+    fn test_officer_steps_belong_to_loan_owner_or_admin() {
+        let mut deps = setup();
+        create_approved_template(&mut deps);
+        let draft_id = create_test_loan(&mut deps, true);
+        let loan_id = create_test_loan(&mut deps, false);
+
+        // Another loan officer cannot move the loan
+        let err = transition(&mut deps, "creator1", &draft_id, LoanStatus::Submitted).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let err = transition(&mut deps, "creator1", &loan_id, LoanStatus::Cancelled).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        transition(&mut deps, "creator", &draft_id, LoanStatus::Submitted).unwrap();
+        transition(&mut deps, "admin", &loan_id, LoanStatus::Cancelled).unwrap();
+    }
+
+    #[test]
+    fn test_decision_records_reviewer_and_reason() {
+        let mut deps = setup();
+        create_approved_template(&mut deps);
+        let loan_id = create_test_loan(&mut deps, false);
+        assign_loan(deps.as_mut(), "reviewer1", addr("creator").to_string(), loan_id.clone());
+
+        transition(&mut deps, "reviewer1", &loan_id, LoanStatus::UnderReview).unwrap();
+        let msg = ExecuteMsg::UpdateLoanReviewStatus {
+            user_id: addr("creator").to_string(),
+            loan_id: loan_id.clone(),
+            new_status: LoanStatus::Rejected,
+            reason: Some("Insufficient income".to_string()),
+        };
+        execute(deps.as_mut(), mock_env(), message_info(&addr("reviewer1"), &[]), msg).unwrap();
+
+        let loan = LOAN_STORAGE.load(&deps.storage, (addr("creator").as_str(), &loan_key(&loan_id))).unwrap();
+        assert_eq!(loan.decided_by, Some(addr("reviewer1")));
+        assert_eq!(loan.decision_reason, Some("Insufficient income".to_string()));
+    }
+
+    #[test]
+    fn test_only_assigned_reviewer_or_admin_can_decide() {
+        let mut deps = setup();
+        create_approved_template(&mut deps);
+        let loan_id = create_test_loan(&mut deps, false);
+        assign_loan(deps.as_mut(), "reviewer1", addr("creator").to_string(), loan_id.clone());
+
+        let err = transition(&mut deps, "reviewer2", &loan_id, LoanStatus::UnderReview).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::NotAssignedReviewer {
+                loan_id: loan_id.clone(),
+            }
+        );
+
+        // Admins may step in without an assignment
+        transition(&mut deps, "reviewer1", &loan_id, LoanStatus::UnderReview).unwrap();
+        transition(&mut deps, "admin", &loan_id, LoanStatus::Approved).unwrap();
+        let loan = LOAN_STORAGE.load(&deps.storage, (addr("creator").as_str(), &loan_key(&loan_id))).unwrap();
+        assert_eq!(loan.decided_by, Some(addr("admin")));
+    }
+
+    #[test]
+    fn test_reviewer_cannot_decide_own_loan() {
+        let mut deps = setup();
+        create_approved_template(&mut deps);
+
+        // The creator also holds the Reviewer role and is assigned their own loan
+        let grant_msg = ExecuteMsg::GrantRole {
+            role: crate::models::Role::Reviewer,
+            address: addr("creator").to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), message_info(&addr("admin"), &[]), grant_msg).unwrap();
+        let loan_id = create_test_loan(&mut deps, false);
+        assign_loan(deps.as_mut(), "creator", addr("creator").to_string(), loan_id.clone());
+
+        let err = transition(&mut deps, "creator", &loan_id, LoanStatus::UnderReview).unwrap_err();
+        assert_eq!(err, ContractError::SelfReviewNotAllowed {});
+    }
+
+    #[test]
+    fn test_assign_loans_is_incremental() {
+        let mut deps = setup();
+        create_approved_template(&mut deps);
+        let loan1 = create_test_loan(&mut deps, false);
+        let loan2 = create_test_loan(&mut deps, false);
+
+        assign_loan(deps.as_mut(), "reviewer1", addr("creator").to_string(), loan1.clone());
+        assign_loan(deps.as_mut(), "reviewer1", addr("creator").to_string(), loan2.clone());
+        // Assigning the same loan twice to the same reviewer is a no-op
+        assign_loan(deps.as_mut(), "reviewer1", addr("creator").to_string(), loan1.clone());
+
+        let assigned_loans = loans_for_reviewer(&deps, &addr("reviewer1"));
+        assert_eq!(
+            assigned_loans,
+            vec![
+                (addr("creator").to_string(), loan1.clone()),
+                (addr("creator").to_string(), loan2),
+            ]
+        );
+
+        let reviewer_bin = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetLoanReviewer {
+                user_id: addr("creator").to_string(),
+                loan_id: loan1,
+            },
+        )
+        .unwrap();
+        let reviewer: Option<Addr> = from_json(&reviewer_bin).unwrap();
+        assert_eq!(reviewer, Some(addr("reviewer1")));
+    }
+
+    #[test]
+    fn test_assign_loans_validates_loans_and_single_reviewer() {
+        let mut deps = setup();
+        create_approved_template(&mut deps);
+        let loan_id = create_test_loan(&mut deps, false);
+        let admin_info = message_info(&addr("admin"), &[]);
+
+        let msg = ExecuteMsg::AssignLoans {
+            reviewer: addr("reviewer1").to_string(),
+            loans: vec![(addr("creator").to_string(), "missing".to_string())],
+        };
+        let err = execute(deps.as_mut(), mock_env(), admin_info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::LoanNotFound {});
+
+        assign_loan(deps.as_mut(), "reviewer1", addr("creator").to_string(), loan_id.clone());
+        let msg = ExecuteMsg::AssignLoans {
+            reviewer: addr("reviewer2").to_string(),
+            loans: vec![(addr("creator").to_string(), loan_id.clone())],
+        };
+        let err = execute(deps.as_mut(), mock_env(), admin_info, msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::LoanAlreadyAssigned {
+                loan_id,
+                reviewer: addr("reviewer1").to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_unassign_and_reassign_loans() {
+        let mut deps = setup();
+        create_approved_template(&mut deps);
+        let loan1 = create_test_loan(&mut deps, false);
+        let loan2 = create_test_loan(&mut deps, false);
+        let admin_info = message_info(&addr("admin"), &[]);
+        assign_loan(deps.as_mut(), "reviewer1", addr("creator").to_string(), loan1.clone());
+        assign_loan(deps.as_mut(), "reviewer1", addr("creator").to_string(), loan2.clone());
+
+        let msg = ExecuteMsg::UnassignLoans {
+            reviewer: addr("reviewer1").to_string(),
+            loans: vec![(addr("creator").to_string(), loan1.clone())],
+        };
+        execute(deps.as_mut(), mock_env(), admin_info.clone(), msg).unwrap();
+        let assigned_loans = loans_for_reviewer(&deps, &addr("reviewer1"));
+        assert_eq!(assigned_loans, vec![(addr("creator").to_string(), loan2.clone())]);
+        let loan = LOAN_STORAGE.load(&deps.storage, (addr("creator").as_str(), &loan_key(&loan1))).unwrap();
+        assert_eq!(loan.reviewer, None);
+
+        let msg = ExecuteMsg::ReassignLoan {
+            user_id: addr("creator").to_string(),
+            loan_id: loan2.clone(),
+            from: addr("reviewer1").to_string(),
+            to: addr("reviewer2").to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), admin_info.clone(), msg).unwrap();
+        assert!(loans_for_reviewer(&deps, &addr("reviewer1")).is_empty());
+        let loan = LOAN_STORAGE.load(&deps.storage, (addr("creator").as_str(), &loan_key(&loan2))).unwrap();
+        assert_eq!(loan.reviewer, Some(addr("reviewer2")));
+
+        // The previous reviewer can no longer decide on the loan
+        let err = transition(&mut deps, "reviewer1", &loan2, LoanStatus::UnderReview).unwrap_err();
+        assert_eq!(err, ContractError::NotAssignedReviewer { loan_id: loan2.clone() });
+        transition(&mut deps, "reviewer2", &loan2, LoanStatus::UnderReview).unwrap();
+
+        let msg = ExecuteMsg::ReassignLoan {
+            user_id: addr("creator").to_string(),
+            loan_id: loan2.clone(),
+            from: addr("reviewer1").to_string(),
+            to: addr("reviewer2").to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), admin_info, msg).unwrap_err();
+        assert_eq!(err, ContractError::NotAssignedReviewer { loan_id: loan2 });
+    }
+
+    #[test]
+    fn test_decided_loans_keep_their_reviewer() {
+        let mut deps = setup();
+        create_approved_template(&mut deps);
+        let loan_id = create_test_loan(&mut deps, false);
+        let creator = addr("creator").to_string();
+        let admin_info = message_info(&addr("admin"), &[]);
+        assign_loan(deps.as_mut(), "reviewer1", creator.clone(), loan_id.clone());
+        transition(&mut deps, "reviewer1", &loan_id, LoanStatus::UnderReview).unwrap();
+        transition(&mut deps, "reviewer1", &loan_id, LoanStatus::Approved).unwrap();
+
+        let closed = ContractError::AssignmentClosed {
+            loan_id: loan_id.clone(),
+            status: LoanStatus::Approved,
+        };
+        let msg = ExecuteMsg::UnassignLoans {
+            reviewer: addr("reviewer1").to_string(),
+            loans: vec![(creator.clone(), loan_id.clone())],
+        };
+        assert_eq!(execute(deps.as_mut(), mock_env(), admin_info.clone(), msg).unwrap_err(), closed);
+        let msg = ExecuteMsg::ReassignLoan {
+            user_id: creator.clone(),
+            loan_id: loan_id.clone(),
+            from: addr("reviewer1").to_string(),
+            to: addr("reviewer2").to_string(),
+        };
+        assert_eq!(execute(deps.as_mut(), mock_env(), admin_info.clone(), msg).unwrap_err(), closed);
+        let loan = LOAN_STORAGE.load(&deps.storage, (creator.as_str(), &loan_key(&loan_id))).unwrap();
+        assert_eq!(loan.reviewer, Some(addr("reviewer1")));
+
+        // Nor can a finished loan that never had a reviewer be given one
+        let unassigned = create_test_loan(&mut deps, false);
+        transition(&mut deps, "creator", &unassigned, LoanStatus::Cancelled).unwrap();
+        let msg = ExecuteMsg::AssignLoans {
+            reviewer: addr("reviewer2").to_string(),
+            loans: vec![(creator, unassigned.clone())],
+        };
+        let err = execute(deps.as_mut(), mock_env(), admin_info, msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::AssignmentClosed {
+                loan_id: unassigned,
+                status: LoanStatus::Cancelled,
+            }
+        );
+    }
+
+    #[test]
+    fn test_loan_ids_are_sequential_and_predictable() {
+        let mut deps = setup();
+        create_approved_template(&mut deps);
+
+        let next_bin = query(deps.as_ref(), mock_env(), QueryMsg::NextLoanId {}).unwrap();
+        let next_id: String = from_json(&next_bin).unwrap();
+        assert_eq!(next_id, "1");
+
+        // Several loans in one message consume consecutive IDs
+        let loan_request = LoanRequest {
+            template_id: "template1".to_string(),
+            values: HashMap::from([("amount".to_string(), "60000".into())]),
+            draft: false,
+        };
+        let msg = ExecuteMsg::CreateLoan {
+            user_id: addr("creator").to_string(),
+            loan_requests: vec![loan_request.clone(), loan_request],
+        };
+        let res = execute(deps.as_mut(), mock_env(), message_info(&addr("creator"), &[]), msg).unwrap();
+        assert_eq!(res.attributes[0], attr("created_loan_id", "1"));
+        assert_eq!(res.attributes[1], attr("created_loan_id", "2"));
+        assert_eq!(create_test_loan(&mut deps, false), "3");
+
+        let next_bin = query(deps.as_ref(), mock_env(), QueryMsg::NextLoanId {}).unwrap();
+        let next_id: String = from_json(&next_bin).unwrap();
+        assert_eq!(next_id, "4");
+    }
+
+    #[test]
+    fn test_loan_indexes_follow_status_and_decision_changes() {
+        let mut deps = setup();
+        create_approved_template(&mut deps);
+        let created_at = mock_env().block.time.seconds();
+        let loan1 = create_test_loan(&mut deps, false);
+        let loan2 = create_test_loan(&mut deps, false);
+        let loan3 = create_test_loan(&mut deps, false);
+        let creator = addr("creator").to_string();
+
+        // Decide loan1 and loan2 at different times
+        for (loan_id, decision, delay) in [
+            (&loan1, LoanStatus::Approved, 100),
+            (&loan2, LoanStatus::Rejected, 200),
+        ] {
+            for new_status in [LoanStatus::UnderReview, decision] {
+                let mut env = mock_env();
+                env.block.time = env.block.time.plus_seconds(delay);
+                let msg = ExecuteMsg::UpdateLoanReviewStatus {
+                    user_id: creator.clone(),
+                    loan_id: loan_id.clone(),
+                    new_status,
+                    reason: None,
+                };
+                execute(deps.as_mut(), env, message_info(&addr("admin"), &[]), msg).unwrap();
+            }
+        }
+
+        let loans = |msg: QueryMsg| -> Vec<(String, String)> {
+            let page: Page<LoanSummary, (String, String)> =
+                from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            page.items.into_iter().map(|summary| (summary.owner, summary.loan_id)).collect()
+        };
+        let by_status = |status| {
+            loans(QueryMsg::GetLoansByStatus {
+                status,
+                include_values: None,
+                start_after: None,
+                limit: None,
+                order: None,
+            })
+        };
+        let by_date = |from_date, date_type: &str| {
+            loans(QueryMsg::GetLoansByDate {
+                from_date,
+                date_type: date_type.to_string(),
+                include_values: None,
+                start_after: None,
+                limit: None,
+                order: None,
+            })
+        };
+
+        // Earlier statuses drop out of the index as loans move on
+        assert_eq!(by_status(LoanStatus::Submitted), vec![(creator.clone(), loan3.clone())]);
+        assert!(by_status(LoanStatus::UnderReview).is_empty());
+        assert_eq!(by_status(LoanStatus::Approved), vec![(creator.clone(), loan1.clone())]);
+        assert_eq!(by_status(LoanStatus::Rejected), vec![(creator.clone(), loan2.clone())]);
+
+        assert_eq!(by_date(created_at, "created").len(), 3);
+        assert!(by_date(created_at + 1, "created").is_empty());
+        assert_eq!(by_date(created_at + 100, "approved"), vec![(creator.clone(), loan1)]);
+        assert!(by_date(created_at + 101, "approved").is_empty());
+        assert_eq!(by_date(created_at + 150, "rejected"), vec![(creator.clone(), loan2)]);
+
+        let by_date_err = |date_type: &str, start_after: Option<(String, String)>| {
+            let msg = QueryMsg::GetLoansByDate {
+                from_date: 0,
+                date_type: date_type.to_string(),
+                include_values: None,
+                start_after,
+                limit: None,
+                order: None,
+            };
+            query(deps.as_ref(), mock_env(), msg).unwrap_err().to_string()
+        };
+        assert!(by_date_err("unknown", None).contains("Unknown date type 'unknown'"));
+        // A cursor loan missing from the date index cannot restart the scan from the beginning
+        assert!(by_date_err("approved", Some((creator, loan3.clone()))).contains(&format!(
+            "Cursor loan '{}' has no Approved date",
+            loan3
+        )));
+    }
+
+    #[test]
+    fn test_loan_queries_paginate_in_both_directions() {
+        let mut deps = setup();
+        create_approved_template(&mut deps);
+        for _ in 0..3 {
+            create_test_loan(&mut deps, false);
+        }
+        let creator = addr("creator").to_string();
+
+        let loans_for_user = |start_after: Option<&str>, order| {
+            let msg = QueryMsg::GetLoansForUser {
+                user_id: creator.clone(),
+                include_values: None,
+                start_after: start_after.map(str::to_string),
+                limit: Some(2),
+                order,
+            };
+            let page: Page<LoanSummary, String> =
+                from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            let ids: Vec<String> = page.items.into_iter().map(|loan| loan.loan_id).collect();
+            (ids, page.next_start_after)
+        };
+        assert_eq!(loans_for_user(None, None), (vec!["1".to_string(), "2".to_string()], Some("2".to_string())));
+        assert_eq!(loans_for_user(Some("2"), None), (vec!["3".to_string()], None));
+        assert_eq!(
+            loans_for_user(None, Some(SortOrder::Descending)),
+            (vec!["3".to_string(), "2".to_string()], Some("2".to_string()))
+        );
+        assert_eq!(
+            loans_for_user(Some("2"), Some(SortOrder::Descending)),
+            (vec!["1".to_string()], None)
+        );
+
+        // Loans created in the same block share an index date and page by primary key
+        let by_date = |start_after: Option<(String, String)>, order| {
+            let msg = QueryMsg::GetLoansByDate {
+                from_date: 0,
+                date_type: "created".to_string(),
+                include_values: None,
+                start_after,
+                limit: Some(2),
+                order,
+            };
+            let page: Page<LoanSummary, (String, String)> =
+                from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            let keys: Vec<(String, String)> = page
+                .items
+                .into_iter()
+                .map(|summary| (summary.owner, summary.loan_id))
+                .collect();
+            (keys, page.next_start_after)
+        };
+        let (first, cursor) = by_date(None, None);
+        assert_eq!(first.len(), 2);
+        let cursor = cursor.unwrap();
+        assert_eq!(cursor, (creator.clone(), "2".to_string()));
+        let (second, next) = by_date(Some(cursor.clone()), None);
+        assert_eq!(second, vec![(creator.clone(), "3".to_string())]);
+        assert_eq!(next, None);
+        let (backwards, _) = by_date(Some(cursor), Some(SortOrder::Descending));
+        assert_eq!(backwards, vec![(creator, "1".to_string())]);
+    }
+
+    #[test]
+    fn test_loan_pages_follow_numeric_id_order() {
+        let mut deps = setup();
+        create_approved_template(&mut deps);
+        for _ in 0..12 {
+            create_test_loan(&mut deps, false);
+        }
+        let creator = addr("creator").to_string();
+        let ids = |range: std::ops::RangeInclusive<u32>| range.map(|id| id.to_string()).collect::<Vec<_>>();
+
+        let loans_for_user = |start_after: Option<&str>, order| {
+            let msg = QueryMsg::GetLoansForUser {
+                user_id: creator.clone(),
+                include_values: None,
+                start_after: start_after.map(str::to_string),
+                limit: Some(5),
+                order,
+            };
+            let page: Page<LoanSummary, String> =
+                from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            let ids: Vec<String> = page.items.into_iter().map(|loan| loan.loan_id).collect();
+            (ids, page.next_start_after)
+        };
+        // "10" comes after "9", not after "1"
+        assert_eq!(loans_for_user(None, None), (ids(1..=5), Some("5".to_string())));
+        assert_eq!(loans_for_user(Some("5"), None), (ids(6..=10), Some("10".to_string())));
+        assert_eq!(loans_for_user(Some("10"), None), (ids(11..=12), None));
+        let (backwards, _) = loans_for_user(Some("11"), Some(SortOrder::Descending));
+        assert_eq!(backwards, ids(6..=10).into_iter().rev().collect::<Vec<_>>());
+
+        // Index pages continue after the cursor loan in the same order
+        let msg = QueryMsg::GetLoansByStatus {
+            status: LoanStatus::Submitted,
+            include_values: None,
+            start_after: Some((creator.clone(), "9".to_string())),
+            limit: None,
+            order: None,
+        };
+        let page: Page<LoanSummary, (String, String)> =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        let loan_ids: Vec<String> = page.items.into_iter().map(|loan| loan.loan_id).collect();
+        assert_eq!(loan_ids, ids(10..=12));
+    }
+
+    #[test]
+    fn test_template_queries_cap_page_size() {
+        let mut deps = setup();
+        let creator_info = message_info(&addr("creator"), &[]);
+        for i in 0..35 {
+            let template_id = format!("template{:02}", i);
+            let msg = ExecuteMsg::CreateLoanTemplate {
+                template_id: template_id.clone(),
+                name: "Home Loan".to_string(),
+                fields: HashMap::new(),
+                rules: vec![],
+            };
+            execute(deps.as_mut(), mock_env(), creator_info.clone(), msg).unwrap();
+            let msg = ExecuteMsg::SubmitTemplateForReview {
+                template_id,
+                reviewer: addr("reviewer1").to_string(),
+            };
+            execute(deps.as_mut(), mock_env(), creator_info.clone(), msg).unwrap();
+        }
+
+        // Without a limit the default page size applies
+        let msg = QueryMsg::GetUserTemplates {
+            user_id: addr("creator").to_string(),
+            start_after: None,
+            limit: None,
+            order: None,
+        };
+        let page: Page<LoanTemplate, String> =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(page.items.len(), 10);
+        assert_eq!(page.next_start_after, Some("template09".to_string()));
+
+        // Larger limits are capped at the hard maximum
+        let msg = QueryMsg::GetReviewerTemplates {
+            reviewer: addr("reviewer1").to_string(),
+            start_after: Some((addr("creator").to_string(), "template02".to_string())),
+            limit: Some(100),
+            order: None,
+        };
+        let page: Page<LoanTemplate, (String, String)> =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(page.items.len(), 30);
+        assert_eq!(page.items[0].id, "template03");
+        assert_eq!(
+            page.next_start_after,
+            Some((addr("creator").to_string(), "template32".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_search_loans_combines_filters() {
+        let mut deps = setup();
+        create_approved_template(&mut deps);
+        let creator = addr("creator").to_string();
+
+        // Loans 1-4 ask for increasing amounts
+        for amount in ["60000", "80000", "120000", "200000"] {
+            let msg = ExecuteMsg::CreateLoan {
+                user_id: creator.clone(),
+                loan_requests: vec![LoanRequest {
+                    template_id: "template1".to_string(),
+                    values: HashMap::from([("amount".to_string(), amount.into())]),
+                    draft: false,
+                }],
+            };
+            execute(deps.as_mut(), mock_env(), message_info(&addr("creator"), &[]), msg).unwrap();
+        }
+        assign_loan(deps.as_mut(), "reviewer1", creator.clone(), "2".to_string());
+        assign_loan(deps.as_mut(), "reviewer1", creator.clone(), "3".to_string());
+        transition(&mut deps, "reviewer1", "3", LoanStatus::UnderReview).unwrap();
+        transition(&mut deps, "reviewer1", "3", LoanStatus::Approved).unwrap();
+        transition(&mut deps, "admin", "4", LoanStatus::UnderReview).unwrap();
+
+        let search = |filter: LoanFilter, start_after: Option<(String, String)>, limit| {
+            let msg = QueryMsg::SearchLoans {
+                filter,
+                include_values: None,
+                start_after,
+                limit,
+                order: None,
+            };
+            let page: Page<LoanSummary, (String, String)> =
+                from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            let ids: Vec<String> = page.items.into_iter().map(|summary| summary.loan_id).collect();
+            (ids, page.next_start_after)
+        };
+        let amount_at_least = |value: &str| NumericPredicate {
+            field: "amount".to_string(),
+            op: Comparison::Gte,
+            value: value.to_string(),
+        };
+
+        // Status set AND amount predicate
+        let filter = LoanFilter {
+            statuses: vec![LoanStatus::Submitted, LoanStatus::UnderReview],
+            predicates: vec![amount_at_least("80000")],
+            ..LoanFilter::default()
+        };
+        assert_eq!(search(filter, None, None).0, vec!["2".to_string(), "4".to_string()]);
+
+        // Reviewer AND owner AND template
+        let filter = LoanFilter {
+            owner: Some(creator.clone()),
+            template_id: Some("template1".to_string()),
+            reviewer: Some(addr("reviewer1").to_string()),
+            ..LoanFilter::default()
+        };
+        assert_eq!(search(filter, None, None).0, vec!["2".to_string(), "3".to_string()]);
+
+        // Undecided loans have no approval date and never match an approval range
+        let now = mock_env().block.time.seconds();
+        let filter = LoanFilter {
+            date: Some(DateRange {
+                field: DateField::Approved,
+                from: None,
+                to: Some(now),
+            }),
+            ..LoanFilter::default()
+        };
+        assert_eq!(search(filter, None, None).0, vec!["3".to_string()]);
+        let filter = LoanFilter {
+            date: Some(DateRange {
+                field: DateField::Created,
+                from: Some(now),
+                to: Some(now),
+            }),
+            predicates: vec![NumericPredicate {
+                field: "amount".to_string(),
+                op: Comparison::Lt,
+                value: "150000".to_string(),
+            }],
+            ..LoanFilter::default()
+        };
+        assert_eq!(search(filter.clone(), None, None).0.len(), 3);
+
+        // Pages count matching loans only
+        let (first, cursor) = search(filter.clone(), None, Some(2));
+        assert_eq!(first, vec!["1".to_string(), "2".to_string()]);
+        let (second, cursor) = search(filter, cursor, Some(2));
+        assert_eq!(second, vec!["3".to_string()]);
+        assert_eq!(cursor, None);
+
+        let msg = QueryMsg::SearchLoans {
+            filter: LoanFilter {
+                predicates: vec![amount_at_least("a lot")],
+                ..LoanFilter::default()
+            },
+            include_values: None,
+            start_after: None,
+            limit: None,
+            order: None,
+        };
+        assert!(query(deps.as_ref(), mock_env(), msg).is_err());
+    }
+
+    #[test]
+    fn test_loan_statistics_follow_calendar_months() {
+        let mut deps = setup();
+        create_approved_template(&mut deps);
+        let approved_id = create_test_loan(&mut deps, false);
+        let rejected_id = create_test_loan(&mut deps, false);
+        for (loan_id, status) in [(&approved_id, LoanStatus::Approved), (&rejected_id, LoanStatus::Rejected)] {
+            assign_loan(deps.as_mut(), "reviewer1", addr("creator").to_string(), loan_id.clone());
+            transition(&mut deps, "reviewer1", loan_id, LoanStatus::UnderReview).unwrap();
+            transition(&mut deps, "reviewer1", loan_id, status).unwrap();
+        }
+
+        // Decisions either side of midnight UTC on 2024-03-01
+        let key = (addr("creator").to_string(), loan_key(&approved_id));
+        let mut loan = LOAN_STORAGE.load(&deps.storage, (&key.0, &key.1)).unwrap();
+        loan.creation_date = 1_705_276_800; // 2024-01-15T00:00:00Z
+        loan.approval_date = Some(1_709_247_600); // 2024-02-29T23:00:00Z
+        LOAN_STORAGE.save(&mut deps.storage, (&key.0, &key.1), &loan).unwrap();
+        let key = (addr("creator").to_string(), loan_key(&rejected_id));
+        let mut loan = LOAN_STORAGE.load(&deps.storage, (&key.0, &key.1)).unwrap();
+        loan.creation_date = 1_704_065_400; // 2023-12-31T23:30:00Z
+        loan.rejection_date = Some(1_709_254_800); // 2024-03-01T01:00:00Z
+        LOAN_STORAGE.save(&mut deps.storage, (&key.0, &key.1), &loan).unwrap();
+
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(1_709_258_400); // 2024-03-01T02:00:00Z
+        let stats = |utc_offset_minutes| -> StdResult<LoanStatistics> {
+            let msg = QueryMsg::GetLoanStatistics {
+                reviewer: Some(addr("reviewer1").to_string()),
+                utc_offset_minutes,
+            };
+            from_json(query(deps.as_ref(), env.clone(), msg)?)
+        };
+
+        let utc = stats(None).unwrap();
+        assert_eq!((utc.approved_this_month, utc.approved_last_month), (0, 1));
+        assert_eq!((utc.rejected_this_month, utc.rejected_last_month), (1, 0));
+        assert_eq!((utc.approved_this_quarter, utc.rejected_this_quarter), (1, 1));
+        assert_eq!((utc.approved_this_year, utc.rejected_this_year), (1, 1));
+        assert_eq!(utc.month_wise_status_count["2024-01"]["Approved"], 1);
+        assert_eq!(utc.month_wise_status_count["2023-12"]["Rejected"], 1);
+
+        // At UTC+03:00 both decisions fall on 1 March and the December loan moves into January
+        let east = stats(Some(180)).unwrap();
+        assert_eq!((east.approved_this_month, east.rejected_this_month), (1, 1));
+        assert_eq!(east.month_wise_status_count.len(), 1);
+        assert_eq!(east.month_wise_status_count["2024-01"].len(), 2);
+
+        // At UTC-02:00 it is just midnight, so both decisions were last month
+        let west = stats(Some(-120)).unwrap();
+        assert_eq!((west.approved_this_month, west.rejected_this_month), (0, 0));
+        assert_eq!((west.approved_last_month, west.rejected_last_month), (1, 1));
+        // Both still fall in the first quarter of 2024
+        assert_eq!((west.approved_this_quarter, west.rejected_this_quarter), (1, 1));
+
+        assert!(stats(Some(15 * 60)).is_err());
+    }
+
+    #[test]
+    fn test_loan_time_series_buckets_events_by_their_dates() {
+        let mut deps = setup();
+        create_approved_template(&mut deps);
+        let approved_id = create_test_loan(&mut deps, false);
+        let rejected_id = create_test_loan(&mut deps, false);
+        let pending_id = create_test_loan(&mut deps, false);
+        for (loan_id, status) in [(&approved_id, LoanStatus::Approved), (&rejected_id, LoanStatus::Rejected)] {
+            assign_loan(deps.as_mut(), "reviewer1", addr("creator").to_string(), loan_id.clone());
+            transition(&mut deps, "reviewer1", loan_id, LoanStatus::UnderReview).unwrap();
+            transition(&mut deps, "reviewer1", loan_id, status).unwrap();
+        }
+        let owner = addr("creator").to_string();
+        let set_dates = |deps: &mut Deps, loan_id: &str, created, decided| {
+            let key = loan_key(loan_id);
+            let mut loan = LOAN_STORAGE.load(&deps.storage, (&owner, &key)).unwrap();
+            loan.creation_date = created;
+            loan.approval_date = loan.approval_date.and(decided);
+            loan.rejection_date = loan.rejection_date.and(decided);
+            LOAN_STORAGE.save(&mut deps.storage, (&owner, &key), &loan).unwrap();
+        };
+        // Created 2024-02-27T10:00Z, approved three days later on Friday 2024-03-01
+        set_dates(&mut deps, &approved_id, 1_709_028_000, Some(1_709_287_200));
+        // Created 2024-01-31, rejected on Monday 2024-03-04
+        set_dates(&mut deps, &rejected_id, 1_706_659_200, Some(1_709_510_400));
+        // Created 2024-03-02 and still waiting
+        set_dates(&mut deps, &pending_id, 1_709_337_600, None);
+
+        let series = |from, to, granularity, group_by| -> StdResult<LoanTimeSeries> {
+            let msg = QueryMsg::GetLoanTimeSeries {
+                from,
+                to,
+                granularity,
+                group_by,
+            };
+            from_json(query(deps.as_ref(), mock_env(), msg)?)
+        };
+        let keys = |bucket: &TimeSeriesBucket| -> Vec<String> {
+            bucket.groups.iter().map(|group| group.key.clone()).collect()
+        };
+
+        // February and March 2024
+        let monthly = series(1_706_745_600, 1_711_929_599, Granularity::Month, GroupBy::Status).unwrap();
+        assert_eq!(monthly.buckets.len(), 2);
+        let (february, march) = (&monthly.buckets[0], &monthly.buckets[1]);
+        assert_eq!((february.label.as_str(), february.start), ("2024-02", 1_706_745_600));
+        // The January loan only shows up through its rejection
+        assert_eq!(keys(february), vec!["Approved".to_string()]);
+        assert_eq!(february.groups[0].created, 1);
+        assert_eq!(february.groups[0].approval_rate, None);
+        assert_eq!(march.label, "2024-03");
+        assert_eq!(keys(march), vec!["Approved".to_string(), "Rejected".to_string(), "Submitted".to_string()]);
+        assert_eq!(march.groups[0].approved, 1);
+        assert_eq!(march.groups[0].approval_rate, Some(Decimal::one()));
+        assert_eq!(march.groups[0].average_time_to_decision, Some(3 * 86_400));
+        assert_eq!(march.groups[1].rejected, 1);
+        assert_eq!(march.groups[2].created, 1);
+
+        let by_reviewer = series(1_706_745_600, 1_711_929_599, Granularity::Month, GroupBy::Reviewer).unwrap();
+        let march = &by_reviewer.buckets[1];
+        assert_eq!(keys(march), vec![String::new(), addr("reviewer1").to_string()]);
+        let reviewed = &march.groups[1];
+        assert_eq!((reviewed.approved, reviewed.rejected), (1, 1));
+        assert_eq!(reviewed.approval_rate, Some(Decimal::percent(50)));
+        assert_eq!(reviewed.average_time_to_decision, Some((259_200 + 2_851_200) / 2));
+        assert_eq!(reviewed.median_time_to_decision, reviewed.average_time_to_decision);
+
+        // Weeks run Monday to Sunday, so the window covers two of them
+        let weekly = series(1_709_028_000, 1_709_510_400, Granularity::Week, GroupBy::Template).unwrap();
+        let labels: Vec<&str> = weekly.buckets.iter().map(|bucket| bucket.label.as_str()).collect();
+        assert_eq!(labels, vec!["2024-02-26", "2024-03-04"]);
+        let first = &weekly.buckets[0].groups[0];
+        assert_eq!((first.key.as_str(), first.created, first.approved), ("template1", 2, 1));
+        assert_eq!(weekly.buckets[1].groups[0].rejected, 1);
+
+        // Quiet days are still listed
+        let daily = series(1_709_251_200, 1_709_596_799, Granularity::Day, GroupBy::Status).unwrap();
+        assert_eq!(daily.buckets.len(), 4);
+        assert!(daily.buckets[2].groups.is_empty());
+
+        assert!(series(1_709_510_400, 1_709_028_000, Granularity::Day, GroupBy::Status).is_err());
+        assert!(series(0, 1_709_510_400, Granularity::Day, GroupBy::Status).is_err());
+    }
+
+    #[test]
+    fn test_running_counters_match_recompute_and_loan_scans() {
+        let mut deps = setup();
+        create_approved_template(&mut deps);
+        let ids: Vec<String> = (0..6).map(|_| create_test_loan(&mut deps, false)).collect();
+        let creator = addr("creator").to_string();
+
+        // Mix of assignments, reassignments and decisions
+        assign_loan(deps.as_mut(), "reviewer1", creator.clone(), ids[0].clone());
+        assign_loan(deps.as_mut(), "reviewer1", creator.clone(), ids[1].clone());
+        assign_loan(deps.as_mut(), "reviewer2", creator.clone(), ids[2].clone());
+        assign_loan(deps.as_mut(), "reviewer2", creator.clone(), ids[3].clone());
+        transition(&mut deps, "reviewer1", &ids[0], LoanStatus::UnderReview).unwrap();
+        transition(&mut deps, "reviewer1", &ids[0], LoanStatus::Approved).unwrap();
+        transition(&mut deps, "creator", &ids[0], LoanStatus::Funded).unwrap();
+        transition(&mut deps, "reviewer2", &ids[2], LoanStatus::UnderReview).unwrap();
+        transition(&mut deps, "reviewer2", &ids[2], LoanStatus::Rejected).unwrap();
+        let msg = ExecuteMsg::ReassignLoan {
+            user_id: creator.clone(),
+            loan_id: ids[3].clone(),
+            from: addr("reviewer2").to_string(),
+            to: addr("reviewer1").to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), message_info(&addr("admin"), &[]), msg).unwrap();
+        transition(&mut deps, "reviewer1", &ids[3], LoanStatus::UnderReview).unwrap();
+        transition(&mut deps, "creator", &ids[4], LoanStatus::Cancelled).unwrap();
+
+        let counters = |deps: &Deps| -> LoanCounters {
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::GetLoanCounters {}).unwrap()).unwrap()
+        };
+        let maintained = counters(&deps);
+        let expected = HashMap::from([
+            ("Funded".to_string(), 1),
+            ("Rejected".to_string(), 1),
+            ("UnderReview".to_string(), 1),
+            ("Cancelled".to_string(), 1),
+            ("Submitted".to_string(), 2),
+        ]);
+        assert_eq!(maintained.by_status, expected);
+        assert_eq!(maintained.by_template["template1"], expected);
+        assert_eq!(maintained.by_reviewer[""].values().sum::<u64>(), 2);
+        assert_eq!(maintained.by_reviewer[addr("reviewer1").as_str()].len(), 3);
+
+        // Per-reviewer figures from the counters equal those scanned from the loans
+        let msg = QueryMsg::GetAllReviewerStatistics {};
+        let all: AllReviewerStatistics = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(all.reviewers_stats.len(), 2);
+        assert_eq!((all.total_pending, all.total_approved, all.total_rejected), (2, 1, 1));
+        for counted in &all.reviewers_stats {
+            let msg = QueryMsg::GetLoanStatistics {
+                reviewer: counted.reviewer.clone(),
+                utc_offset_minutes: None,
+            };
+            let scanned: LoanStatistics = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            assert_eq!(counted, &scanned);
+        }
+
+        // Rebuilding from the loans repairs drift and changes nothing else
+        STATUS_COUNTS.save(&mut deps.storage, "Funded", &7).unwrap();
+        STATUS_COUNTS.save(&mut deps.storage, "Defaulted", &2).unwrap();
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("reviewer1"), &[]),
+            ExecuteMsg::RecomputeStatistics {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("admin"), &[]),
+            ExecuteMsg::RecomputeStatistics {},
+        )
+        .unwrap();
+        assert_eq!(res.attributes[1], attr("loans", "6"));
+        assert_eq!(counters(&deps), maintained);
+    }
+
+    #[test]
+    fn test_decisions_count_for_the_decider_through_reassignment() {
+        let mut deps = setup();
+        create_approved_template(&mut deps);
+        let loan_id = create_test_loan(&mut deps, false);
+        let creator = addr("creator").to_string();
+        assign_loan(deps.as_mut(), "reviewer1", creator.clone(), loan_id.clone());
+        transition(&mut deps, "reviewer1", &loan_id, LoanStatus::UnderReview).unwrap();
+        transition(&mut deps, "admin", &loan_id, LoanStatus::Approved).unwrap();
+
+        let statistics = |deps: &Deps| -> (Vec<LoanStatistics>, Vec<LoanStatistics>) {
+            let msg = QueryMsg::GetAllReviewerStatistics {};
+            let all: AllReviewerStatistics = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            let scanned = all
+                .reviewers_stats
+                .iter()
+                .map(|counted| {
+                    let msg = QueryMsg::GetLoanStatistics {
+                        reviewer: counted.reviewer.clone(),
+                        utc_offset_minutes: None,
+                    };
+                    from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap()
+                })
+                .collect();
+            (all.reviewers_stats, scanned)
+        };
+        let (counted, scanned) = statistics(&deps);
+        assert_eq!(counted, scanned);
+        let approvals: Vec<(String, u64)> = counted
+            .iter()
+            .map(|stats| (stats.reviewer.clone().unwrap(), stats.approved_this_month))
+            .collect();
+        assert_eq!(approvals, vec![(addr("admin").to_string(), 1), (addr("reviewer1").to_string(), 0)]);
+
+        // Decided loans can no longer be reassigned, but ones moved before that was enforced
+        // leave the decision where it was
+        let key = loan_key(&loan_id);
+        let mut loan = LOAN_STORAGE.load(&deps.storage, (creator.as_str(), &key)).unwrap();
+        loan.reviewer = Some(addr("reviewer2"));
+        stats::save_loan(&mut deps.storage, (creator.as_str(), &key), &loan).unwrap();
+        let (after, scanned) = statistics(&deps);
+        assert_eq!(after, scanned);
+        assert_eq!(after[0], counted[0]);
+
+        let msg = ExecuteMsg::RecomputeStatistics {};
+        execute(deps.as_mut(), mock_env(), message_info(&addr("admin"), &[]), msg).unwrap();
+        assert_eq!(statistics(&deps).0, after);
+    }
+
+    // Loan amendments, template versions and template resubmission
+
+    // Creates and approves `template2`: an editable amount, a fixed purpose and an optional note
+    fn create_editable_template(deps: &mut Deps) {
+        let text = |is_editable: bool, required: bool| FieldType::String {
+            is_editable,
+            required,
+            default: None,
+            format: None,
+            min_value: None,
+            max_value: None,
+        };
+        let fields = HashMap::from([
+            (
+                "amount".to_string(),
+                FieldType::Number {
+                    is_editable: true,
+                    required: true,
+                    default: None,
+                    min_value: Some("50000".to_string()),
+                    max_value: None,
+                },
+            ),
+            ("purpose".to_string(), text(false, true)),
+            ("note".to_string(), text(true, false)),
+        ]);
+        let creator_info = message_info(&addr("creator"), &[]);
+        let msg = ExecuteMsg::CreateLoanTemplate {
+            template_id: "template2".to_string(),
+            name: "Car Loan".to_string(),
+            fields,
+            rules: vec![],
+        };
+        execute(deps.as_mut(), mock_env(), creator_info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::SubmitTemplateForReview {
+            template_id: "template2".to_string(),
+            reviewer: addr("reviewer1").to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
+        let msg = ExecuteMsg::ReviewTemplate {
+            user_id: addr("creator").to_string(),
+            template_id: "template2".to_string(),
+            approve: true,
+            comment: None,
+        };
+        execute(deps.as_mut(), mock_env(), message_info(&addr("reviewer1"), &[]), msg).unwrap();
+    }
+
+    fn amend(
+        deps: &mut Deps,
+        sender: &str,
+        loan_id: &str,
+        changes: &[(&str, Option<&str>)],
+    ) -> Result<cosmwasm_std::Response, ContractError> {
+        let msg = ExecuteMsg::AmendLoan {
+            loan_id: loan_id.to_string(),
+            changes: changes
+                .iter()
+                .map(|(field, value)| (field.to_string(), value.map(FieldValue::from)))
+                .collect(),
+        };
+        execute(deps.as_mut(), mock_env(), message_info(&addr(sender), &[]), msg)
+    }
+
+    #[test]
+    fn test_amend_loan_keeps_numbered_revisions() {
+        let mut deps = setup();
+        create_editable_template(&mut deps);
+        let msg = ExecuteMsg::CreateLoan {
+            user_id: addr("creator").to_string(),
+            loan_requests: vec![LoanRequest {
+                template_id: "template2".to_string(),
+                values: HashMap::from([
+                    ("amount".to_string(), "60000".into()),
+                    ("purpose".to_string(), "car".into()),
+                    ("note".to_string(), "first car".into()),
+                ]),
+                draft: true,
+            }],
+        };
+        execute(deps.as_mut(), mock_env(), message_info(&addr("creator"), &[]), msg).unwrap();
+        let loan_id = "1";
+        let original = LOAN_STORAGE.load(&deps.storage, (addr("creator").as_str(), &loan_key(loan_id))).unwrap();
+        assert_eq!(original.template_owner, addr("creator").to_string());
+
+        let res = amend(&mut deps, "creator", loan_id, &[("amount", Some("70000"))]).unwrap();
+        assert!(res.attributes.contains(&attr("revision", "1")));
+
+        // Only editable fields the template defines, with values that still validate
+        let err = amend(&mut deps, "creator", loan_id, &[("amount", Some("80000")), ("purpose", Some("boat"))]).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::FieldNotEditable {
+                field_name: "purpose".to_string()
+            }
+        );
+        let err = amend(&mut deps, "creator", loan_id, &[("color", Some("red"))]).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::UnknownField {
+                field_name: "color".to_string()
+            }
+        );
+        let err = amend(&mut deps, "creator", loan_id, &[("amount", Some("100"))]).unwrap_err();
+        assert!(matches!(err, ContractError::ValidationFailed { .. }));
+        let err = amend(&mut deps, "creator", loan_id, &[("amount", Some("70000"))]).unwrap_err();
+        assert_eq!(err, ContractError::NothingToAmend {});
+        // Loans are amended by the officer who created them
+        let err = amend(&mut deps, "creator1", loan_id, &[("amount", Some("80000"))]).unwrap_err();
+        assert_eq!(err, ContractError::LoanNotFound {});
+
+        transition(&mut deps, "creator", loan_id, LoanStatus::Submitted).unwrap();
+        assign_loan(deps.as_mut(), "reviewer1", addr("creator").to_string(), loan_id.to_string());
+        transition(&mut deps, "reviewer1", loan_id, LoanStatus::UnderReview).unwrap();
+        amend(&mut deps, "creator", loan_id, &[("amount", Some("75000")), ("note", None)]).unwrap();
+
+        let loan = LOAN_STORAGE.load(&deps.storage, (addr("creator").as_str(), &loan_key(loan_id))).unwrap();
+        assert_eq!(loan.values.get("amount"), Some(&"75000".into()));
+        assert_eq!(loan.values.get("note"), None);
+
+        let msg = QueryMsg::GetLoanRevisions {
+            loan_id: loan_id.to_string(),
+            start_after: None,
+            limit: None,
+            order: Some(SortOrder::Descending),
+        };
+        let page: Page<LoanRevision, u32> = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        let revisions: Vec<u32> = page.items.iter().map(|r| r.revision).collect();
+        assert_eq!(revisions, vec![2, 1]);
+        let first = &page.items[1];
+        assert_eq!(first.values, original.values);
+        assert_eq!(first.amended_by, addr("creator"));
+        assert_eq!(
+            first.changes,
+            vec![FieldChange {
+                field: "amount".to_string(),
+                before: Some("60000".into()),
+                after: Some("70000".into()),
+            }]
+        );
+        assert_eq!(
+            page.items[0].changes,
+            vec![
+                FieldChange {
+                    field: "amount".to_string(),
+                    before: Some("70000".into()),
+                    after: Some("75000".into()),
+                },
+                FieldChange {
+                    field: "note".to_string(),
+                    before: Some("first car".into()),
+                    after: None,
+                },
+            ]
+        );
+
+        // Decided loans are final
+        transition(&mut deps, "reviewer1", loan_id, LoanStatus::Approved).unwrap();
+        let err = amend(&mut deps, "creator", loan_id, &[("amount", Some("90000"))]).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::LoanNotAmendable {
+                loan_id: loan_id.to_string(),
+                status: LoanStatus::Approved,
+            }
+        );
+    }
+
+    #[test]
+    fn test_amendments_share_one_history_however_the_id_is_written() {
+        let mut deps = setup();
+        create_editable_template(&mut deps);
+        let msg = ExecuteMsg::CreateLoan {
+            user_id: addr("creator").to_string(),
+            loan_requests: vec![LoanRequest {
+                template_id: "template2".to_string(),
+                values: HashMap::from([("amount".to_string(), "60000".into()), ("purpose".to_string(), "car".into())]),
+                draft: true,
+            }],
+        };
+        execute(deps.as_mut(), mock_env(), message_info(&addr("creator"), &[]), msg).unwrap();
+
+        let res = amend(&mut deps, "creator", "1", &[("amount", Some("70000"))]).unwrap();
+        assert!(res.attributes.contains(&attr("revision", "1")));
+        let res = amend(&mut deps, "creator", "01", &[("amount", Some("80000"))]).unwrap();
+        assert!(res.attributes.contains(&attr("revision", "2")));
+
+        let stored: Vec<u32> = LOAN_REVISIONS
+            .prefix(&loan_key("1"))
+            .keys(&deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<_>>()
+            .unwrap();
+        assert_eq!(stored, vec![1, 2]);
+        for loan_id in ["1", "001"] {
+            let msg = QueryMsg::GetLoanRevisions {
+                loan_id: loan_id.to_string(),
+                start_after: None,
+                limit: None,
+                order: None,
+            };
+            let page: Page<LoanRevision, u32> = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            let revisions: Vec<u32> = page.items.iter().map(|r| r.revision).collect();
+            assert_eq!(revisions, vec![1, 2]);
+        }
+    }
+
+    #[test]
+    fn test_template_versions_are_reviewed_and_kept_for_their_loans() {
+        let mut deps = setup();
+        create_editable_template(&mut deps);
+        let creator_info = message_info(&addr("creator"), &[]);
+        let loan = |amount: &str| ExecuteMsg::CreateLoan {
+            user_id: addr("creator").to_string(),
+            loan_requests: vec![LoanRequest {
+                template_id: "template2".to_string(),
+                values: HashMap::from([("amount".to_string(), amount.into()), ("purpose".to_string(), "car".into())]),
+                draft: false,
+            }],
+        };
+        execute(deps.as_mut(), mock_env(), creator_info.clone(), loan("60000")).unwrap();
+
+        // Editing an approved template starts version 2 with a higher minimum
+        let mut template = USER_TEMPLATES.load(&deps.storage, (addr("creator").as_str(), "template2")).unwrap();
+        template.fields.insert(
+            "amount".to_string(),
+            FieldType::Number {
+                is_editable: true,
+                required: true,
+                default: None,
+                min_value: Some("70000".to_string()),
+                max_value: None,
+            },
+        );
+        let update = |name: &str| ExecuteMsg::UpdateLoanTemplate {
+            template_id: "template2".to_string(),
+            name: name.to_string(),
+            fields: template.fields.clone(),
+            rules: vec![],
+        };
+        let res = execute(deps.as_mut(), mock_env(), creator_info.clone(), update("Car Loan v2")).unwrap();
+        assert!(res.attributes.contains(&attr("version", "2")));
+        // The draft is edited in place until it is submitted
+        execute(deps.as_mut(), mock_env(), creator_info.clone(), update("Car Loan")).unwrap();
+        let draft = USER_TEMPLATES.load(&deps.storage, (addr("creator").as_str(), "template2")).unwrap();
+        assert_eq!((draft.version, draft.review_status, draft.reviewer), (2, ReviewStatus::Pending, None));
+
+        // New loans keep using version 1 until version 2 is approved
+        execute(deps.as_mut(), mock_env(), creator_info.clone(), loan("60000")).unwrap();
+        let msg = ExecuteMsg::SubmitTemplateForReview {
+            template_id: "template2".to_string(),
+            reviewer: addr("reviewer1").to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), creator_info.clone(), msg).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), creator_info.clone(), update("Car Loan")).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::TemplateUnderReview {
+                template_id: "template2".to_string()
+            }
+        );
+        let msg = ExecuteMsg::ReviewTemplate {
+            user_id: addr("creator").to_string(),
+            template_id: "template2".to_string(),
+            approve: true,
+            comment: None,
+        };
+        execute(deps.as_mut(), mock_env(), message_info(&addr("reviewer1"), &[]), msg).unwrap();
+
+        let err = execute(deps.as_mut(), mock_env(), creator_info.clone(), loan("60000")).unwrap_err();
+        assert!(matches!(err, ContractError::ValidationFailed { .. }));
+        execute(deps.as_mut(), mock_env(), creator_info, loan("80000")).unwrap();
+        let versions: Vec<u32> = ["1", "2", "3"]
+            .iter()
+            .map(|loan_id| LOAN_STORAGE.load(&deps.storage, (addr("creator").as_str(), &loan_key(loan_id))).unwrap().template_version)
+            .collect();
+        assert_eq!(versions, vec![1, 1, 2]);
+
+        // Loans on version 1 are still amended against it
+        amend(&mut deps, "creator", "1", &[("amount", Some("65000"))]).unwrap();
+
+        let msg = QueryMsg::GetTemplateVersions {
+            user_id: addr("creator").to_string(),
+            template_id: "template2".to_string(),
+            start_after: None,
+            limit: None,
+            order: None,
+        };
+        let page: Page<LoanTemplate, u32> = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        let names: Vec<(u32, &str)> = page.items.iter().map(|t| (t.version, t.name.as_str())).collect();
+        assert_eq!(names, vec![(1, "Car Loan"), (2, "Car Loan")]);
+        assert_eq!(
+            page.items[0].fields["amount"],
+            FieldType::Number {
+                is_editable: true,
+                required: true,
+                default: None,
+                min_value: Some("50000".to_string()),
+                max_value: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_rejected_template_can_be_revised_and_resubmitted() {
+        let mut deps = setup();
+        let creator_info = message_info(&addr("creator"), &[]);
+        let fields = HashMap::from([(
+            "amount".to_string(),
+            FieldType::Number {
+                is_editable: false,
+                required: true,
+                default: None,
+                min_value: None,
+                max_value: None,
+            },
+        )]);
+        let msg = ExecuteMsg::CreateLoanTemplate {
+            template_id: "template1".to_string(),
+            name: "Home Loan".to_string(),
+            fields: fields.clone(),
+            rules: vec![],
+        };
+        execute(deps.as_mut(), mock_env(), creator_info.clone(), msg).unwrap();
+        let submit = |reviewer: &str| ExecuteMsg::SubmitTemplateForReview {
+            template_id: "template1".to_string(),
+            reviewer: addr(reviewer).to_string(),
+        };
+        let review = |approve: bool, comment: Option<&str>| ExecuteMsg::ReviewTemplate {
+            user_id: addr("creator").to_string(),
+            template_id: "template1".to_string(),
+            approve,
+            comment: comment.map(str::to_string),
+        };
+
+        execute(deps.as_mut(), mock_env(), creator_info.clone(), submit("reviewer1")).unwrap();
+        let reviewer1_info = message_info(&addr("reviewer1"), &[]);
+        execute(deps.as_mut(), mock_env(), reviewer1_info, review(false, Some("Amount needs a minimum"))).unwrap();
+
+        // Resubmitting unchanged goes to another reviewer, who rejects it again
+        execute(deps.as_mut(), mock_env(), creator_info.clone(), submit("reviewer2")).unwrap();
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(60);
+        let reviewer2_info = message_info(&addr("reviewer2"), &[]);
+        execute(deps.as_mut(), env.clone(), reviewer2_info.clone(), review(false, None)).unwrap();
+
+        // Revising makes it a draft again
+        let mut revised = fields;
+        revised.insert(
+            "amount".to_string(),
+            FieldType::Number {
+                is_editable: false,
+                required: true,
+                default: None,
+                min_value: Some("1000".to_string()),
+                max_value: None,
+            },
+        );
+        let msg = ExecuteMsg::UpdateLoanTemplate {
+            template_id: "template1".to_string(),
+            name: "Home Loan".to_string(),
+            fields: revised.clone(),
+            rules: vec![],
+        };
+        execute(deps.as_mut(), mock_env(), creator_info.clone(), msg).unwrap();
+        let template = USER_TEMPLATES.load(&deps.storage, (addr("creator").as_str(), "template1")).unwrap();
+        assert_eq!((template.version, template.review_status, template.reviewer), (1, ReviewStatus::Pending, None));
+
+        execute(deps.as_mut(), mock_env(), creator_info.clone(), submit("reviewer2")).unwrap();
+        execute(deps.as_mut(), env.clone(), reviewer2_info, review(true, Some("Looks good"))).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), creator_info, submit("reviewer1")).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::TemplateAlreadyReviewed {
+                template_id: "template1".to_string()
+            }
+        );
+
+        let msg = QueryMsg::GetTemplateReviewHistory {
+            user_id: addr("creator").to_string(),
+            template_id: "template1".to_string(),
+            start_after: None,
+            limit: None,
+            order: None,
+        };
+        let page: Page<TemplateReview, u32> = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(
+            page.items,
+            vec![
+                TemplateReview {
+                    review: 1,
+                    version: 1,
+                    reviewer: addr("reviewer1"),
+                    decision: ReviewStatus::Rejected,
+                    comment: Some("Amount needs a minimum".to_string()),
+                    timestamp: mock_env().block.time.seconds(),
+                },
+                TemplateReview {
+                    review: 2,
+                    version: 1,
+                    reviewer: addr("reviewer2"),
+                    decision: ReviewStatus::Rejected,
+                    comment: None,
+                    timestamp: env.block.time.seconds(),
+                },
+                TemplateReview {
+                    review: 3,
+                    version: 1,
+                    reviewer: addr("reviewer2"),
+                    decision: ReviewStatus::Approved,
+                    comment: Some("Looks good".to_string()),
+                    timestamp: env.block.time.seconds(),
+                },
+            ]
+        );
+
+        // Only the approved revision is published
+        let msg = QueryMsg::GetTemplateVersions {
+            user_id: addr("creator").to_string(),
+            template_id: "template1".to_string(),
+            start_after: None,
+            limit: None,
+            order: None,
+        };
+        let page: Page<LoanTemplate, u32> = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].fields, revised);
+    }
+
+    #[test]
+    fn test_same_template_id_is_reviewed_per_owner() {
+        let mut deps = setup();
+        for owner in ["creator", "creator1"] {
+            let owner_info = message_info(&addr(owner), &[]);
+            let msg = ExecuteMsg::CreateLoanTemplate {
+                template_id: "template1".to_string(),
+                name: "Home Loan".to_string(),
+                fields: HashMap::new(),
+                rules: vec![],
+            };
+            execute(deps.as_mut(), mock_env(), owner_info.clone(), msg).unwrap();
+            let msg = ExecuteMsg::SubmitTemplateForReview {
+                template_id: "template1".to_string(),
+                reviewer: addr("reviewer1").to_string(),
+            };
+            execute(deps.as_mut(), mock_env(), owner_info, msg).unwrap();
+        }
+
+        // The second submission does not replace the first
+        let msg = QueryMsg::GetReviewerTemplates {
+            reviewer: addr("reviewer1").to_string(),
+            start_after: None,
+            limit: None,
+            order: None,
+        };
+        let page: Page<LoanTemplate, (String, String)> =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(page.items.len(), 2);
+
+        for (owner, approve) in [("creator", false), ("creator1", true)] {
+            let msg = ExecuteMsg::ReviewTemplate {
+                user_id: addr(owner).to_string(),
+                template_id: "template1".to_string(),
+                approve,
+                comment: None,
+            };
+            execute(deps.as_mut(), mock_env(), message_info(&addr("reviewer1"), &[]), msg).unwrap();
+        }
+        for (owner, status) in [("creator", ReviewStatus::Rejected), ("creator1", ReviewStatus::Approved)] {
+            let template = USER_TEMPLATES.load(&deps.storage, (addr(owner).as_str(), "template1")).unwrap();
+            assert_eq!(template.review_status, status);
+            let msg = QueryMsg::GetTemplateReviewHistory {
+                user_id: addr(owner).to_string(),
+                template_id: "template1".to_string(),
+                start_after: None,
+                limit: None,
+                order: None,
+            };
+            let page: Page<TemplateReview, u32> = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            assert_eq!(page.items.len(), 1);
+        }
+    }
 }
@@ -0,0 +1,475 @@
+use cosmwasm_std::{entry_point, DepsMut, Env, Response, StdError};
+use semver::Version;
+
+use crate::{error::ContractError, msg::MigrateMsg};
+
+pub const CONTRACT_NAME: &str = concat!("crates.io:", env!("CARGO_PKG_NAME"));
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// Deployments made before version tracking was introduced
+const LEGACY_VERSION: &str = "0.1.0";
+
+#[entry_point]
+pub fn migrate(mut deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    // Contracts instantiated by 0.1.0 never wrote cw2 info
+    let stored_version = match cw2::get_contract_version(deps.storage) {
+        Ok(info) => {
+            if info.contract != CONTRACT_NAME {
+                return Err(ContractError::InvalidContractName {
+                    expected: CONTRACT_NAME.to_string(),
+                    found: info.contract,
+                });
+            }
+            info.version
+        }
+        Err(_) => LEGACY_VERSION.to_string(),
+    };
+
+    let stored = parse_version(&stored_version)?;
+    let current = parse_version(CONTRACT_VERSION)?;
+    if stored > current {
+        return Err(ContractError::CannotDowngrade {
+            stored: stored_version,
+            current: CONTRACT_VERSION.to_string(),
+        });
+    }
+
+    // Run every routine newer than the stored version, oldest first
+    if stored < parse_version("0.2.0")? {
+        migrations::v0_2_0(deps.branch(), &msg)?;
+    }
+    if stored < parse_version("0.3.0")? {
+        migrations::v0_3_0(deps.branch())?;
+    }
+    if stored < parse_version("0.4.0")? {
+        migrations::v0_4_0(deps.branch())?;
+    }
+    if stored < parse_version("0.5.0")? {
+        migrations::v0_5_0(deps.branch())?;
+    }
+    if stored < parse_version("0.6.0")? {
+        migrations::v0_6_0(deps.branch())?;
+    }
+    if stored < parse_version("0.7.0")? {
+        migrations::v0_7_0(deps.branch())?;
+    }
+    let mut unresolved = vec![];
+    if stored < parse_version("0.8.0")? {
+        unresolved = migrations::v0_8_0(deps.branch())?;
+    }
+    if stored < parse_version("0.9.0")? {
+        migrations::v0_9_0(deps.branch())?;
+    }
+    if stored < parse_version("0.10.0")? {
+        migrations::v0_10_0(deps.branch())?;
+    }
+    if stored < parse_version("0.11.0")? {
+        migrations::v0_11_0(deps.branch())?;
+    }
+    let mut date_formats = vec![];
+    if stored < parse_version("0.12.0")? {
+        date_formats = migrations::v0_12_0(deps.branch())?;
+    }
+    if stored < parse_version("0.13.0")? {
+        migrations::v0_13_0(deps.branch())?;
+    }
+
+    cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let mut response = Response::new()
+        .add_attribute("method", "migrate")
+        .add_attribute("from_version", stored_version)
+        .add_attribute("to_version", CONTRACT_VERSION);
+    // Loans whose template owner could not be told apart; they cannot be amended
+    if !unresolved.is_empty() {
+        response = response.add_attribute("unresolved_template_owners", unresolved.join(","));
+    }
+    // Date fields whose format now rejects every value until their owner revises it
+    if !date_formats.is_empty() {
+        response = response.add_attribute("date_formats_to_review", date_formats.join(","));
+    }
+    Ok(response)
+}
+
+fn parse_version(version: &str) -> Result<Version, ContractError> {
+    Version::parse(version).map_err(|e| StdError::generic_err(e.to_string()).into())
+}
+
+pub mod legacy {
+    use std::collections::HashMap;
+
+    use cosmwasm_std::Addr;
+    use cw_storage_plus::Map;
+    use schemars::JsonSchema;
+    use serde::{Deserialize, Serialize};
+
+    use crate::models::{FieldType, LoanData, ReviewTuple};
+
+    // Layouts written by 0.1.0 and 0.2.0, read back from the same storage namespaces
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    pub enum ReviewStatusV1 {
+        Pending,
+        Approved,
+        Reviewed,
+        Rejected,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    pub struct LoanDataV1 {
+        pub loan_id: String,
+        pub template_id: String,
+        pub values: HashMap<String, String>,
+        pub review_status: ReviewStatusV1,
+        pub creation_date: u64,
+        pub approval_date: Option<u64>,
+        pub rejection_date: Option<u64>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    pub struct LoanTemplateV1 {
+        pub id: String,
+        pub name: String,
+        pub fields: HashMap<String, FieldType>,
+        pub submitter: String,
+        pub reviewer: Option<String>,
+        pub review_status: ReviewStatusV1,
+    }
+
+    pub const LOAN_STORAGE_V1: Map<(&str, &str), LoanDataV1> = Map::new("loans");
+    pub const USER_TEMPLATES_V1: Map<(&str, &str), LoanTemplateV1> = Map::new("user_templates");
+
+    // Before 0.6.0 loans were kept without secondary indexes
+    pub const LOAN_STORAGE_V5: Map<(&str, &str), LoanData> = Map::new("loans");
+
+    // Reviewer assignments written by 0.5.0 and earlier, now the `reviewer` field of each loan
+    pub const REVIEWER_ASSIGNMENTS_V5: Map<&Addr, Vec<(String, String)>> = Map::new("assignments");
+    pub const LOAN_REVIEWERS_V5: Map<(&str, &str), Addr> = Map::new("loan_reviewers");
+
+    // Before 0.10.0 reviewer assignments were keyed by template ID alone
+    pub const TEMPLATE_REVIEWERS_V9: Map<&str, ReviewTuple> = Map::new("template_reviewers");
+}
+
+mod migrations {
+    use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+    use cosmwasm_std::{DepsMut, Order, StdResult};
+
+    use super::legacy::{
+        ReviewStatusV1, LOAN_REVIEWERS_V5, LOAN_STORAGE_V1, LOAN_STORAGE_V5,
+        REVIEWER_ASSIGNMENTS_V5, TEMPLATE_REVIEWERS_V9, USER_TEMPLATES_V1,
+    };
+    use crate::{
+        error::ContractError,
+        models::{Config, FieldType, LoanData, LoanRevision, LoanStatus, LoanTemplate, ReviewStatus, ReviewTuple, Role, StatusTransition},
+        msg::MigrateMsg,
+        roles, stats, templates,
+        states::{loan_key, CONFIG, LOAN_REVISIONS, LOAN_STORAGE, NEXT_LOAN_ID, TEMPLATE_REVIEWERS, TEMPLATE_VERSIONS, USER_TEMPLATES},
+    };
+
+    // 0.1.0 -> 0.2.0: introduce the admin config
+    pub fn v0_2_0(deps: DepsMut, msg: &MigrateMsg) -> Result<(), ContractError> {
+        if !CONFIG.exists(deps.storage) {
+            let admin = msg
+                .admin
+                .as_ref()
+                .ok_or(ContractError::MigrationAdminRequired {})?;
+            let admin = deps.api.addr_validate(admin)?;
+            CONFIG.save(deps.storage, &Config { admin: Some(admin.clone()) })?;
+            roles::grant(deps.storage, &admin, Role::Admin)?;
+        }
+        Ok(())
+    }
+
+    // 0.2.0 -> 0.3.0: loans move to the lifecycle status machine, templates lose `Reviewed`
+    pub fn v0_3_0(deps: DepsMut) -> Result<(), ContractError> {
+        // Collect first: the map cannot be written while it is being iterated
+        let loans = LOAN_STORAGE_V1
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for ((user_id, loan_id), old) in loans {
+            let (status, decided_at) = match old.review_status {
+                ReviewStatusV1::Pending => (LoanStatus::Submitted, None),
+                ReviewStatusV1::Reviewed => (LoanStatus::UnderReview, None),
+                ReviewStatusV1::Approved => (LoanStatus::Approved, old.approval_date),
+                ReviewStatusV1::Rejected => (LoanStatus::Rejected, old.rejection_date),
+            };
+
+            // Only the timestamps the old layout kept can be replayed
+            let mut status_history = vec![StatusTransition {
+                status: LoanStatus::Submitted,
+                timestamp: old.creation_date,
+            }];
+            if status != LoanStatus::Submitted {
+                status_history.push(StatusTransition {
+                    status,
+                    timestamp: decided_at.unwrap_or(old.creation_date),
+                });
+            }
+
+            let loan = LoanData {
+                loan_id: old.loan_id,
+                template_id: old.template_id,
+                template_owner: String::new(), // Looked up by 0.8.0
+                template_version: 1,
+                values: old.values.into_iter().map(|(k, v)| (k, v.into())).collect(),
+                status,
+                creation_date: old.creation_date,
+                // A loan can no longer carry both decisions
+                approval_date: old.approval_date.filter(|_| status == LoanStatus::Approved),
+                rejection_date: old.rejection_date.filter(|_| status == LoanStatus::Rejected),
+                status_history,
+                decided_by: None,
+                decision_reason: None,
+                reviewer: None,
+            };
+            LOAN_STORAGE_V5.save(deps.storage, (&user_id, &loan_id), &loan)?;
+        }
+
+        let templates = USER_TEMPLATES_V1
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for ((user_id, template_id), old) in templates {
+            let template = LoanTemplate {
+                id: old.id,
+                name: old.name,
+                fields: old.fields,
+                submitter: old.submitter,
+                reviewer: old.reviewer,
+                review_status: match old.review_status {
+                    ReviewStatusV1::Approved => ReviewStatus::Approved,
+                    ReviewStatusV1::Rejected => ReviewStatus::Rejected,
+                    ReviewStatusV1::Pending | ReviewStatusV1::Reviewed => ReviewStatus::Pending,
+                },
+                rules: vec![],
+                version: 1,
+            };
+            USER_TEMPLATES.save(deps.storage, (&user_id, &template_id), &template)?;
+        }
+
+        Ok(())
+    }
+
+    // 0.3.0 -> 0.4.0: build the loan -> reviewer index from the per-reviewer lists
+    pub fn v0_4_0(deps: DepsMut) -> Result<(), ContractError> {
+        let assignments = REVIEWER_ASSIGNMENTS_V5
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for (reviewer, loans) in assignments {
+            // Old lists were overwritten wholesale, so they may name missing loans or
+            // loans also held by another reviewer; the first reviewer in key order wins
+            let mut kept = vec![];
+            for (user_id, loan_id) in loans {
+                if !LOAN_STORAGE_V5.has(deps.storage, (&user_id, &loan_id))
+                    || LOAN_REVIEWERS_V5.has(deps.storage, (&user_id, &loan_id))
+                {
+                    continue;
+                }
+                LOAN_REVIEWERS_V5.save(deps.storage, (&user_id, &loan_id), &reviewer)?;
+                kept.push((user_id, loan_id));
+            }
+
+            if kept.is_empty() {
+                REVIEWER_ASSIGNMENTS_V5.remove(deps.storage, &reviewer);
+            } else {
+                REVIEWER_ASSIGNMENTS_V5.save(deps.storage, &reviewer, &kept)?;
+            }
+        }
+        Ok(())
+    }
+
+    // 0.4.0 -> 0.5.0: loan IDs come from a counter instead of random UUIDs
+    pub fn v0_5_0(deps: DepsMut) -> Result<(), ContractError> {
+        // Existing UUID keys stay as they are: they are still unique, every index refers to
+        // them, and a hyphenated UUID can never collide with a decimal counter value
+        if !NEXT_LOAN_ID.exists(deps.storage) {
+            NEXT_LOAN_ID.save(deps.storage, &1)?;
+        }
+        Ok(())
+    }
+
+    // 0.5.0 -> 0.6.0: index loans and move each loan's reviewer onto the loan itself
+    pub fn v0_6_0(deps: DepsMut) -> Result<(), ContractError> {
+        let loans = LOAN_STORAGE_V5
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for ((user_id, loan_id), mut loan) in loans {
+            loan.reviewer = LOAN_REVIEWERS_V5.may_load(deps.storage, (&user_id, &loan_id))?;
+            // No index entries exist yet, so there are none to remove
+            LOAN_STORAGE.replace(deps.storage, (&user_id, &loan_id), Some(&loan), None)?;
+        }
+
+        LOAN_REVIEWERS_V5.clear(deps.storage);
+        REVIEWER_ASSIGNMENTS_V5.clear(deps.storage);
+        Ok(())
+    }
+
+    // 0.6.0 -> 0.7.0: start the running loan counters from the loans already stored
+    pub fn v0_7_0(deps: DepsMut) -> Result<(), ContractError> {
+        stats::recompute(deps.storage)?;
+        Ok(())
+    }
+
+    // 0.7.0 -> 0.8.0: record whose template each loan was validated against. Older loans kept
+    // only the template ID, so the owner is found among the stored templates with that ID;
+    // returns the "user_id/loan_id" keys of loans where none or several users have one
+    pub fn v0_8_0(deps: DepsMut) -> Result<Vec<String>, ContractError> {
+        let mut owners: HashMap<String, Vec<String>> = HashMap::new();
+        for key in USER_TEMPLATES.keys(deps.storage, None, None, Order::Ascending) {
+            let (user_id, template_id) = key?;
+            owners.entry(template_id).or_default().push(user_id);
+        }
+
+        let loans = LOAN_STORAGE
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        let mut unresolved = vec![];
+        for ((user_id, key), mut loan) in loans {
+            if !loan.template_owner.is_empty() {
+                continue;
+            }
+            match owners.get(&loan.template_id).map(Vec::as_slice) {
+                Some([owner]) => {
+                    loan.template_owner = owner.clone();
+                    LOAN_STORAGE.save(deps.storage, (&user_id, &key), &loan)?;
+                }
+                _ => unresolved.push(format!("{}/{}", user_id, loan.loan_id)),
+            }
+        }
+        Ok(unresolved)
+    }
+
+    // 0.8.0 -> 0.9.0: every approved template becomes its own published first version
+    pub fn v0_9_0(deps: DepsMut) -> Result<(), ContractError> {
+        let approved = USER_TEMPLATES
+            .range(deps.storage, None, None, Order::Ascending)
+            .filter(|item| !matches!(item, Ok((_, template)) if template.review_status != ReviewStatus::Approved))
+            .collect::<StdResult<Vec<_>>>()?;
+        for ((user_id, _), template) in approved {
+            templates::publish(deps.storage, &user_id, &template)?;
+        }
+        Ok(())
+    }
+
+    // 0.9.0 -> 0.10.0: key reviewer assignments by (user_id, template_id). Two officers
+    // submitting the same template ID shared one entry, so a pending template whose entry
+    // was overwritten gets it back from its own `reviewer`
+    pub fn v0_10_0(deps: DepsMut) -> Result<(), ContractError> {
+        let assignments = TEMPLATE_REVIEWERS_V9
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        TEMPLATE_REVIEWERS_V9.clear(deps.storage);
+        for (template_id, tuple) in assignments {
+            TEMPLATE_REVIEWERS.save(deps.storage, (&tuple.creater, &template_id), &tuple)?;
+        }
+
+        let templates = USER_TEMPLATES
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for ((user_id, template_id), template) in templates {
+            let Some(reviewer) = template.reviewer else {
+                continue;
+            };
+            if template.review_status == ReviewStatus::Pending
+                && !TEMPLATE_REVIEWERS.has(deps.storage, (&user_id, &template_id))
+            {
+                let tuple = ReviewTuple {
+                    reviewer,
+                    creater: user_id.clone(),
+                };
+                TEMPLATE_REVIEWERS.save(deps.storage, (&user_id, &template_id), &tuple)?;
+            }
+        }
+        Ok(())
+    }
+    // 0.10.0 -> 0.11.0: zero-pad decimal loan keys so loans page in ID order; the counters
+    // do not change, only the keys the loans and their index entries sit under. Revisions
+    // move to the same keys, and histories split across spellings of one ID ("7", "07")
+    // are merged in time order and renumbered from 1
+    pub fn v0_11_0(deps: DepsMut) -> Result<(), ContractError> {
+        let loans = LOAN_STORAGE
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for ((user_id, loan_id), loan) in loans {
+            let key = loan_key(&loan_id);
+            if key == loan_id {
+                continue;
+            }
+            LOAN_STORAGE.replace(deps.storage, (&user_id, &loan_id), None, Some(&loan))?;
+            LOAN_STORAGE.replace(deps.storage, (&user_id, &key), Some(&loan), None)?;
+        }
+
+        let revisions = LOAN_REVISIONS
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        let mut histories: BTreeMap<String, Vec<LoanRevision>> = BTreeMap::new();
+        for ((loan_id, revision), entry) in revisions {
+            LOAN_REVISIONS.remove(deps.storage, (&loan_id, revision));
+            histories.entry(loan_key(&loan_id)).or_default().push(entry);
+        }
+        for (key, mut history) in histories {
+            history.sort_by_key(|entry| (entry.timestamp, entry.revision));
+            for (revision, mut entry) in (1..).zip(history) {
+                entry.revision = revision;
+                LOAN_REVISIONS.save(deps.storage, (&key, revision), &entry)?;
+            }
+        }
+        Ok(())
+    }
+    // One value in each form a date may take since 0.11.0
+    const DATE_FORMS: [&str; 4] = ["1709214300", "2024-02-29", "2024-02-29T13:45:00Z", "2024-02-29T13:45:00+02:00"];
+
+    // 0.11.0 -> 0.12.0: date values must read as dates before their `format` applies, where
+    // older ones only had to match it. Returns the "user_id/template_id/field" of every date
+    // field, in drafts and approved versions, whose format matches none of the accepted forms
+    pub fn v0_12_0(deps: DepsMut) -> Result<Vec<String>, ContractError> {
+        let mut found = BTreeSet::new();
+        let drafts = USER_TEMPLATES
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|((user_id, _), template)| (user_id, template)));
+        let versions = TEMPLATE_VERSIONS
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|((user_id, _, _), template)| (user_id, template)));
+        for item in drafts.chain(versions) {
+            let (user_id, template) = item?;
+            for (name, field_type) in &template.fields {
+                let prefix = format!("{}/{}/", user_id, template.id);
+                date_format_paths(&prefix, name, field_type, &mut found);
+            }
+        }
+        Ok(found.into_iter().collect())
+    }
+
+    fn date_format_paths(prefix: &str, path: &str, field_type: &FieldType, found: &mut BTreeSet<String>) {
+        match field_type {
+            FieldType::Date { format: Some(format), .. } => {
+                let usable = regex_lite::Regex::new(format)
+                    .is_ok_and(|re| DATE_FORMS.iter().any(|value| re.is_match(value)));
+                if !usable {
+                    found.insert(format!("{}{}", prefix, path));
+                }
+            }
+            FieldType::Object { fields, .. } => {
+                for (name, field_type) in fields {
+                    date_format_paths(prefix, &format!("{}.{}", path, name), field_type, found);
+                }
+            }
+            FieldType::List { item, .. } => date_format_paths(prefix, &format!("{}[]", path), item, found),
+            _ => {}
+        }
+    }
+
+    // 0.12.0 -> 0.13.0: decisions count for whoever made them rather than the assigned
+    // reviewer; index every loan by its decider and rebuild the counters
+    pub fn v0_13_0(deps: DepsMut) -> Result<(), ContractError> {
+        let loans = LOAN_STORAGE
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for ((user_id, key), loan) in loans {
+            // No previous value, so only the missing index entries are written
+            LOAN_STORAGE.replace(deps.storage, (&user_id, &key), Some(&loan), None)?;
+        }
+        stats::recompute(deps.storage)?;
+        Ok(())
+    }
+}
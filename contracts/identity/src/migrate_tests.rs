@@ -0,0 +1,514 @@
+mod test {
+
+    use crate::error::ContractError;
+    use crate::instantiate::instantiate;
+    use crate::migrate::legacy::{LOAN_STORAGE_V1, REVIEWER_ASSIGNMENTS_V5, TEMPLATE_REVIEWERS_V9, USER_TEMPLATES_V1};
+    use crate::migrate::{migrate, CONTRACT_NAME, CONTRACT_VERSION};
+    use crate::exec::execute;
+    use crate::models::{FieldType, LoanRequest, LoanRevision, LoanStatistics, LoanStatus, LoanSummary, LoanTemplate, Page, ReviewStatus, ReviewTuple, Role, StatusTransition};
+    use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg};
+    use crate::calendar::YearMonth;
+    use crate::roles;
+    use crate::query::query;
+    use crate::msg::QueryMsg;
+    use crate::states::{loan_key, CONFIG, LOAN_REVISIONS, LOAN_STORAGE, MONTH_COUNTS, NEXT_LOAN_ID, REVIEWER_DECISIONS, STATUS_COUNTS, TEMPLATE_REVIEWERS, TEMPLATE_VERSIONS, USER_TEMPLATES};
+    use crate::test_helpers::{addr, approved_template, create_loan, setup};
+    use cosmwasm_std::testing::{message_info, mock_dependencies, mock_env};
+    use cw_storage_plus::{Index, PrimaryKey};
+    use cosmwasm_std::{attr, from_json, Addr, Order, StdResult, Storage};
+    use std::collections::HashMap;
+
+    // Writes a loan and a template exactly as 0.1.0 serialized them
+    fn store_legacy_state(storage: &mut dyn Storage) {
+        storage.set(
+            &LOAN_STORAGE_V1.key(("creator", "loan1")),
+            br#"{"loan_id":"loan1","template_id":"template1","values":{"amount":"60000"},"review_status":"Approved","creation_date":100,"approval_date":200,"rejection_date":null}"#,
+        );
+        storage.set(
+            &USER_TEMPLATES_V1.key(("creator", "template1")),
+            br#"{"id":"template1","name":"Home Loan","fields":{"amount":{"Number":{"is_editable":false,"min_value":"50000.0","max_value":null}}},"submitter":"creator","reviewer":"reviewer1","review_status":"Approved"}"#,
+        );
+    }
+
+    #[test]
+    fn test_instantiate_sets_contract_version() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            admin: addr("admin").to_string(),
+            reviewers: vec![],
+            loan_officers: vec![],
+            auditors: vec![],
+        };
+        instantiate(deps.as_mut(), mock_env(), message_info(&addr("admin"), &[]), msg).unwrap();
+
+        let version = cw2::get_contract_version(&deps.storage).unwrap();
+        assert_eq!(version.contract, CONTRACT_NAME);
+        assert_eq!(version.version, CONTRACT_VERSION);
+    }
+
+    #[test]
+    fn test_migrate_legacy_deployment() {
+        let mut deps = mock_dependencies();
+        store_legacy_state(&mut deps.storage);
+
+        let msg = MigrateMsg {
+            admin: Some(addr("admin").to_string()),
+        };
+        let res = migrate(deps.as_mut(), mock_env(), msg).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("method", "migrate"),
+                attr("from_version", "0.1.0"),
+                attr("to_version", CONTRACT_VERSION),
+            ]
+        );
+
+        let loan = LOAN_STORAGE.load(&deps.storage, ("creator", "loan1")).unwrap();
+        assert_eq!(loan.template_id, "template1");
+        assert_eq!(loan.template_owner, "creator");
+        assert_eq!(loan.values["amount"].as_text().unwrap(), "60000");
+        assert_eq!(loan.status, LoanStatus::Approved);
+        assert_eq!(loan.approval_date, Some(200));
+        assert_eq!(
+            loan.status_history,
+            vec![
+                StatusTransition { status: LoanStatus::Submitted, timestamp: 100 },
+                StatusTransition { status: LoanStatus::Approved, timestamp: 200 },
+            ]
+        );
+
+        // Counters start out matching the migrated loans
+        assert_eq!(STATUS_COUNTS.load(&deps.storage, "Approved").unwrap(), 1);
+        assert_eq!(MONTH_COUNTS.load(&deps.storage, ("1970-01", "Approved")).unwrap(), 1);
+
+        let template = USER_TEMPLATES.load(&deps.storage, ("creator", "template1")).unwrap();
+        assert_eq!(template.name, "Home Loan");
+        assert_eq!(template.reviewer, Some("reviewer1".to_string()));
+        assert_eq!(template.review_status, ReviewStatus::Approved);
+        // Loans created before versioning were validated against the first version
+        assert_eq!(TEMPLATE_VERSIONS.load(&deps.storage, ("creator", "template1", 1)).unwrap(), template);
+
+        let config = CONFIG.load(&deps.storage).unwrap();
+        assert_eq!(config.admin, Some(addr("admin")));
+        assert!(roles::has_role(&deps.storage, &addr("admin"), Role::Admin));
+
+        let version = cw2::get_contract_version(&deps.storage).unwrap();
+        assert_eq!(version.version, CONTRACT_VERSION);
+    }
+
+    #[test]
+    fn test_migrate_legacy_deployment_requires_admin() {
+        let mut deps = mock_dependencies();
+        store_legacy_state(&mut deps.storage);
+
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { admin: None }).unwrap_err();
+        assert_eq!(err, ContractError::MigrationAdminRequired {});
+    }
+
+    #[test]
+    fn test_migrate_refuses_downgrade() {
+        let mut deps = mock_dependencies();
+        cw2::set_contract_version(&mut deps.storage, CONTRACT_NAME, "99.0.0").unwrap();
+
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { admin: None }).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::CannotDowngrade {
+                stored: "99.0.0".to_string(),
+                current: CONTRACT_VERSION.to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_migrate_refuses_other_contract() {
+        let mut deps = mock_dependencies();
+        cw2::set_contract_version(&mut deps.storage, "crates.io:cw20-base", "0.1.0").unwrap();
+
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { admin: None }).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidContractName {
+                expected: CONTRACT_NAME.to_string(),
+                found: "crates.io:cw20-base".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_migrate_legacy_statuses() {
+        let mut deps = mock_dependencies();
+        cw2::set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.2.0").unwrap();
+
+        // 0.2.0 let a loan be rejected and then approved, keeping both dates
+        deps.storage.set(
+            &LOAN_STORAGE_V1.key(("creator", "loan2")),
+            br#"{"loan_id":"loan2","template_id":"template1","values":{},"review_status":"Approved","creation_date":100,"approval_date":300,"rejection_date":200}"#,
+        );
+        deps.storage.set(
+            &LOAN_STORAGE_V1.key(("creator", "loan3")),
+            br#"{"loan_id":"loan3","template_id":"template1","values":{},"review_status":"Pending","creation_date":100,"approval_date":null,"rejection_date":null}"#,
+        );
+        deps.storage.set(
+            &USER_TEMPLATES_V1.key(("creator", "template2")),
+            br#"{"id":"template2","name":"Car Loan","fields":{},"submitter":"creator","reviewer":null,"review_status":"Reviewed"}"#,
+        );
+
+        migrate(deps.as_mut(), mock_env(), MigrateMsg { admin: None }).unwrap();
+
+        let loan = LOAN_STORAGE.load(&deps.storage, ("creator", "loan2")).unwrap();
+        assert_eq!(loan.status, LoanStatus::Approved);
+        assert_eq!(loan.approval_date, Some(300));
+        assert_eq!(loan.rejection_date, None);
+
+        let loan = LOAN_STORAGE.load(&deps.storage, ("creator", "loan3")).unwrap();
+        assert_eq!(loan.status, LoanStatus::Submitted);
+        assert_eq!(loan.status_history.len(), 1);
+
+        let template = USER_TEMPLATES.load(&deps.storage, ("creator", "template2")).unwrap();
+        assert_eq!(template.review_status, ReviewStatus::Pending);
+    }
+
+    #[test]
+    fn test_migrate_moves_reviewer_assignments_onto_loans() {
+        let mut deps = mock_dependencies();
+        cw2::set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.2.0").unwrap();
+        store_legacy_state(&mut deps.storage);
+
+        // Overwritten lists could name missing loans or share a loan between reviewers
+        let loan = ("creator".to_string(), "loan1".to_string());
+        let missing = ("creator".to_string(), "missing".to_string());
+        REVIEWER_ASSIGNMENTS_V5
+            .save(&mut deps.storage, &addr("reviewer1"), &vec![loan.clone(), missing])
+            .unwrap();
+        REVIEWER_ASSIGNMENTS_V5
+            .save(&mut deps.storage, &addr("reviewer2"), &vec![loan.clone()])
+            .unwrap();
+
+        migrate(deps.as_mut(), mock_env(), MigrateMsg { admin: None }).unwrap();
+
+        let (first, second) = if addr("reviewer1") < addr("reviewer2") {
+            (addr("reviewer1"), addr("reviewer2"))
+        } else {
+            (addr("reviewer2"), addr("reviewer1"))
+        };
+        let stored = LOAN_STORAGE.load(&deps.storage, ("creator", "loan1")).unwrap();
+        assert_eq!(stored.reviewer, Some(first.clone()));
+        assert!(REVIEWER_ASSIGNMENTS_V5.is_empty(&deps.storage));
+
+        // The rebuilt indexes answer the loan queries
+        let loans_for = |reviewer: &Addr| -> Vec<(String, String)> {
+            let msg = QueryMsg::GetLoansForReviewer {
+                reviewer: reviewer.to_string(),
+                include_values: None,
+                start_after: None,
+                limit: None,
+                order: None,
+            };
+            let page: Page<LoanSummary, (String, String)> =
+                from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+            page.items.into_iter().map(|summary| (summary.owner, summary.loan_id)).collect()
+        };
+        assert_eq!(loans_for(&first), vec![loan.clone()]);
+        assert!(loans_for(&second).is_empty());
+        let msg = QueryMsg::GetLoansByStatus {
+            status: LoanStatus::Approved,
+            include_values: None,
+            start_after: None,
+            limit: None,
+            order: None,
+        };
+        let approved: Page<LoanSummary, (String, String)> =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(approved.items.len(), 1);
+        assert_eq!(approved.items[0].loan_id, loan.1);
+        assert_eq!(approved.items[0].amount, Some("60000".to_string()));
+    }
+
+    #[test]
+    fn test_migrate_starts_loan_counter_beside_uuid_keys() {
+        let mut deps = mock_dependencies();
+        deps.storage.set(
+            &LOAN_STORAGE_V1.key(("creator", "6f1c2a4e-8d1b-4f3e-9a7c-2b5d8e0f1a3c")),
+            br#"{"loan_id":"6f1c2a4e-8d1b-4f3e-9a7c-2b5d8e0f1a3c","template_id":"template1","values":{},"review_status":"Pending","creation_date":100,"approval_date":null,"rejection_date":null}"#,
+        );
+
+        let msg = MigrateMsg {
+            admin: Some(addr("admin").to_string()),
+        };
+        migrate(deps.as_mut(), mock_env(), msg).unwrap();
+
+        assert_eq!(NEXT_LOAN_ID.load(&deps.storage).unwrap(), 1);
+        let loan = LOAN_STORAGE
+            .load(&deps.storage, ("creator", "6f1c2a4e-8d1b-4f3e-9a7c-2b5d8e0f1a3c"))
+            .unwrap();
+        assert_eq!(loan.status, LoanStatus::Submitted);
+    }
+
+    #[test]
+    fn test_migrate_finds_template_owner_of_each_loan() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            admin: addr("admin").to_string(),
+            reviewers: vec![addr("reviewer1").to_string()],
+            loan_officers: vec![addr("officer_a").to_string(), addr("officer_b").to_string()],
+            auditors: vec![],
+        };
+        instantiate(deps.as_mut(), mock_env(), message_info(&addr("admin"), &[]), msg).unwrap();
+
+        // officer_a owns "shared"; both officers have a template called "dup"
+        for (owner, template_id) in [("officer_a", "shared"), ("officer_a", "dup"), ("officer_b", "dup")] {
+            let msg = ExecuteMsg::CreateLoanTemplate {
+                template_id: template_id.to_string(),
+                name: "Home Loan".to_string(),
+                fields: HashMap::new(),
+                rules: vec![],
+            };
+            execute(deps.as_mut(), mock_env(), message_info(&addr(owner), &[]), msg).unwrap();
+            let msg = ExecuteMsg::SubmitTemplateForReview {
+                template_id: template_id.to_string(),
+                reviewer: addr("reviewer1").to_string(),
+            };
+            execute(deps.as_mut(), mock_env(), message_info(&addr(owner), &[]), msg).unwrap();
+            let msg = ExecuteMsg::ReviewTemplate {
+                user_id: addr(owner).to_string(),
+                template_id: template_id.to_string(),
+                approve: true,
+                comment: None,
+            };
+            execute(deps.as_mut(), mock_env(), message_info(&addr("reviewer1"), &[]), msg).unwrap();
+        }
+
+        // officer_b files loans from officer_a's templates
+        let msg = ExecuteMsg::CreateLoan {
+            user_id: addr("officer_a").to_string(),
+            loan_requests: ["shared", "dup"]
+                .iter()
+                .map(|template_id| LoanRequest {
+                    template_id: template_id.to_string(),
+                    values: HashMap::new(),
+                    draft: false,
+                })
+                .collect(),
+        };
+        execute(deps.as_mut(), mock_env(), message_info(&addr("officer_b"), &[]), msg).unwrap();
+
+        // Loans stored by 0.7.0 did not know their template's owner
+        let officer_b = addr("officer_b").to_string();
+        for loan_id in ["1", "2"] {
+            let key = loan_key(loan_id);
+            let mut loan = LOAN_STORAGE.load(&deps.storage, (&officer_b, &key)).unwrap();
+            loan.template_owner = String::new();
+            LOAN_STORAGE.save(&mut deps.storage, (&officer_b, &key), &loan).unwrap();
+        }
+        cw2::set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.7.0").unwrap();
+
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { admin: None }).unwrap();
+        assert!(res
+            .attributes
+            .contains(&attr("unresolved_template_owners", format!("{}/2", officer_b))));
+
+        let loan = LOAN_STORAGE.load(&deps.storage, (&officer_b, &loan_key("1"))).unwrap();
+        assert_eq!(loan.template_owner, addr("officer_a").to_string());
+        let loan = LOAN_STORAGE.load(&deps.storage, (&officer_b, &loan_key("2"))).unwrap();
+        assert_eq!(loan.template_owner, "");
+    }
+
+    #[test]
+    fn test_migrate_pads_decimal_loan_keys() {
+        let mut deps = setup();
+        approved_template(&mut deps, "t1", Vec::<(&str, FieldType)>::new(), vec![]);
+        for _ in 0..11 {
+            create_loan(&mut deps, "t1", vec![]).unwrap();
+        }
+        let officer = addr("creator");
+
+        // 0.10.0 stored loans under their bare decimal ID
+        for id in 1..=11 {
+            let loan_id = id.to_string();
+            let key = loan_key(&loan_id);
+            let loan = LOAN_STORAGE.load(&deps.storage, (officer.as_str(), &key)).unwrap();
+            LOAN_STORAGE.replace(&mut deps.storage, (officer.as_str(), &key), None, Some(&loan)).unwrap();
+            LOAN_STORAGE.replace(&mut deps.storage, (officer.as_str(), &loan_id), Some(&loan), None).unwrap();
+        }
+        // and kept revisions under the ID as each amendment spelled it, each spelling from 1
+        let revision = |revision, timestamp| LoanRevision {
+            revision,
+            values: HashMap::new(),
+            changes: vec![],
+            amended_by: officer.clone(),
+            timestamp,
+        };
+        LOAN_REVISIONS.save(&mut deps.storage, ("3", 1), &revision(1, 100)).unwrap();
+        LOAN_REVISIONS.save(&mut deps.storage, ("3", 2), &revision(2, 300)).unwrap();
+        LOAN_REVISIONS.save(&mut deps.storage, ("03", 1), &revision(1, 200)).unwrap();
+        cw2::set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.10.0").unwrap();
+
+        migrate(deps.as_mut(), mock_env(), MigrateMsg { admin: None }).unwrap();
+
+        assert!(!LOAN_STORAGE.has(&deps.storage, (officer.as_str(), "10")));
+        let msg = QueryMsg::GetLoansByStatus {
+            status: LoanStatus::Submitted,
+            include_values: None,
+            start_after: None,
+            limit: Some(30),
+            order: None,
+        };
+        let page: Page<LoanSummary, (String, String)> =
+            from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        let loan_ids: Vec<String> = page.items.into_iter().map(|loan| loan.loan_id).collect();
+        assert_eq!(loan_ids, (1..=11).map(|id| id.to_string()).collect::<Vec<_>>());
+        assert_eq!(STATUS_COUNTS.load(&deps.storage, "Submitted").unwrap(), 11);
+
+        // Both spellings now make one history, renumbered in the order the amendments were made
+        let history: Vec<(u32, u64)> = LOAN_REVISIONS
+            .prefix(&loan_key("3"))
+            .range(&deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(revision, entry)| (revision, entry.timestamp)))
+            .collect::<StdResult<_>>()
+            .unwrap();
+        assert_eq!(history, vec![(1, 100), (2, 200), (3, 300)]);
+        let stored = LOAN_REVISIONS.load(&deps.storage, (&loan_key("3"), 2)).unwrap();
+        assert_eq!(stored.revision, 2);
+        assert!(!LOAN_REVISIONS.has(&deps.storage, ("3", 1)));
+        assert!(!LOAN_REVISIONS.has(&deps.storage, ("03", 1)));
+    }
+
+    #[test]
+    fn test_migrate_flags_date_formats_no_date_can_match() {
+        let mut deps = mock_dependencies();
+        cw2::set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.11.0").unwrap();
+        let date = |format: &str| FieldType::Date {
+            is_editable: false,
+            required: true,
+            default: None,
+            format: Some(format.to_string()),
+            min_value: None,
+            max_value: None,
+        };
+        let fields = HashMap::from([
+            ("iso".to_string(), date(r"^\d{4}-\d{2}-\d{2}$")),
+            ("slashed".to_string(), date(r"^\d{2}/\d{2}/\d{4}$")),
+            (
+                "history".to_string(),
+                FieldType::List {
+                    is_editable: false,
+                    required: true,
+                    item: Box::new(FieldType::Object {
+                        is_editable: false,
+                        required: true,
+                        fields: HashMap::from([("on".to_string(), date("^[A-Z][a-z]{2} \\d{4}$"))]),
+                    }),
+                    min_items: None,
+                    max_items: None,
+                },
+            ),
+        ]);
+        let template = LoanTemplate {
+            id: "t1".to_string(),
+            name: "Home Loan".to_string(),
+            fields,
+            submitter: "creator".to_string(),
+            reviewer: None,
+            review_status: ReviewStatus::Approved,
+            rules: vec![],
+            version: 1,
+        };
+        USER_TEMPLATES.save(&mut deps.storage, ("creator", "t1"), &template).unwrap();
+        TEMPLATE_VERSIONS.save(&mut deps.storage, ("creator", "t1", 1), &template).unwrap();
+
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { admin: None }).unwrap();
+        assert!(res
+            .attributes
+            .contains(&attr("date_formats_to_review", "creator/t1/history[].on,creator/t1/slashed")));
+    }
+
+    #[test]
+    fn test_migrate_keys_template_reviewers_by_owner() {
+        let mut deps = mock_dependencies();
+        cw2::set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.9.0").unwrap();
+        let pending = |submitter: &str| LoanTemplate {
+            id: "template1".to_string(),
+            name: "Home Loan".to_string(),
+            fields: HashMap::new(),
+            submitter: submitter.to_string(),
+            reviewer: Some("reviewer1".to_string()),
+            review_status: ReviewStatus::Pending,
+            rules: vec![],
+            version: 1,
+        };
+        // Both officers submitted "template1"; the second submission overwrote the first entry
+        USER_TEMPLATES.save(&mut deps.storage, ("officer_a", "template1"), &pending("officer_a")).unwrap();
+        USER_TEMPLATES.save(&mut deps.storage, ("officer_b", "template1"), &pending("officer_b")).unwrap();
+        let tuple = ReviewTuple {
+            reviewer: "reviewer1".to_string(),
+            creater: "officer_b".to_string(),
+        };
+        TEMPLATE_REVIEWERS_V9.save(&mut deps.storage, "template1", &tuple).unwrap();
+
+        migrate(deps.as_mut(), mock_env(), MigrateMsg { admin: None }).unwrap();
+
+        for owner in ["officer_a", "officer_b"] {
+            let tuple = TEMPLATE_REVIEWERS.load(&deps.storage, (owner, "template1")).unwrap();
+            assert_eq!(tuple.reviewer, "reviewer1");
+            assert_eq!(tuple.creater, owner);
+        }
+        let assigned: Vec<_> = TEMPLATE_REVIEWERS
+            .idx
+            .reviewer
+            .prefix("reviewer1".to_string())
+            .keys(&deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<_>>()
+            .unwrap();
+        assert_eq!(assigned.len(), 2);
+    }
+
+    #[test]
+    fn test_migrate_credits_decisions_to_their_decider() {
+        let mut deps = setup();
+        approved_template(&mut deps, "t1", Vec::<(&str, FieldType)>::new(), vec![]);
+        create_loan(&mut deps, "t1", vec![]).unwrap();
+        let creator = addr("creator").to_string();
+        let msg = ExecuteMsg::AssignLoans {
+            reviewer: addr("reviewer1").to_string(),
+            loans: vec![(creator.clone(), "1".to_string())],
+        };
+        execute(deps.as_mut(), mock_env(), message_info(&addr("admin"), &[]), msg).unwrap();
+        for (sender, new_status) in [("reviewer1", LoanStatus::UnderReview), ("admin", LoanStatus::Approved)] {
+            let msg = ExecuteMsg::UpdateLoanReviewStatus {
+                user_id: creator.clone(),
+                loan_id: "1".to_string(),
+                new_status,
+                reason: None,
+            };
+            execute(deps.as_mut(), mock_env(), message_info(&addr(sender), &[]), msg).unwrap();
+        }
+
+        // 0.12.0 had no decider index and credited the decision to the assigned reviewer
+        let key = loan_key("1");
+        let loan = LOAN_STORAGE.load(&deps.storage, (&creator, &key)).unwrap();
+        let pk = (creator.as_str(), key.as_str()).joined_key();
+        LOAN_STORAGE.idx.decider.remove(&mut deps.storage, &pk, &loan).unwrap();
+        let month = YearMonth::of(mock_env().block.time.seconds(), 0).to_string();
+        REVIEWER_DECISIONS.clear(&mut deps.storage);
+        REVIEWER_DECISIONS
+            .save(&mut deps.storage, (addr("reviewer1").as_str(), &month, "approved"), &1)
+            .unwrap();
+        cw2::set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.12.0").unwrap();
+
+        migrate(deps.as_mut(), mock_env(), MigrateMsg { admin: None }).unwrap();
+
+        let decisions: Vec<_> = REVIEWER_DECISIONS
+            .keys(&deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<_>>()
+            .unwrap();
+        assert_eq!(decisions, vec![(addr("admin").to_string(), month, "approved".to_string())]);
+        let msg = QueryMsg::GetLoanStatistics {
+            reviewer: Some(addr("admin").to_string()),
+            utc_offset_minutes: None,
+        };
+        let scanned: LoanStatistics = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(scanned.approved_this_month, 1);
+    }
+}
@@ -1,12 +1,60 @@
 use core::fmt;
+use std::cmp::Ordering;
 use std::collections::HashMap;
 
-use cosmwasm_std::Addr;
+use cosmwasm_std::{Addr, Decimal, Order};
+use cw_utils::Expiration;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, JsonSchema)]
-pub struct InstantiateMsg {}
+use crate::calendar::{civil_from_days, days_from_civil, format_date, YearMonth, SECONDS_PER_DAY};
+
+// Contract-wide configuration written at instantiate
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    pub admin: Option<Addr>, // contract administrator, always holds the Admin role; None once renounced
+}
+
+// Admin rotation waiting for the proposed address to accept it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AdminTransfer {
+    pub new_admin: Addr,
+    pub expiry: Option<Expiration>, // the proposal can no longer be accepted once expired
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Ownership {
+    pub admin: Option<Addr>,
+    pub pending_admin: Option<Addr>,
+    pub pending_expiry: Option<Expiration>,
+}
+
+// Roles that gate the execute handlers
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema, Hash)]
+pub enum Role {
+    Admin,
+    Reviewer,
+    LoanOfficer,
+    Auditor,
+}
+
+impl Role {
+    // Storage key used for the role registry
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Admin => "admin",
+            Role::Reviewer => "reviewer",
+            Role::LoanOfficer => "loan_officer",
+            Role::Auditor => "auditor",
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct IdentityMetadata {
@@ -17,31 +65,252 @@ pub struct IdentityMetadata {
     pub avatar: String, // 3d animated model ipfs hash
 }
 
-// Loan data structure with review status
+// Value of a loan field. Scalars stay strings so amounts keep their exact
+// digits; lists and objects hold the values of `List` and `Object` fields
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(untagged)]
+pub enum FieldValue {
+    Text(String),
+    List(Vec<FieldValue>),
+    Object(HashMap<String, FieldValue>),
+}
+
+impl FieldValue {
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            FieldValue::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for FieldValue {
+    fn from(text: &str) -> Self {
+        FieldValue::Text(text.to_string())
+    }
+}
+
+impl From<String> for FieldValue {
+    fn from(text: String) -> Self {
+        FieldValue::Text(text)
+    }
+}
+
+// Loan data structure with lifecycle status
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct LoanData {
     pub loan_id: String, // Unique loan ID for each loan
     pub template_id: String, // The template used for this loan
-    pub values: HashMap<String, String>, // Field values (stored as strings to handle multiple data types)
-    pub review_status: ReviewStatus, // Track the review status of each loan
+    #[serde(default)]
+    pub template_owner: String, // User the template is stored under
+    #[serde(default = "first_version")]
+    pub template_version: u32, // Approved template version the values were validated against
+    pub values: HashMap<String, FieldValue>, // Field values, nested to mirror the template's fields
+    pub status: LoanStatus, // Current lifecycle status of the loan
     pub creation_date: u64, // Unix timestamp when the loan was created
     pub approval_date: Option<u64>, // Unix timestamp when the loan was approved
     pub rejection_date: Option<u64>, // Unix timestamp when the loan was rejected
+    pub status_history: Vec<StatusTransition>, // Every status the loan has entered, oldest first
+    pub decided_by: Option<Addr>, // Reviewer (or admin) who approved or rejected the loan
+    pub decision_reason: Option<String>, // Optional reason given with the decision
+    pub reviewer: Option<Addr>, // Reviewer the loan is currently assigned to
 }
 
-// Define the possible review statuses for a loan
+// Template field treated as the loan's headline amount in summaries
+pub const AMOUNT_FIELD: &str = "amount";
+
+// Compact view of a loan returned by the listing queries
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LoanSummary {
+    pub owner: String, // User the loan is stored under
+    pub loan_id: String,
+    pub template_id: String,
+    pub status: LoanStatus,
+    pub creation_date: u64,
+    pub approval_date: Option<u64>,
+    pub rejection_date: Option<u64>,
+    pub amount: Option<String>, // Value of the loan's "amount" field, if its template has one
+    pub loan: Option<LoanData>, // Full loan record, only when the query sets `include_values`
+}
+
+impl LoanSummary {
+    pub fn new(owner: String, loan: LoanData, include_values: bool) -> Self {
+        LoanSummary {
+            owner,
+            loan_id: loan.loan_id.clone(),
+            template_id: loan.template_id.clone(),
+            status: loan.status,
+            creation_date: loan.creation_date,
+            approval_date: loan.approval_date,
+            rejection_date: loan.rejection_date,
+            amount: loan.values.get(AMOUNT_FIELD).and_then(FieldValue::as_text).map(str::to_string),
+            loan: include_values.then_some(loan),
+        }
+    }
+}
+
+// Loan date a search can be bounded on
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+pub enum DateField {
+    Created,
+    Approved,
+    Rejected,
+}
+
+impl DateField {
+    // None while the loan has not reached the event, so undecided loans never match
+    pub fn date_of(&self, loan: &LoanData) -> Option<u64> {
+        match self {
+            DateField::Created => Some(loan.creation_date),
+            DateField::Approved => loan.approval_date,
+            DateField::Rejected => loan.rejection_date,
+        }
+    }
+}
+
+// Inclusive bounds on one of the loan dates
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct DateRange {
+    pub field: DateField,
+    pub from: Option<u64>, // Unix timestamp, inclusive
+    pub to: Option<u64>, // Unix timestamp, inclusive
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+pub enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+}
+
+impl Comparison {
+    pub fn holds(&self, ordering: Ordering) -> bool {
+        match self {
+            Comparison::Eq => ordering.is_eq(),
+            Comparison::Ne => ordering.is_ne(),
+            Comparison::Lt => ordering.is_lt(),
+            Comparison::Lte => ordering.is_le(),
+            Comparison::Gt => ordering.is_gt(),
+            Comparison::Gte => ordering.is_ge(),
+        }
+    }
+}
+
+// Compares a numeric loan field with a constant, e.g. `amount Gte 10000`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct NumericPredicate {
+    pub field: String, // Name of the template field
+    pub op: Comparison,
+    pub value: String, // Decimal constant the field value is compared with
+}
+
+// Criteria for SearchLoans; every criterion given must hold
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, JsonSchema)]
+pub struct LoanFilter {
+    pub owner: Option<String>, // User the loans are stored under
+    pub template_id: Option<String>,
+    #[serde(default)]
+    pub statuses: Vec<LoanStatus>, // Any of these statuses; empty matches every status
+    pub reviewer: Option<String>, // Currently assigned reviewer
+    pub date: Option<DateRange>,
+    #[serde(default)]
+    pub predicates: Vec<NumericPredicate>,
+}
+
+// Define the possible review statuses for a template
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, Hash)]
 pub enum ReviewStatus {
     Pending,
     Approved,
-    Reviewed,
     Rejected,
 }
 
+// Lifecycle of a loan; allowed moves are defined by `LoanStatus::next_statuses`
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema, Hash)]
+pub enum LoanStatus {
+    Draft,
+    Submitted,
+    UnderReview,
+    Approved,
+    Rejected,
+    Funded,
+    Repaying,
+    Closed,
+    Defaulted,
+    Cancelled,
+}
+
+impl LoanStatus {
+    // Transition table: the statuses reachable in one step from this one
+    pub fn next_statuses(&self) -> &'static [LoanStatus] {
+        use LoanStatus::*;
+        match self {
+            Draft => &[Submitted, Cancelled],
+            Submitted => &[UnderReview, Cancelled],
+            UnderReview => &[Approved, Rejected, Cancelled],
+            Approved => &[Funded, Cancelled],
+            Funded => &[Repaying],
+            Repaying => &[Closed, Defaulted],
+            Rejected | Closed | Defaulted | Cancelled => &[],
+        }
+    }
+
+    pub fn can_transition_to(&self, to: LoanStatus) -> bool {
+        self.next_statuses().contains(&to)
+    }
+
+    // Submitted or under review, i.e. still waiting for a decision
+    pub fn is_awaiting_decision(&self) -> bool {
+        matches!(self, LoanStatus::Submitted | LoanStatus::UnderReview)
+    }
+
+    // Review steps belong to reviewers, every other move to loan officers
+    pub fn transition_role(&self) -> Role {
+        match self {
+            LoanStatus::UnderReview | LoanStatus::Approved | LoanStatus::Rejected => Role::Reviewer,
+            _ => Role::LoanOfficer,
+        }
+    }
+}
+
+impl fmt::Display for LoanStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+// Values a loan held before one amendment, numbered from 1 per loan
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LoanRevision {
+    pub revision: u32,
+    pub values: HashMap<String, FieldValue>, // Every value as it was before the amendment
+    pub changes: Vec<FieldChange>, // Fields the amendment changed, by name
+    pub amended_by: Addr,
+    pub timestamp: u64, // Unix timestamp of the amendment
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: Option<FieldValue>, // None when the field was absent
+    pub after: Option<FieldValue>, // None when the amendment cleared the field
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StatusTransition {
+    pub status: LoanStatus, // Status the loan entered
+    pub timestamp: u64, // Unix timestamp of the transition
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct LoanRequest {
     pub template_id: String, // ID of the loan template
-    pub values: HashMap<String, String>, // Field values for the loan
+    pub values: HashMap<String, FieldValue>, // Field values for the loan
+    #[serde(default)]
+    pub draft: bool, // Keep the loan as a Draft instead of submitting it right away
 }
 
 
@@ -61,8 +330,12 @@ pub struct LoanStatistics {
     pub rejected_last_month: u64,
     pub approved_this_month: u64,
     pub approved_last_month: u64,
+    pub rejected_this_quarter: u64,
+    pub approved_this_quarter: u64,
+    pub rejected_this_year: u64,
+    pub approved_this_year: u64,
     pub average_time_to_process: Option<String>,  // in seconds
-    pub month_wise_status_count: HashMap<String, HashMap<String, u64>>, // Month -> {Status -> Count}
+    pub month_wise_status_count: HashMap<String, HashMap<String, u64>>, // "YYYY-MM" -> {Status -> Count}
 }
 
 
@@ -74,28 +347,333 @@ pub struct AllReviewerStatistics {
     pub reviewers_stats: Vec<LoanStatistics>,
 }
 
+// Running totals maintained as loans are created and change status
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LoanCounters {
+    pub by_status: HashMap<String, u64>,                      // Status -> Count
+    pub by_template: HashMap<String, HashMap<String, u64>>,   // Template -> {Status -> Count}
+    pub by_month: HashMap<String, HashMap<String, u64>>,      // UTC creation "YYYY-MM" -> {Status -> Count}
+    pub by_reviewer: HashMap<String, HashMap<String, u64>>,   // Reviewer ("" if unassigned) -> {Status -> Count}
+}
+
+// Width of a time series bucket, in UTC
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+pub enum Granularity {
+    Day,
+    Week,  // Monday to Sunday
+    Month, // Calendar month
+}
+
+impl Granularity {
+    // Start of the bucket holding `timestamp`
+    pub fn bucket_start(&self, timestamp: u64) -> u64 {
+        let days = timestamp as i64 / SECONDS_PER_DAY;
+        let start_day = match self {
+            Granularity::Day => days,
+            // 1970-01-01 was a Thursday
+            Granularity::Week => days - (days + 3).rem_euclid(7),
+            Granularity::Month => {
+                let (year, month, _) = civil_from_days(days);
+                days_from_civil(year, month, 1)
+            }
+        };
+        start_day.max(0) as u64 * SECONDS_PER_DAY as u64
+    }
+
+    // Start of the bucket after the one starting at `start`
+    pub fn next_bucket_start(&self, start: u64) -> u64 {
+        match self {
+            Granularity::Day => start + SECONDS_PER_DAY as u64,
+            Granularity::Week => self.bucket_start(start) + 7 * SECONDS_PER_DAY as u64,
+            Granularity::Month => {
+                let ym = YearMonth::of(start, 0);
+                let (year, month) = if ym.month == 12 { (ym.year + 1, 1) } else { (ym.year, ym.month + 1) };
+                days_from_civil(year, month, 1) as u64 * SECONDS_PER_DAY as u64
+            }
+        }
+    }
+
+    // `YYYY-MM` for months, otherwise the `YYYY-MM-DD` the bucket starts on
+    pub fn label(&self, start: u64) -> String {
+        match self {
+            Granularity::Month => YearMonth::of(start, 0).to_string(),
+            Granularity::Day | Granularity::Week => format_date(start),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+pub enum GroupBy {
+    Status,   // Current status of the loan
+    Template,
+    Reviewer, // "" for unassigned loans
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TimeSeriesGroup {
+    pub key: String,
+    pub created: u64,
+    pub approved: u64,
+    pub rejected: u64,
+    pub approval_rate: Option<Decimal>,           // approved / (approved + rejected)
+    pub average_time_to_decision: Option<u64>,    // in seconds, over decisions in the bucket
+    pub median_time_to_decision: Option<u64>,     // in seconds
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TimeSeriesBucket {
+    pub start: u64,    // Unix timestamp
+    pub label: String,
+    pub groups: Vec<TimeSeriesGroup>, // Sorted by key, only groups with activity
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LoanTimeSeries {
+    pub granularity: Granularity,
+    pub group_by: GroupBy,
+    pub buckets: Vec<TimeSeriesBucket>, // Every bucket from `from` to `to`, including empty ones
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub enum FieldType {
     String {
         is_editable: bool,
-        format: Option<String>,
-        min_value: Option<String>, // Minimum allowed value (optional)
-        max_value: Option<String>, // Regex or specific format (optional)
+        #[serde(default = "required_by_default")]
+        required: bool,
+        #[serde(default)]
+        default: Option<String>,
+        format: Option<String>,    // Regex the value must match (optional)
+        min_value: Option<String>, // Minimum length in characters (optional)
+        max_value: Option<String>, // Maximum length in characters (optional)
     },
     Number {
         is_editable: bool,
+        #[serde(default = "required_by_default")]
+        required: bool,
+        #[serde(default)]
+        default: Option<String>,
         min_value: Option<String>, // Minimum allowed value (optional)
         max_value: Option<String>, // Maximum allowed value (optional)
     },
+    // Non-negative whole number, up to Uint256
+    Integer {
+        is_editable: bool,
+        #[serde(default = "required_by_default")]
+        required: bool,
+        #[serde(default)]
+        default: Option<String>,
+        min_value: Option<String>,
+        max_value: Option<String>,
+    },
+    // Non-negative decimal with at most `precision` fractional digits (up to 18)
+    Decimal {
+        is_editable: bool,
+        #[serde(default = "required_by_default")]
+        required: bool,
+        #[serde(default)]
+        default: Option<String>,
+        precision: u32,
+        min_value: Option<String>,
+        max_value: Option<String>,
+    },
     Boolean {
         is_editable: bool,
+        #[serde(default = "required_by_default")]
+        required: bool,
+        #[serde(default)]
+        default: Option<String>,
     },
     Date {
         is_editable: bool,
-        format: Option<String>,
-        min_value: Option<String>, // Minimum allowed value (optional)
-        max_value: Option<String>, // Format (optional) or expected timestamp
+        #[serde(default = "required_by_default")]
+        required: bool,
+        #[serde(default)]
+        default: Option<String>,
+        format: Option<String>,    // Regex a value must also match once it reads as a date (optional)
+        min_value: Option<String>, // Earliest date, absolute or relative like "now-18y" (optional)
+        max_value: Option<String>, // Latest date, absolute or relative like "now+30d" (optional)
+    },
+    // One of a fixed list of values
+    Enum {
+        is_editable: bool,
+        #[serde(default = "required_by_default")]
+        required: bool,
+        #[serde(default)]
+        default: Option<String>,
+        options: Vec<String>,
+    },
+    // Bech32 account address accepted by the chain
+    Address {
+        is_editable: bool,
+        #[serde(default = "required_by_default")]
+        required: bool,
+        #[serde(default)]
+        default: Option<String>,
+    },
+    // Amount and denom written together, e.g. "1000uatom"
+    Coin {
+        is_editable: bool,
+        #[serde(default = "required_by_default")]
+        required: bool,
+        #[serde(default)]
+        default: Option<String>,
+        allowed_denoms: Vec<String>, // Any denom when empty
+    },
+    // Percent with up to two decimals, e.g. "12.25"; bounds are in basis points (1% = 100)
+    Percentage {
+        is_editable: bool,
+        #[serde(default = "required_by_default")]
+        required: bool,
+        #[serde(default)]
+        default: Option<String>,
+        min_bps: Option<u32>,
+        max_bps: Option<u32>, // At most 10000
+    },
+    Email {
+        is_editable: bool,
+        #[serde(default = "required_by_default")]
+        required: bool,
+        #[serde(default)]
+        default: Option<String>,
+    },
+    // E.164, e.g. "+14155550100"
+    PhoneNumber {
+        is_editable: bool,
+        #[serde(default = "required_by_default")]
+        required: bool,
+        #[serde(default)]
+        default: Option<String>,
+    },
+    // ISO 3166-1 alpha-2, e.g. "DE"
+    CountryCode {
+        is_editable: bool,
+        #[serde(default = "required_by_default")]
+        required: bool,
+        #[serde(default)]
+        default: Option<String>,
+    },
+    // Group of named sub-fields, filled with a JSON object
+    Object {
+        is_editable: bool,
+        #[serde(default = "required_by_default")]
+        required: bool,
+        fields: HashMap<String, FieldType>,
     },
+    // Sequence of values that all match `item`, filled with a JSON array
+    List {
+        is_editable: bool,
+        #[serde(default = "required_by_default")]
+        required: bool,
+        item: Box<FieldType>,
+        min_items: Option<u32>,
+        max_items: Option<u32>,
+    },
+}
+
+// Templates stored before fields could be optional required every field
+fn required_by_default() -> bool {
+    true
+}
+
+// Templates and loans stored before versioning all belong to a template's first version
+fn first_version() -> u32 {
+    1
+}
+
+impl FieldType {
+    // Whether `AmendLoan` may change the value after the loan is created
+    pub fn is_editable(&self) -> bool {
+        match self {
+            FieldType::String { is_editable, .. }
+            | FieldType::Number { is_editable, .. }
+            | FieldType::Integer { is_editable, .. }
+            | FieldType::Decimal { is_editable, .. }
+            | FieldType::Boolean { is_editable, .. }
+            | FieldType::Date { is_editable, .. }
+            | FieldType::Enum { is_editable, .. }
+            | FieldType::Address { is_editable, .. }
+            | FieldType::Coin { is_editable, .. }
+            | FieldType::Percentage { is_editable, .. }
+            | FieldType::Email { is_editable, .. }
+            | FieldType::PhoneNumber { is_editable, .. }
+            | FieldType::CountryCode { is_editable, .. }
+            | FieldType::Object { is_editable, .. }
+            | FieldType::List { is_editable, .. } => *is_editable,
+        }
+    }
+
+    pub fn required(&self) -> bool {
+        match self {
+            FieldType::String { required, .. }
+            | FieldType::Number { required, .. }
+            | FieldType::Integer { required, .. }
+            | FieldType::Decimal { required, .. }
+            | FieldType::Boolean { required, .. }
+            | FieldType::Date { required, .. }
+            | FieldType::Enum { required, .. }
+            | FieldType::Address { required, .. }
+            | FieldType::Coin { required, .. }
+            | FieldType::Percentage { required, .. }
+            | FieldType::Email { required, .. }
+            | FieldType::PhoneNumber { required, .. }
+            | FieldType::CountryCode { required, .. }
+            | FieldType::Object { required, .. }
+            | FieldType::List { required, .. } => *required,
+        }
+    }
+
+    // Value filled in when a loan leaves the field out
+    pub fn default_value(&self) -> Option<&str> {
+        match self {
+            FieldType::String { default, .. }
+            | FieldType::Number { default, .. }
+            | FieldType::Integer { default, .. }
+            | FieldType::Decimal { default, .. }
+            | FieldType::Boolean { default, .. }
+            | FieldType::Date { default, .. }
+            | FieldType::Enum { default, .. }
+            | FieldType::Address { default, .. }
+            | FieldType::Coin { default, .. }
+            | FieldType::Percentage { default, .. }
+            | FieldType::Email { default, .. }
+            | FieldType::PhoneNumber { default, .. }
+            | FieldType::CountryCode { default, .. } => default.as_deref(),
+            // Nested values have no text form; their sub-fields carry the defaults
+            FieldType::Object { .. } | FieldType::List { .. } => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldErrorCode {
+    Missing, // A required field was left out
+    Unknown, // The template has no such field
+    Format,  // The value does not have the field's form
+    Range,   // The value is well formed but not allowed
+    Type,    // The value is the wrong kind, e.g. a list for a number
+    Rule,    // A cross-field rule failed
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct FieldError {
+    pub field: String, // Path of the value, e.g. "collateral[2].value", or the rule ID
+    pub code: FieldErrorCode,
+    pub message: String,
+}
+
+// Outcome of checking loan values without creating the loan
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct LoanValidation {
+    pub valid: bool,
+    pub errors: Vec<FieldError>,
+}
+
+// Outcome of checking field definitions without storing a template
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct TemplateValidation {
+    pub valid: bool,
+    pub problems: Vec<String>, // One message per problem, in field name order
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -106,9 +684,55 @@ pub struct LoanTemplate {
     pub submitter: String, // The user who created the template
     pub reviewer: Option<String>, // The reviewer assigned to review this template
     pub review_status: ReviewStatus, // Review status (Pending, Approved, Rejected)
+    #[serde(default)]
+    pub rules: Vec<ValidationRule>, // Cross-field checks, run after each field is validated
+    #[serde(default = "first_version")]
+    pub version: u32, // Numbered from 1; approved versions are kept unchanged in TEMPLATE_VERSIONS
 }
+
+// Cross-field check on a loan's values, e.g. "down_payment <= amount * 0.2"
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ValidationRule {
+    pub id: String, // Names the rule in errors; unique within the template
+    pub expression: String,
+    pub message: Option<String>, // Error shown when the rule fails, the expression by default
+}
+// One decision on a template version; a template's reviews are numbered from 1
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct TemplateReview {
+    pub review: u32,
+    pub version: u32, // Template version the decision was made on
+    pub reviewer: Addr,
+    pub decision: ReviewStatus, // Approved or Rejected
+    pub comment: Option<String>,
+    pub timestamp: u64, // Unix timestamp of the decision
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct ReviewTuple{
     pub reviewer: String,
     pub creater: String,
-}
\ No newline at end of file
+}
+
+// Sort direction for paginated queries
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+impl From<SortOrder> for Order {
+    fn from(order: SortOrder) -> Self {
+        match order {
+            SortOrder::Ascending => Order::Ascending,
+            SortOrder::Descending => Order::Descending,
+        }
+    }
+}
+
+// One page of a listing query; pass `next_start_after` back as `start_after` for the next page
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Page<T, C> {
+    pub items: Vec<T>, // At most `limit` items, in the requested order
+    pub next_start_after: Option<C>, // Cursor of the last item, None once there is nothing left
+}
@@ -1,15 +1,30 @@
 use std::collections::HashMap;
 
 use crate::models::{
-    AllReviewerStatistics, FieldType, IdentityMetadata, LoanData, LoanRequest, LoanStatistics, LoanTemplate, ReviewStatus
+    AllReviewerStatistics, Config, FieldType, FieldValue, Granularity, GroupBy, IdentityMetadata, LoanCounters, LoanData, LoanFilter, LoanRequest, LoanRevision, LoanStatistics, LoanStatus, LoanSummary, LoanTemplate, LoanTimeSeries, LoanValidation, Ownership, Page, Role, SortOrder, TemplateReview, TemplateValidation, ValidationRule
 };
 use cosmwasm_schema::{cw_serde, QueryResponses};
 use cosmwasm_std::Addr;
+use cw_utils::Expiration;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub enum InstantiateMsg {}
+pub struct InstantiateMsg {
+    pub admin: String, // contract administrator
+    #[serde(default)]
+    pub reviewers: Vec<String>, // initial holders of the Reviewer role
+    #[serde(default)]
+    pub loan_officers: Vec<String>, // initial holders of the LoanOfficer role
+    #[serde(default)]
+    pub auditors: Vec<String>, // initial holders of the Auditor role
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {
+    #[serde(default)]
+    pub admin: Option<String>, // required when migrating a deployment that predates the admin config
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -21,28 +36,70 @@ pub enum ExecuteMsg {
         user_id: String,
         loan_requests: Vec<LoanRequest>,
     },
-    AssignLoansToReviewer {
+    AssignLoans {
+        reviewer: String,
+        loans: Vec<(String, String)>, // (user_id, loan_id) pairs added to the reviewer's queue
+    },
+    UnassignLoans {
         reviewer: String,
         loans: Vec<(String, String)>,
     },
+    ReassignLoan {
+        user_id: String,
+        loan_id: String,
+        from: String,
+        to: String,
+    },
     UpdateLoanReviewStatus {
         user_id: String,
         loan_id: String,
-        new_status: ReviewStatus,
+        new_status: LoanStatus,
+        reason: Option<String>, // Recorded on the loan with approval or rejection decisions
+    },
+    AmendLoan {
+        loan_id: String,
+        changes: HashMap<String, Option<FieldValue>>, // New value per field; null clears an optional field
     },
     CreateLoanTemplate {
         template_id: String,
         name: String,
         fields: HashMap<String, FieldType>,
+        #[serde(default)]
+        rules: Vec<ValidationRule>,
     },
+    UpdateLoanTemplate {
+        template_id: String,
+        name: String,
+        fields: HashMap<String, FieldType>,
+        #[serde(default)]
+        rules: Vec<ValidationRule>,
+    }, // Edits the draft version, or starts a new one once the latest is approved
     SubmitTemplateForReview {
         template_id: String,
         reviewer: String,
     },
     ReviewTemplate {
+        user_id: String, // Owner of the template
         template_id: String,
         approve: bool,
+        comment: Option<String>, // Kept in the template's review history, e.g. why it was rejected
+    },
+    GrantRole {
+        role: Role,
+        address: String,
+    },
+    RevokeRole {
+        role: Role,
+        address: String,
     },
+    ProposeAdminTransfer {
+        new_admin: String,
+        expiry: Option<Expiration>,
+    },
+    AcceptAdminTransfer {},
+    CancelAdminTransfer {},
+    RenounceAdmin {},
+    RecomputeStatistics {}, // Admin only; rebuilds the running loan counters from the stored loans
 }
 
 #[cw_serde]
@@ -50,29 +107,137 @@ pub enum ExecuteMsg {
 pub enum QueryMsg {
     #[returns(IdentityMetadata)]
     UserInfo { address: Addr },
-    #[returns(Vec<(Addr, IdentityMetadata)>)]
-    UserInfoAll {},
-    #[returns(Vec<LoanData>)]
-    GetLoansForUser { user_id: String },
+    #[returns(Page<(Addr, IdentityMetadata), String>)]
+    UserInfoAll {
+        start_after: Option<String>, // Address to continue after
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    },
+    #[returns(Page<LoanSummary, String>)]
+    GetLoansForUser {
+        user_id: String,
+        include_values: Option<bool>, // Attach the full LoanData to each summary
+        start_after: Option<String>, // Loan ID to continue after
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    },
     #[returns(LoanData)]
     GetLoanDetails { user_id: String, loan_id: String },
-    #[returns(Vec<LoanData>)]
-    GetLoansForReviewer { reviewer: String },
-    #[returns(Vec<LoanData>)]
-    GetLoansByStatus { status: ReviewStatus },
-    #[returns(Vec<LoanData>)]
+    #[returns(Page<LoanRevision, u32>)]
+    GetLoanRevisions {
+        loan_id: String,
+        start_after: Option<u32>, // Revision number to continue after
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    },
+    #[returns(Page<LoanSummary, (String, String)>)]
+    GetLoansForReviewer {
+        reviewer: String,
+        include_values: Option<bool>,
+        start_after: Option<(String, String)>, // (user_id, loan_id) to continue after
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    },
+    #[returns(Page<LoanSummary, (String, String)>)]
+    GetLoansByStatus {
+        status: LoanStatus,
+        include_values: Option<bool>,
+        start_after: Option<(String, String)>, // (user_id, loan_id) to continue after
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    },
+    #[returns(Page<LoanSummary, (String, String)>)]
     GetLoansByDate {
         from_date: u64,    // Unix timestamp for filtering loans
         date_type: String, // "created", "approved", or "rejected"
+        include_values: Option<bool>,
+        start_after: Option<(String, String)>, // (user_id, loan_id) to continue after
+        limit: Option<u32>,
+        order: Option<SortOrder>,
     },
+    #[returns(Page<LoanSummary, (String, String)>)]
+    SearchLoans {
+        filter: LoanFilter,
+        include_values: Option<bool>,
+        start_after: Option<(String, String)>, // (user_id, loan_id) to continue after
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    },
+    #[returns(String)]
+    NextLoanId {},
+    #[returns(Option<Addr>)]
+    GetLoanReviewer { user_id: String, loan_id: String },
     #[returns(LoanStatistics)]
-    GetLoanStatistics { reviewer: Option<String> },
+    GetLoanStatistics {
+        reviewer: Option<String>,
+        utc_offset_minutes: Option<i32>, // calendar periods in local time, e.g. -300 for UTC-05:00; UTC by default
+    },
     #[returns(AllReviewerStatistics)]
     GetAllReviewerStatistics {},
+    #[returns(LoanCounters)]
+    GetLoanCounters {},
+    #[returns(LoanTimeSeries)]
+    GetLoanTimeSeries {
+        from: u64, // Unix timestamp, inclusive
+        to: u64,   // Unix timestamp, inclusive
+        granularity: Granularity,
+        group_by: GroupBy,
+    },
+
+    #[returns(Page<LoanTemplate, String>)]
+    GetUserTemplates {
+        user_id: String,
+        start_after: Option<String>, // Template ID to continue after
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    },
+
+    #[returns(Page<LoanTemplate, u32>)]
+    GetTemplateVersions {
+        user_id: String,
+        template_id: String,
+        start_after: Option<u32>, // Version to continue after
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    },
+
+    #[returns(Page<TemplateReview, u32>)]
+    GetTemplateReviewHistory {
+        user_id: String,
+        template_id: String,
+        start_after: Option<u32>, // Review number to continue after
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    },
+
+    #[returns(Page<LoanTemplate, (String, String)>)]
+    GetReviewerTemplates {
+        reviewer: String,
+        start_after: Option<(String, String)>, // (user_id, template_id) to continue after
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    },
+
+    #[returns(TemplateValidation)]
+    ValidateTemplate {
+        fields: HashMap<String, FieldType>,
+        #[serde(default)]
+        rules: Vec<ValidationRule>,
+    },
+
+    #[returns(LoanValidation)]
+    ValidateLoanRequest {
+        template_owner: String,
+        template_id: String,
+        values: HashMap<String, FieldValue>,
+    },
+
+    #[returns(Config)]
+    GetConfig {},
 
-    #[returns(Vec<LoanTemplate>)]
-    GetUserTemplates { user_id: String },
+    #[returns(Vec<Addr>)]
+    GetRoleMembers { role: Role },
 
-    #[returns(Vec<LoanTemplate>)]
-    GetReviewerTemplates { reviewer: String },
+    #[returns(Ownership)]
+    Ownership {},
 }
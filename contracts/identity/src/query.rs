@@ -6,114 +6,330 @@ use crate::msg::QueryMsg;
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::UserInfo { address } => to_json_binary(&query::query_identity(deps, address)?),
-        QueryMsg::UserInfoAll {} => to_json_binary(&query::query_all_identities(deps)?),
-        QueryMsg::GetLoansByStatus { status } => {
-            to_json_binary(&query::query_loans_by_status(deps, status)?)
-        }
-        QueryMsg::GetLoansForUser { user_id } => {
-            to_json_binary(&query::query_loans_for_user(deps, user_id)?)
-        }
+        QueryMsg::UserInfoAll {
+            start_after,
+            limit,
+            order,
+        } => to_json_binary(&query::query_all_identities(deps, start_after, limit, order)?),
+        QueryMsg::GetLoansByStatus {
+            status,
+            include_values,
+            start_after,
+            limit,
+            order,
+        } => to_json_binary(&query::query_loans_by_status(
+            deps,
+            status,
+            include_values.unwrap_or(false),
+            start_after,
+            limit,
+            order,
+        )?),
+        QueryMsg::GetLoansForUser {
+            user_id,
+            include_values,
+            start_after,
+            limit,
+            order,
+        } => to_json_binary(&query::query_loans_for_user(
+            deps,
+            user_id,
+            include_values.unwrap_or(false),
+            start_after,
+            limit,
+            order,
+        )?),
         QueryMsg::GetLoanDetails { user_id, loan_id } => {
             to_json_binary(&query::query_loan(deps, user_id, loan_id)?)
         }
-        QueryMsg::GetLoansForReviewer { reviewer } => {
-            to_json_binary(&query::query_loans_for_reviewer(deps, reviewer)?)
+        QueryMsg::GetLoanRevisions {
+            loan_id,
+            start_after,
+            limit,
+            order,
+        } => to_json_binary(&query::query_loan_revisions(deps, loan_id, start_after, limit, order)?),
+        QueryMsg::GetLoansForReviewer {
+            reviewer,
+            include_values,
+            start_after,
+            limit,
+            order,
+        } => to_json_binary(&query::query_loans_for_reviewer(
+            deps,
+            reviewer,
+            include_values.unwrap_or(false),
+            start_after,
+            limit,
+            order,
+        )?),
+        QueryMsg::SearchLoans {
+            filter,
+            include_values,
+            start_after,
+            limit,
+            order,
+        } => to_json_binary(&query::query_search_loans(
+            deps,
+            filter,
+            include_values.unwrap_or(false),
+            start_after,
+            limit,
+            order,
+        )?),
+        QueryMsg::NextLoanId {} => to_json_binary(&query::query_next_loan_id(deps)?),
+        QueryMsg::GetLoanReviewer { user_id, loan_id } => {
+            to_json_binary(&query::query_loan_reviewer(deps, user_id, loan_id)?)
         }
         QueryMsg::GetLoansByDate {
             from_date,
             date_type,
-        } => to_json_binary(&query::query_loans_by_date(deps, from_date, date_type)?),
-        QueryMsg::GetLoanStatistics { reviewer } => {
-            to_json_binary(&query::query_loan_statistics(deps, _env, reviewer)?)
+            include_values,
+            start_after,
+            limit,
+            order,
+        } => to_json_binary(&query::query_loans_by_date(
+            deps,
+            from_date,
+            date_type,
+            include_values.unwrap_or(false),
+            start_after,
+            limit,
+            order,
+        )?),
+        QueryMsg::GetLoanStatistics {
+            reviewer,
+            utc_offset_minutes,
+        } => {
+            to_json_binary(&query::query_loan_statistics(deps, _env, reviewer, utc_offset_minutes)?)
         },
         QueryMsg::GetAllReviewerStatistics {} => {
             to_json_binary(&query::query_all_reviewer_statistics(deps, _env)?)
         },
+        QueryMsg::ValidateTemplate { fields, rules } => {
+            to_json_binary(&query::query_validate_template(deps, _env, fields, rules))
+        }
+        QueryMsg::ValidateLoanRequest {
+            template_owner,
+            template_id,
+            values,
+        } => to_json_binary(&query::query_validate_loan_request(deps, _env, template_owner, template_id, values)?),
+        QueryMsg::GetLoanCounters {} => to_json_binary(&query::query_loan_counters(deps)?),
+        QueryMsg::GetLoanTimeSeries {
+            from,
+            to,
+            granularity,
+            group_by,
+        } => to_json_binary(&query::query_loan_time_series(deps, from, to, granularity, group_by)?),
         QueryMsg::GetUserTemplates {
             user_id,
+            start_after,
+            limit,
+            order,
         } => {
-            to_json_binary(&query::query_user_templates(deps, user_id)?)
+            to_json_binary(&query::query_user_templates(deps, user_id, start_after, limit, order)?)
         },
+        QueryMsg::GetTemplateVersions {
+            user_id,
+            template_id,
+            start_after,
+            limit,
+            order,
+        } => to_json_binary(&query::query_template_versions(
+            deps,
+            user_id,
+            template_id,
+            start_after,
+            limit,
+            order,
+        )?),
+        QueryMsg::GetTemplateReviewHistory {
+            user_id,
+            template_id,
+            start_after,
+            limit,
+            order,
+        } => to_json_binary(&query::query_template_review_history(
+            deps,
+            user_id,
+            template_id,
+            start_after,
+            limit,
+            order,
+        )?),
         QueryMsg::GetReviewerTemplates {
             reviewer,
+            start_after,
+            limit,
+            order,
         } => {
-            to_json_binary(&query::query_templates_for_reviewer(deps, reviewer)?)
+            to_json_binary(&query::query_templates_for_reviewer(deps, reviewer, start_after, limit, order)?)
         }
+        QueryMsg::GetConfig {} => to_json_binary(&query::query_config(deps)?),
+        QueryMsg::GetRoleMembers { role } => {
+            to_json_binary(&query::query_role_members(deps, role)?)
+        }
+        QueryMsg::Ownership {} => to_json_binary(&query::query_ownership(deps)?),
     }
 }
 
+#[allow(clippy::module_inception)]
 mod query {
-    use std::collections::HashMap;
+    use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+    use std::str::FromStr;
 
-    use cosmwasm_std::{Addr, Deps, Env, StdResult};
+    use cosmwasm_std::{Addr, Decimal, Decimal256, Deps, Env, Order, StdError, StdResult};
+    use cw_storage_plus::{Bound, PrimaryKey};
 
     use crate::{
-        models::{AllReviewerStatistics, IdentityMetadata, LoanData, LoanStatistics, LoanTemplate, ReviewStatus}, states::{IDENTITIES, LOAN_STORAGE, REVIEWER_ASSIGNMENTS, TEMPLATE_REVIEWERS, USER_TEMPLATES}
+        calendar::{YearMonth, MAX_UTC_OFFSET_MINUTES},
+        error::ContractError,
+        models::{AllReviewerStatistics, Config, DateField, DateRange, FieldType, FieldValue, Granularity, GroupBy, IdentityMetadata, LoanCounters, LoanData, LoanFilter, LoanRevision, LoanStatistics, LoanStatus, LoanSummary, LoanTemplate, LoanTimeSeries, LoanValidation, Ownership, Page, Role, SortOrder, TemplateReview, TemplateValidation, TimeSeriesBucket, TimeSeriesGroup, ValidationRule}, roles, templates, validation, states::{loan_key, ADMIN_TRANSFER, CONFIG, IDENTITIES, LOAN_REVISIONS, LOAN_STORAGE, MONTH_COUNTS, NEXT_LOAN_ID, REVIEWER_COUNTS, REVIEWER_DECISIONS, REVIEWER_DECISION_TIME, STATUS_COUNTS, TEMPLATE_COUNTS, TEMPLATE_REVIEWERS, TEMPLATE_REVIEW_HISTORY, TEMPLATE_VERSIONS, USER_TEMPLATES}
     };
 
+    // Page size when a listing query gives no limit, and the most it may ask for
+    const DEFAULT_LIMIT: u32 = 10;
+    const MAX_LIMIT: u32 = 30;
+    // A year of daily buckets
+    const MAX_TIME_SERIES_BUCKETS: usize = 366;
+
+    type LoanIter<'a> = Box<dyn Iterator<Item = StdResult<((String, String), LoanData)>> + 'a>;
+
+    pub fn query_config(deps: Deps) -> StdResult<Config> {
+        CONFIG.load(deps.storage)
+    }
+
+    pub fn query_ownership(deps: Deps) -> StdResult<Ownership> {
+        let config = CONFIG.load(deps.storage)?;
+        let transfer = ADMIN_TRANSFER.may_load(deps.storage)?;
+        Ok(Ownership {
+            admin: config.admin,
+            pending_admin: transfer.as_ref().map(|t| t.new_admin.clone()),
+            pending_expiry: transfer.and_then(|t| t.expiry),
+        })
+    }
+
+    pub fn query_role_members(deps: Deps, role: Role) -> StdResult<Vec<Addr>> {
+        roles::members(deps.storage, role)
+    }
+
     pub fn query_loan(deps: Deps, user_id: String, loan_id: String) -> StdResult<LoanData> {
-        let loan = LOAN_STORAGE
-            .load(deps.storage, (&user_id, &loan_id));
-        Ok(loan?)
+        LOAN_STORAGE.load(deps.storage, (&user_id, &loan_key(&loan_id)))
     }
+
+    pub fn query_loan_revisions(
+        deps: Deps,
+        loan_id: String,
+        start_after: Option<u32>,
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    ) -> StdResult<Page<LoanRevision, u32>> {
+        let order = order_or_default(order);
+        let (min, max) = bounds_after(start_after.map(Bound::exclusive), order);
+        let revisions = LOAN_REVISIONS
+            .prefix(&loan_key(&loan_id))
+            .range(deps.storage, min, max, order)
+            .map(|item| item.map(|(_, revision)| revision));
+        paginate(revisions, limit, |revision| revision.revision)
+    }
+
     pub fn query_identity(deps: Deps, address: Addr) -> StdResult<IdentityMetadata> {
         let identity = IDENTITIES.load(deps.storage, &address)?;
         Ok(identity)
     }
 
-    pub fn query_all_identities(deps: Deps) -> StdResult<Vec<(Addr, IdentityMetadata)>> {
-        let identities: StdResult<Vec<_>> = IDENTITIES
-            .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
-            .collect();
-        identities
+    pub fn query_all_identities(
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    ) -> StdResult<Page<(Addr, IdentityMetadata), String>> {
+        let start_after = start_after
+            .map(|address| deps.api.addr_validate(&address))
+            .transpose()?;
+        let order = order_or_default(order);
+        let (min, max) = bounds_after(start_after.as_ref().map(Bound::exclusive), order);
+        paginate(
+            IDENTITIES.range(deps.storage, min, max, order),
+            limit,
+            |(address, _)| address.to_string(),
+        )
     }
 
     // Function to query loan data by (user_id)
-    pub fn query_loans_for_user(deps: Deps, user_id: String) -> StdResult<Vec<LoanData>> {
-        // Use range to iterate over all the loans in storage
-        let loans: Vec<LoanData> = LOAN_STORAGE
-            .range(deps.storage, None, None, cosmwasm_std::Order::Ascending) // Iterate over the storage
-            .filter_map(|item| {
-                let ((stored_user_id, _loan_id), loan_data) = item.ok()?; // Extract the user_id and loan_id
-                if stored_user_id == user_id {
-                    Some(loan_data) // Keep only loans for the provided user_id
-                } else {
-                    None // Filter out loans that don't match the user_id
-                }
-            })
-            .collect(); // Collect all the loans into a Vec<LoanData>
-
-        Ok(loans)
+    pub fn query_loans_for_user(
+        deps: Deps,
+        user_id: String,
+        include_values: bool,
+        start_after: Option<String>,
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    ) -> StdResult<Page<LoanSummary, String>> {
+        let order = order_or_default(order);
+        let start_after = start_after.map(|loan_id| loan_key(&loan_id));
+        let (min, max) = bounds_after(start_after.as_deref().map(Bound::exclusive), order);
+        // The user ID is the first half of the primary key
+        let loans = LOAN_STORAGE
+            .prefix(&user_id)
+            .range(deps.storage, min, max, order)
+            .map(|item| {
+                item.map(|(_, loan)| LoanSummary::new(user_id.clone(), loan, include_values))
+            });
+        paginate(loans, limit, |summary| summary.loan_id.clone())
     }
 
     // Function to query loans by their review status
     pub fn query_loans_by_status(
         deps: Deps,
-        status: ReviewStatus,
-    ) -> StdResult<Vec<(String, String)>> {
-        let loans: Vec<(String, String)> = LOAN_STORAGE
-            .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
-            .filter_map(|item| {
-                let ((user_id, loan_id), loan) = item.ok()?;
-                if loan.review_status == status {
-                    Some((user_id.to_string(), loan_id.to_string()))
-                } else {
-                    None
-                }
-            })
-            .collect();
-
-        Ok(loans)
+        status: LoanStatus,
+        include_values: bool,
+        start_after: Option<(String, String)>,
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    ) -> StdResult<Page<LoanSummary, (String, String)>> {
+        let order = order_or_default(order);
+        let (min, max) = bounds_after(key_cursor(start_after).map(Bound::exclusive), order);
+        let loans = LOAN_STORAGE
+            .idx
+            .status
+            .prefix(status.to_string())
+            .range(deps.storage, min, max, order);
+        paginate(summarize(loans, include_values), limit, summary_cursor)
     }
 
     // Function to query loans assigned to a reviewer
     pub fn query_loans_for_reviewer(
         deps: Deps,
         reviewer: String,
-    ) -> StdResult<Vec<(String, String)>> {
+        include_values: bool,
+        start_after: Option<(String, String)>,
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    ) -> StdResult<Page<LoanSummary, (String, String)>> {
         let reviewer_addr = deps.api.addr_validate(&reviewer)?;
-        let loans = REVIEWER_ASSIGNMENTS.load(deps.storage, &reviewer_addr)?;
-        Ok(loans)
+        let order = order_or_default(order);
+        let (min, max) = bounds_after(key_cursor(start_after).map(Bound::exclusive), order);
+        let loans = LOAN_STORAGE
+            .idx
+            .reviewer
+            .prefix(reviewer_addr.to_string())
+            .range(deps.storage, min, max, order);
+        paginate(summarize(loans, include_values), limit, summary_cursor)
+    }
+
+    // Function to query the ID the next created loan will receive
+    pub fn query_next_loan_id(deps: Deps) -> StdResult<String> {
+        Ok(NEXT_LOAN_ID.load(deps.storage)?.to_string())
+    }
+
+    // Function to query the reviewer a loan is assigned to, if any
+    pub fn query_loan_reviewer(
+        deps: Deps,
+        user_id: String,
+        loan_id: String,
+    ) -> StdResult<Option<Addr>> {
+        let loan = LOAN_STORAGE.may_load(deps.storage, (&user_id, &loan_key(&loan_id)))?;
+        Ok(loan.and_then(|loan| loan.reviewer))
     }
 
     // Function to query loans by their creation, approval, or rejection date
@@ -121,88 +337,276 @@ mod query {
         deps: Deps,
         from_date: u64,
         date_type: String,
-    ) -> StdResult<Vec<(String, String)>> {
-        let loans: Vec<(String, String)> = LOAN_STORAGE
-            .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
-            .filter_map(|item| {
-                let ((user_id, loan_id), loan) = item.ok()?;
-                match date_type.as_str() {
-                    "created" if loan.creation_date >= from_date => {
-                        Some((user_id.to_string(), loan_id.to_string()))
-                    }
-                    "approved" if loan.approval_date.unwrap_or(0) >= from_date => {
-                        Some((user_id.to_string(), loan_id.to_string()))
-                    }
-                    "rejected" if loan.rejection_date.unwrap_or(0) >= from_date => {
-                        Some((user_id.to_string(), loan_id.to_string()))
-                    }
-                    _ => None,
-                }
+        include_values: bool,
+        start_after: Option<(String, String)>,
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    ) -> StdResult<Page<LoanSummary, (String, String)>> {
+        let field = match date_type.as_str() {
+            "created" => DateField::Created,
+            "approved" => DateField::Approved,
+            "rejected" => DateField::Rejected,
+            _ => {
+                return Err(StdError::generic_err(format!(
+                    "Unknown date type '{}': expected created, approved or rejected",
+                    date_type
+                )))
+            }
+        };
+        let range = DateRange {
+            field,
+            from: Some(from_date),
+            to: None,
+        };
+        // The index range holds exactly the matching loans, so the page limit bounds the scan
+        let loans = date_range_scan(deps, &range, key_cursor(start_after), order_or_default(order))?;
+        paginate(summarize(loans, include_values), limit, summary_cursor)
+    }
+
+    // Function to search loans matching every criterion of the filter
+    pub fn query_search_loans(
+        deps: Deps,
+        filter: LoanFilter,
+        include_values: bool,
+        start_after: Option<(String, String)>,
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    ) -> StdResult<Page<LoanSummary, (String, String)>> {
+        let order = order_or_default(order);
+        let reviewer = filter
+            .reviewer
+            .as_deref()
+            .map(|reviewer| deps.api.addr_validate(reviewer))
+            .transpose()?;
+        let predicates = filter
+            .predicates
+            .iter()
+            .map(|predicate| {
+                let value = Decimal256::from_str(&predicate.value).map_err(|_| {
+                    StdError::generic_err(format!(
+                        "Predicate value '{}' for field '{}' is not a number",
+                        predicate.value, predicate.field
+                    ))
+                })?;
+                Ok((predicate, value))
             })
-            .collect();
+            .collect::<StdResult<Vec<_>>>()?;
+
+        let matches = |owner: &str, loan: &LoanData| {
+            filter.owner.as_ref().is_none_or(|o| o == owner)
+                && filter.template_id.as_ref().is_none_or(|t| *t == loan.template_id)
+                && (filter.statuses.is_empty() || filter.statuses.contains(&loan.status))
+                && reviewer.as_ref().is_none_or(|r| loan.reviewer.as_ref() == Some(r))
+                && filter.date.as_ref().is_none_or(|range| {
+                    range.field.date_of(loan).is_some_and(|date| {
+                        range.from.is_none_or(|from| date >= from)
+                            && range.to.is_none_or(|to| date <= to)
+                    })
+                })
+                && predicates.iter().all(|(predicate, value)| {
+                    loan.values
+                        .get(&predicate.field)
+                        .and_then(FieldValue::as_text)
+                        .and_then(|v| Decimal256::from_str(v).ok())
+                        .is_some_and(|v| predicate.op.holds(v.cmp(value)))
+                })
+        };
+
+        // Walk the narrowest index the filter allows and check the rest on each loan
+        let start_after = key_cursor(start_after);
+        let pk_bounds = |start_after: Option<(String, String)>| {
+            bounds_after(start_after.map(Bound::exclusive), order)
+        };
+        let cursor = start_after.clone();
+        let cursor = cursor.as_ref().map(|(user_id, loan_id)| (user_id.as_str(), loan_id.as_str()));
+        let loans: LoanIter =
+            if let Some(owner) = &filter.owner {
+                let (min, max) =
+                    bounds_after(cursor.map(|(_, loan_id)| Bound::exclusive(loan_id)), order);
+                Box::new(
+                    LOAN_STORAGE
+                        .prefix(owner)
+                        .range(deps.storage, min, max, order)
+                        .map(|item| item.map(|(loan_id, loan)| ((owner.clone(), loan_id), loan))),
+                )
+            } else if let Some(reviewer) = &reviewer {
+                let (min, max) = pk_bounds(start_after);
+                LOAN_STORAGE
+                    .idx
+                    .reviewer
+                    .prefix(reviewer.to_string())
+                    .range(deps.storage, min, max, order)
+            } else if let Some(template_id) = &filter.template_id {
+                let (min, max) = pk_bounds(start_after);
+                LOAN_STORAGE
+                    .idx
+                    .template
+                    .prefix(template_id.clone())
+                    .range(deps.storage, min, max, order)
+            } else if let Some(range) = &filter.date {
+                date_range_scan(deps, range, start_after, order)?
+            } else if let [status] = filter.statuses.as_slice() {
+                let (min, max) = pk_bounds(start_after);
+                LOAN_STORAGE
+                    .idx
+                    .status
+                    .prefix(status.to_string())
+                    .range(deps.storage, min, max, order)
+            } else {
+                let (min, max) = bounds_after(cursor.map(Bound::exclusive), order);
+                LOAN_STORAGE.range(deps.storage, min, max, order)
+            };
 
-        Ok(loans)
+        let loans = loans.filter(|item| match item {
+            Ok(((owner, _), loan)) => matches(owner, loan),
+            Err(_) => true,
+        });
+        paginate(summarize(loans, include_values), limit, summary_cursor)
+    }
+
+    // Scans the creation or decision date index between the range bounds
+    fn date_range_scan<'a>(
+        deps: Deps<'a>,
+        range: &DateRange,
+        start_after: Option<(String, String)>,
+        order: Order,
+    ) -> StdResult<LoanIter<'a>> {
+        // Index keys end in the primary key, so the lowest key for a date pairs it with ("", "")
+        let lowest = |date: u64| (date, (String::new(), String::new()));
+        let from = Bound::inclusive(lowest(range.from.unwrap_or(0)));
+        let to = range
+            .to
+            .and_then(|to| to.checked_add(1))
+            .map(|end| Bound::exclusive(lowest(end)));
+
+        // A cursor resumes right after the cursor loan's own index entry, so it must have one
+        let after = match start_after {
+            Some((user_id, key)) => {
+                let loan = LOAN_STORAGE.load(deps.storage, (&user_id, &key))?;
+                let date = range.field.date_of(&loan).ok_or_else(|| {
+                    StdError::generic_err(format!(
+                        "Cursor loan '{}' has no {:?} date to continue from",
+                        loan.loan_id, range.field
+                    ))
+                })?;
+                Some(Bound::exclusive((date, (user_id, key))))
+            }
+            None => None,
+        };
+        let (min, max) = match order {
+            Order::Ascending => (after.or(Some(from)), to),
+            Order::Descending => (Some(from), after.or(to)),
+        };
+
+        Ok(match range.field {
+            DateField::Created => LOAN_STORAGE.idx.created.range(deps.storage, min, max, order),
+            // Decisions are indexed under their kind, so each kind is one contiguous range
+            DateField::Approved | DateField::Rejected => {
+                let kind = if range.field == DateField::Approved { "approved" } else { "rejected" };
+                LOAN_STORAGE
+                    .idx
+                    .decided
+                    .sub_prefix(kind.to_string())
+                    .range(deps.storage, min, max, order)
+            }
+        })
     }
 
     pub fn query_loan_statistics(
         deps: Deps,
         env: Env,
         reviewer: Option<String>,
+        utc_offset_minutes: Option<i32>,
     ) -> StdResult<LoanStatistics> {
-        let reviewer_addr = deps.api.addr_validate(&reviewer.unwrap_or_default().as_str())?;
-        let loan_statistics = prepapre_loan_statistics(env.clone(), deps, reviewer_addr)?;
+        let reviewer_addr = deps.api.addr_validate(reviewer.unwrap_or_default().as_str())?;
+        let utc_offset_minutes = utc_offset_minutes.unwrap_or(0);
+        if utc_offset_minutes.abs() > MAX_UTC_OFFSET_MINUTES {
+            return Err(StdError::generic_err(format!(
+                "UTC offset must be within ±{} minutes",
+                MAX_UTC_OFFSET_MINUTES
+            )));
+        }
+        let loan_statistics = prepapre_loan_statistics(env.clone(), deps, reviewer_addr, utc_offset_minutes)?;
         Ok(loan_statistics)
     }
 
-    fn prepapre_loan_statistics(env: Env, deps: Deps<'_>, reviewer_addr: Addr) -> Result<LoanStatistics, cosmwasm_std::StdError> {
-        let current_timestamp = env.block.time.seconds();
-        let month_seconds = 30 * 24 * 60 * 60;
-        let current_month_start = current_timestamp - (current_timestamp % month_seconds);
-        let last_month_start = current_month_start - month_seconds;
+    // Buckets by calendar month, quarter and year in the zone `utc_offset_minutes` east of UTC
+    fn prepapre_loan_statistics(env: Env, deps: Deps<'_>, reviewer_addr: Addr, utc_offset_minutes: i32) -> Result<LoanStatistics, cosmwasm_std::StdError> {
+        let this_month = YearMonth::of(env.block.time.seconds(), utc_offset_minutes);
+        let last_month = this_month.previous();
+        let in_this_quarter =
+            |month: YearMonth| month.year == this_month.year && month.quarter() == this_month.quarter();
         let mut pending_count = 0;
         let mut rejected_this_month = 0;
         let mut rejected_last_month = 0;
         let mut approved_this_month = 0;
         let mut approved_last_month = 0;
+        let mut rejected_this_quarter = 0;
+        let mut approved_this_quarter = 0;
+        let mut rejected_this_year = 0;
+        let mut approved_this_year = 0;
         let mut total_processing_time: u64 = 0;
         let mut processed_loans_count = 0;
         let mut month_wise_status_count: HashMap<String, HashMap<String, u64>> = HashMap::new();
-        if let Some(assigned_loans) = REVIEWER_ASSIGNMENTS.may_load(deps.storage, &reviewer_addr)? {
-            for (user_id, loan_id) in assigned_loans {
-                if let Some(loan) = LOAN_STORAGE.may_load(deps.storage, (&user_id, &loan_id))? {
-                    // Count pending loans
-                    if loan.review_status == ReviewStatus::Pending {
-                        pending_count += 1;
-                    }
-    
-                    // Process approval or rejection stats based on date
-                    if let Some(approval_date) = loan.approval_date {
-                        if approval_date >= current_month_start {
-                            approved_this_month += 1;
-                        } else if approval_date >= last_month_start {
-                            approved_last_month += 1;
-                        }
-                        total_processing_time += approval_date - loan.creation_date;
-                        processed_loans_count += 1;
-                    }
+        let assigned_loans = LOAN_STORAGE
+            .idx
+            .reviewer
+            .prefix(reviewer_addr.to_string())
+            .range(deps.storage, None, None, Order::Ascending);
+        for item in assigned_loans {
+            let (_, loan) = item?;
+            // Count pending loans
+            if loan.status.is_awaiting_decision() {
+                pending_count += 1;
+            }
     
-                    if let Some(rejection_date) = loan.rejection_date {
-                        if rejection_date >= current_month_start {
-                            rejected_this_month += 1;
-                        } else if rejection_date >= last_month_start {
-                            rejected_last_month += 1;
-                        }
-                        total_processing_time += rejection_date - loan.creation_date;
-                        processed_loans_count += 1;
-                    }
+            // Month-wise count of loans by status
+            let loan_creation_month = YearMonth::of(loan.creation_date, utc_offset_minutes).to_string();
+            let status_count = month_wise_status_count
+                .entry(loan_creation_month)
+                .or_default();
+            *status_count.entry(loan.status.to_string()).or_insert(0) += 1;
+        }
+        // Decisions count for whoever made them, whether or not they were assigned
+        let decided_loans = LOAN_STORAGE
+            .idx
+            .decider
+            .prefix(reviewer_addr.to_string())
+            .range(deps.storage, None, None, Order::Ascending);
+        for item in decided_loans {
+            let (_, loan) = item?;
+            if let Some(approval_date) = loan.approval_date {
+                let month = YearMonth::of(approval_date, utc_offset_minutes);
+                if month == this_month {
+                    approved_this_month += 1;
+                } else if month == last_month {
+                    approved_last_month += 1;
+                }
+                if in_this_quarter(month) {
+                    approved_this_quarter += 1;
+                }
+                if month.year == this_month.year {
+                    approved_this_year += 1;
+                }
+                total_processing_time += approval_date - loan.creation_date;
+                processed_loans_count += 1;
+            }
     
-                    // Month-wise count of loans by status
-                    let loan_creation_month = format!("{}", loan.creation_date / month_seconds);
-                    let status_count = month_wise_status_count
-                        .entry(loan_creation_month)
-                        .or_insert_with(HashMap::new);
-                    *status_count.entry(loan.review_status.clone().to_string()).or_insert(0) += 1;
+            if let Some(rejection_date) = loan.rejection_date {
+                let month = YearMonth::of(rejection_date, utc_offset_minutes);
+                if month == this_month {
+                    rejected_this_month += 1;
+                } else if month == last_month {
+                    rejected_last_month += 1;
                 }
+                if in_this_quarter(month) {
+                    rejected_this_quarter += 1;
+                }
+                if month.year == this_month.year {
+                    rejected_this_year += 1;
+                }
+                total_processing_time += rejection_date - loan.creation_date;
+                processed_loans_count += 1;
             }
         }
         let average_time_to_process_float = if processed_loans_count > 0 {
@@ -212,18 +616,125 @@ mod query {
         };
         let average_time_to_process = average_time_to_process_float.map(|e: f64| e.to_string());
         let loan_statistics = LoanStatistics {
-            reviewer: None,
+            reviewer: Some(reviewer_addr.to_string()),
             pending_count,
             rejected_this_month,
             rejected_last_month,
             approved_this_month,
             approved_last_month,
+            rejected_this_quarter,
+            approved_this_quarter,
+            rejected_this_year,
+            approved_this_year,
             average_time_to_process,
             month_wise_status_count,
         };
         Ok(loan_statistics)
     }
     
+    // Same figures as `prepapre_loan_statistics` in UTC, read from the running counters
+    // instead of the reviewer's loans
+    fn counted_loan_statistics(deps: Deps, env: &Env, reviewer: &str) -> StdResult<LoanStatistics> {
+        let this_month = YearMonth::of(env.block.time.seconds(), 0);
+        let last_month = this_month.previous();
+        let mut statistics = LoanStatistics {
+            reviewer: Some(reviewer.to_string()),
+            pending_count: 0,
+            rejected_this_month: 0,
+            rejected_last_month: 0,
+            approved_this_month: 0,
+            approved_last_month: 0,
+            rejected_this_quarter: 0,
+            approved_this_quarter: 0,
+            rejected_this_year: 0,
+            approved_this_year: 0,
+            average_time_to_process: None,
+            month_wise_status_count: HashMap::new(),
+        };
+
+        let pending = [LoanStatus::Submitted.to_string(), LoanStatus::UnderReview.to_string()];
+        for item in REVIEWER_COUNTS.sub_prefix(reviewer).range(deps.storage, None, None, Order::Ascending) {
+            let ((month, status), count) = item?;
+            if pending.contains(&status) {
+                statistics.pending_count += count;
+            }
+            *statistics
+                .month_wise_status_count
+                .entry(month)
+                .or_default()
+                .entry(status)
+                .or_insert(0) += count;
+        }
+
+        let mut processed_loans_count = 0;
+        for item in REVIEWER_DECISIONS.sub_prefix(reviewer).range(deps.storage, None, None, Order::Ascending) {
+            let ((month, kind), count) = item?;
+            let month = YearMonth::from_str(&month)
+                .map_err(|_| StdError::generic_err(format!("Invalid counter month '{}'", month)))?;
+            let (this_month_count, last_month_count, quarter_count, year_count) = if kind == "approved" {
+                (
+                    &mut statistics.approved_this_month,
+                    &mut statistics.approved_last_month,
+                    &mut statistics.approved_this_quarter,
+                    &mut statistics.approved_this_year,
+                )
+            } else {
+                (
+                    &mut statistics.rejected_this_month,
+                    &mut statistics.rejected_last_month,
+                    &mut statistics.rejected_this_quarter,
+                    &mut statistics.rejected_this_year,
+                )
+            };
+            if month == this_month {
+                *this_month_count += count;
+            } else if month == last_month {
+                *last_month_count += count;
+            }
+            if month.year == this_month.year && month.quarter() == this_month.quarter() {
+                *quarter_count += count;
+            }
+            if month.year == this_month.year {
+                *year_count += count;
+            }
+            processed_loans_count += count;
+        }
+        if processed_loans_count > 0 {
+            let total_processing_time = REVIEWER_DECISION_TIME.may_load(deps.storage, reviewer)?.unwrap_or_default();
+            statistics.average_time_to_process =
+                Some((total_processing_time as f64 / processed_loans_count as f64).to_string());
+        }
+        Ok(statistics)
+    }
+
+    pub fn query_loan_counters(deps: Deps) -> StdResult<LoanCounters> {
+        let by_status = STATUS_COUNTS
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<HashMap<_, _>>>()?;
+        let mut by_template: HashMap<String, HashMap<String, u64>> = HashMap::new();
+        for item in TEMPLATE_COUNTS.range(deps.storage, None, None, Order::Ascending) {
+            let ((template_id, status), count) = item?;
+            by_template.entry(template_id).or_default().insert(status, count);
+        }
+        let mut by_month: HashMap<String, HashMap<String, u64>> = HashMap::new();
+        for item in MONTH_COUNTS.range(deps.storage, None, None, Order::Ascending) {
+            let ((month, status), count) = item?;
+            by_month.entry(month).or_default().insert(status, count);
+        }
+        // Reviewer counters are also split by month, which this view folds away
+        let mut by_reviewer: HashMap<String, HashMap<String, u64>> = HashMap::new();
+        for item in REVIEWER_COUNTS.range(deps.storage, None, None, Order::Ascending) {
+            let ((reviewer, _, status), count) = item?;
+            *by_reviewer.entry(reviewer).or_default().entry(status).or_insert(0) += count;
+        }
+        Ok(LoanCounters {
+            by_status,
+            by_template,
+            by_month,
+            by_reviewer,
+        })
+    }
+
     pub fn query_all_reviewer_statistics(
         deps: Deps,
         env: Env,
@@ -234,16 +745,25 @@ mod query {
         let mut total_rejected = 0;
         let mut reviewers_stats: Vec<LoanStatistics> = vec![];
     
-        // Iterate over all reviewers
-        let all_reviewers: Vec<Addr> = REVIEWER_ASSIGNMENTS
-            .keys(deps.storage, None, None, cosmwasm_std::Order::Ascending)
-            .map(|result| result.unwrap())
-            .collect();
+        // Everyone with assigned loans or decisions; unassigned and undecided loans sit under ""
+        let mut all_reviewers: BTreeSet<String> = BTreeSet::new();
+        let assigned = REVIEWER_COUNTS
+            .keys(deps.storage, None, None, Order::Ascending)
+            .map(|key| key.map(|(reviewer, _, _)| reviewer));
+        let deciders = REVIEWER_DECISIONS
+            .keys(deps.storage, None, None, Order::Ascending)
+            .map(|key| key.map(|(decider, _, _)| decider));
+        for reviewer in assigned.chain(deciders) {
+            let reviewer = reviewer?;
+            if !reviewer.is_empty() {
+                all_reviewers.insert(reviewer);
+            }
+        }
     
         // Process each reviewer
-        for reviewer_addr in all_reviewers {
+        for reviewer in all_reviewers {
 
-            let loan_statistics = prepapre_loan_statistics(env.clone(), deps, reviewer_addr)?;
+            let loan_statistics = counted_loan_statistics(deps, &env, &reviewer)?;
     
             // Update totals
             total_pending += loan_statistics.pending_count;
@@ -262,38 +782,277 @@ mod query {
         })
     }
 
-    pub fn query_templates_for_reviewer(deps: Deps, reviewer: String) -> StdResult<Vec<LoanTemplate>> {
-        let templates: Vec<LoanTemplate> = TEMPLATE_REVIEWERS
-            .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
-            .filter_map(|item| {
-                let (template_id, assigned_reviewer_tuple) = item.ok()?;
-                if assigned_reviewer_tuple.reviewer == reviewer {
-                    // Now query USER_TEMPLATES for the specific template
-                    let template = USER_TEMPLATES
-                        .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
-                        .find_map(|template_item| {
-                            let (_, template) = template_item.ok()?;
-                            if template.id == template_id {
-                                Some(template)
-                            } else {
-                                None
-                            }
-                        });
-                    template
-                } else {
-                    None
+    // Counts for one group within one time series bucket
+    #[derive(Default)]
+    struct GroupTally {
+        created: u64,
+        approved: u64,
+        rejected: u64,
+        decision_times: Vec<u64>,
+    }
+
+    impl GroupTally {
+        fn into_group(mut self, key: String) -> TimeSeriesGroup {
+            let decided = self.approved + self.rejected;
+            let count = self.decision_times.len() as u64;
+            self.decision_times.sort_unstable();
+            let mid = self.decision_times.len() / 2;
+            let median_time_to_decision = match self.decision_times.len() {
+                0 => None,
+                len if len % 2 == 1 => Some(self.decision_times[mid]),
+                _ => Some((self.decision_times[mid - 1] + self.decision_times[mid]) / 2),
+            };
+            TimeSeriesGroup {
+                key,
+                created: self.created,
+                approved: self.approved,
+                rejected: self.rejected,
+                approval_rate: (decided > 0).then(|| Decimal::from_ratio(self.approved, decided)),
+                average_time_to_decision: (count > 0).then(|| self.decision_times.iter().sum::<u64>() / count),
+                median_time_to_decision,
+            }
+        }
+    }
+
+    pub fn query_loan_time_series(
+        deps: Deps,
+        from: u64,
+        to: u64,
+        granularity: Granularity,
+        group_by: GroupBy,
+    ) -> StdResult<LoanTimeSeries> {
+        if from > to {
+            return Err(StdError::generic_err("from must not be after to"));
+        }
+
+        // Lay out every bucket first so quiet periods still show up
+        let mut buckets: BTreeMap<u64, BTreeMap<String, GroupTally>> = BTreeMap::new();
+        let mut start = granularity.bucket_start(from);
+        while start <= to {
+            if buckets.len() == MAX_TIME_SERIES_BUCKETS {
+                return Err(StdError::generic_err(format!(
+                    "Time series cannot span more than {} buckets",
+                    MAX_TIME_SERIES_BUCKETS
+                )));
+            }
+            buckets.insert(start, BTreeMap::new());
+            start = granularity.next_bucket_start(start);
+        }
+
+        // Each event lands in the bucket of its own date, so a loan created before the
+        // window still counts towards the bucket it was decided in
+        for field in [DateField::Created, DateField::Approved, DateField::Rejected] {
+            let range = DateRange {
+                field,
+                from: Some(from),
+                to: Some(to),
+            };
+            for item in date_range_scan(deps, &range, None, Order::Ascending)? {
+                let (_, loan) = item?;
+                let Some(date) = field.date_of(&loan) else {
+                    continue;
+                };
+                let key = match group_by {
+                    GroupBy::Status => loan.status.to_string(),
+                    GroupBy::Template => loan.template_id.clone(),
+                    GroupBy::Reviewer => loan.reviewer.as_ref().map(Addr::to_string).unwrap_or_default(),
+                };
+                let tally = buckets
+                    .entry(granularity.bucket_start(date))
+                    .or_default()
+                    .entry(key)
+                    .or_default();
+                match field {
+                    DateField::Created => tally.created += 1,
+                    DateField::Approved => tally.approved += 1,
+                    DateField::Rejected => tally.rejected += 1,
+                }
+                if field != DateField::Created {
+                    tally.decision_times.push(date.saturating_sub(loan.creation_date));
                 }
+            }
+        }
+
+        let buckets = buckets
+            .into_iter()
+            .map(|(start, groups)| TimeSeriesBucket {
+                start,
+                label: granularity.label(start),
+                groups: groups.into_iter().map(|(key, tally)| tally.into_group(key)).collect(),
             })
-            .collect(); // Collect LoanTemplate items into a Vec
-    
-        Ok(templates) // Return the vector of LoanTemplates
+            .collect();
+        Ok(LoanTimeSeries {
+            granularity,
+            group_by,
+            buckets,
+        })
     }
 
-    pub fn query_user_templates(deps: Deps, user_id: String) -> StdResult<Vec<LoanTemplate>> {
-        USER_TEMPLATES
+    // Runs the checks `create_loan_template` applies, reporting every problem instead of the first
+    pub fn query_validate_template(
+        deps: Deps,
+        env: Env,
+        fields: HashMap<String, FieldType>,
+        rules: Vec<ValidationRule>,
+    ) -> TemplateValidation {
+        let problems: Vec<String> = validation::template_problems(deps.api, env.block.time.seconds() as i64, &fields, &rules)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        TemplateValidation {
+            valid: problems.is_empty(),
+            problems,
+        }
+    }
+
+    // Runs the checks `create_loan` applies to the values, defaults included, and stores nothing
+    pub fn query_validate_loan_request(
+        deps: Deps,
+        env: Env,
+        template_owner: String,
+        template_id: String,
+        mut values: HashMap<String, FieldValue>,
+    ) -> StdResult<LoanValidation> {
+        let owner = deps.api.addr_validate(&template_owner)?;
+        // The version a new loan would be validated against, refused like `create_loan` refuses it
+        USER_TEMPLATES.load(deps.storage, (owner.as_str(), template_id.as_str()))?;
+        let template = templates::latest_published(deps.storage, owner.as_str(), &template_id)?
+            .ok_or_else(|| {
+                StdError::generic_err(ContractError::TemplateNotApproved { template_id }.to_string())
+            })?;
+        validation::apply_defaults(&template.fields, &mut values);
+        let errors = validation::loan_errors(deps.api, env.block.time.seconds() as i64, &template, &values)
+            .map_err(|e| StdError::generic_err(e.to_string()))?;
+        Ok(LoanValidation {
+            valid: errors.is_empty(),
+            errors,
+        })
+    }
+
+    pub fn query_templates_for_reviewer(
+        deps: Deps,
+        reviewer: String,
+        start_after: Option<(String, String)>,
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    ) -> StdResult<Page<LoanTemplate, (String, String)>> {
+        let order = order_or_default(order);
+        let (min, max) = bounds_after(start_after.map(Bound::exclusive), order);
+        let templates = TEMPLATE_REVIEWERS
+            .idx
+            .reviewer
+            .prefix(reviewer)
+            .keys(deps.storage, min, max, order)
+            .map(|key| {
+                key.and_then(|(user_id, template_id)| {
+                    USER_TEMPLATES.load(deps.storage, (&user_id, &template_id))
+                })
+            });
+        paginate(templates, limit, |template| (template.submitter.clone(), template.id.clone()))
+    }
+
+    pub fn query_user_templates(
+        deps: Deps,
+        user_id: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    ) -> StdResult<Page<LoanTemplate, String>> {
+        let order = order_or_default(order);
+        let (min, max) = bounds_after(start_after.as_deref().map(Bound::exclusive), order);
+        let templates = USER_TEMPLATES
             .prefix(&user_id)
-            .range(deps.storage, None, None, cosmwasm_std::Order::Ascending)
-            .map(|item| item.map(|(_, template)| template))
-            .collect()
+            .range(deps.storage, min, max, order)
+            .map(|item| item.map(|(_, template)| template));
+        paginate(templates, limit, |template| template.id.clone())
+    }
+
+    // Approved versions of one template, oldest first by default
+    pub fn query_template_versions(
+        deps: Deps,
+        user_id: String,
+        template_id: String,
+        start_after: Option<u32>,
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    ) -> StdResult<Page<LoanTemplate, u32>> {
+        let order = order_or_default(order);
+        let (min, max) = bounds_after(start_after.map(Bound::exclusive), order);
+        let versions = TEMPLATE_VERSIONS
+            .prefix((&user_id, &template_id))
+            .range(deps.storage, min, max, order)
+            .map(|item| item.map(|(_, template)| template));
+        paginate(versions, limit, |template| template.version)
+    }
+
+    // Every decision on one template, oldest first by default
+    pub fn query_template_review_history(
+        deps: Deps,
+        user_id: String,
+        template_id: String,
+        start_after: Option<u32>,
+        limit: Option<u32>,
+        order: Option<SortOrder>,
+    ) -> StdResult<Page<TemplateReview, u32>> {
+        let order = order_or_default(order);
+        let (min, max) = bounds_after(start_after.map(Bound::exclusive), order);
+        let reviews = TEMPLATE_REVIEW_HISTORY
+            .prefix((&user_id, &template_id))
+            .range(deps.storage, min, max, order)
+            .map(|item| item.map(|(_, review)| review));
+        paginate(reviews, limit, |review| review.review)
+    }
+
+    fn summarize<'a>(
+        loans: impl Iterator<Item = StdResult<((String, String), LoanData)>> + 'a,
+        include_values: bool,
+    ) -> impl Iterator<Item = StdResult<LoanSummary>> + 'a {
+        loans.map(move |item| {
+            item.map(|((user_id, _), loan)| LoanSummary::new(user_id, loan, include_values))
+        })
+    }
+
+    fn summary_cursor(summary: &LoanSummary) -> (String, String) {
+        (summary.owner.clone(), summary.loan_id.clone())
+    }
+
+    // Turns a (user_id, loan_id) cursor into the primary key it continues after
+    fn key_cursor(start_after: Option<(String, String)>) -> Option<(String, String)> {
+        start_after.map(|(user_id, loan_id)| (user_id, loan_key(&loan_id)))
+    }
+
+    fn order_or_default(order: Option<SortOrder>) -> Order {
+        order.map_or(Order::Ascending, Order::from)
+    }
+
+    // Places the exclusive `start_after` bound on the side the iteration starts from
+    fn bounds_after<'a, K: PrimaryKey<'a>>(
+        start_after: Option<Bound<'a, K>>,
+        order: Order,
+    ) -> (Option<Bound<'a, K>>, Option<Bound<'a, K>>) {
+        match order {
+            Order::Ascending => (start_after, None),
+            Order::Descending => (None, start_after),
+        }
+    }
+
+    // Takes one page from `items`; reading one extra item tells whether another page exists
+    fn paginate<T, C>(
+        items: impl Iterator<Item = StdResult<T>>,
+        limit: Option<u32>,
+        cursor: impl Fn(&T) -> C,
+    ) -> StdResult<Page<T, C>> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let mut items = items.take(limit + 1).collect::<StdResult<Vec<_>>>()?;
+        let next_start_after = if items.len() > limit {
+            items.truncate(limit);
+            items.last().map(cursor)
+        } else {
+            None
+        };
+        Ok(Page {
+            items,
+            next_start_after,
+        })
     }
 }
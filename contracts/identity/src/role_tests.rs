@@ -0,0 +1,302 @@
+mod test {
+
+    use crate::error::ContractError;
+    use crate::exec::execute;
+    use crate::instantiate::instantiate;
+    use crate::models::{Config, FieldType, Ownership, Role};
+    use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+    use crate::query::query;
+    use crate::test_helpers::{addr, setup};
+    use cosmwasm_std::testing::{message_info, mock_dependencies, mock_env};
+    use cosmwasm_std::{attr, from_json, Addr};
+    use cw_utils::Expiration;
+    use std::collections::HashMap;
+
+    fn role_members(deps: cosmwasm_std::Deps, role: Role) -> Vec<Addr> {
+        let bin = query(deps, mock_env(), QueryMsg::GetRoleMembers { role }).unwrap();
+        from_json(&bin).unwrap()
+    }
+
+    fn ownership(deps: cosmwasm_std::Deps) -> Ownership {
+        let bin = query(deps, mock_env(), QueryMsg::Ownership {}).unwrap();
+        from_json(&bin).unwrap()
+    }
+
+    #[test]
+    fn test_instantiate_stores_config_and_roles() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            admin: addr("admin").to_string(),
+            reviewers: vec![addr("reviewer1").to_string()],
+            loan_officers: vec![addr("officer1").to_string()],
+            auditors: vec![addr("auditor1").to_string()],
+        };
+        instantiate(deps.as_mut(), mock_env(), message_info(&addr("admin"), &[]), msg).unwrap();
+
+        let config_bin = query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap();
+        let config: Config = from_json(&config_bin).unwrap();
+        assert_eq!(config.admin, Some(addr("admin")));
+
+        assert_eq!(role_members(deps.as_ref(), Role::Admin), vec![addr("admin")]);
+        assert_eq!(role_members(deps.as_ref(), Role::Reviewer), vec![addr("reviewer1")]);
+        assert_eq!(role_members(deps.as_ref(), Role::LoanOfficer), vec![addr("officer1")]);
+        assert_eq!(role_members(deps.as_ref(), Role::Auditor), vec![addr("auditor1")]);
+    }
+
+    #[test]
+    fn test_instantiate_rejects_invalid_admin() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            admin: "not-an-address".to_string(),
+            reviewers: vec![],
+            loan_officers: vec![],
+            auditors: vec![],
+        };
+        let res = instantiate(deps.as_mut(), mock_env(), message_info(&addr("admin"), &[]), msg);
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_grant_and_revoke_role() {
+        let mut deps = setup();
+        let admin_info = message_info(&addr("admin"), &[]);
+
+        let grant_msg = ExecuteMsg::GrantRole {
+            role: Role::Reviewer,
+            address: addr("reviewer3").to_string(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), admin_info.clone(), grant_msg).unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("method", "grant_role"),
+                attr("role", "Reviewer"),
+                attr("address", addr("reviewer3")),
+            ]
+        );
+        assert_eq!(role_members(deps.as_ref(), Role::Reviewer).len(), 3);
+
+        let revoke_msg = ExecuteMsg::RevokeRole {
+            role: Role::Reviewer,
+            address: addr("reviewer1").to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), admin_info, revoke_msg).unwrap();
+        let mut remaining = vec![addr("reviewer2"), addr("reviewer3")];
+        remaining.sort();
+        assert_eq!(role_members(deps.as_ref(), Role::Reviewer), remaining);
+    }
+
+    #[test]
+    fn test_non_admin_cannot_grant_role() {
+        let mut deps = setup();
+
+        let grant_msg = ExecuteMsg::GrantRole {
+            role: Role::Admin,
+            address: addr("creator").to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("creator"), &[]),
+            grant_msg,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+    }
+
+    #[test]
+    fn test_cannot_revoke_contract_admin() {
+        let mut deps = setup();
+
+        let revoke_msg = ExecuteMsg::RevokeRole {
+            role: Role::Admin,
+            address: addr("admin").to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("admin"), &[]),
+            revoke_msg,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::CannotRevokeContractAdmin {});
+    }
+
+    #[test]
+    fn test_handlers_enforce_roles() {
+        let mut deps = setup();
+        let outsider_info = message_info(&addr("outsider"), &[]);
+
+        let mut fields = HashMap::new();
+        fields.insert(
+            "borrower".to_string(),
+            FieldType::String {
+                is_editable: true,
+                required: true,
+                default: None,
+                format: None,
+                min_value: None,
+                max_value: None,
+            },
+        );
+        let create_msg = ExecuteMsg::CreateLoanTemplate {
+            template_id: "template1".to_string(),
+            name: "Home Loan".to_string(),
+            fields,
+            rules: vec![],
+        };
+        let err = execute(deps.as_mut(), mock_env(), outsider_info.clone(), create_msg.clone())
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // A loan officer may create the template but cannot assign it to a non-reviewer
+        let officer_info = message_info(&addr("creator"), &[]);
+        execute(deps.as_mut(), mock_env(), officer_info.clone(), create_msg).unwrap();
+        let submit_msg = ExecuteMsg::SubmitTemplateForReview {
+            template_id: "template1".to_string(),
+            reviewer: addr("outsider").to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), officer_info, submit_msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::MissingRole {
+                address: addr("outsider").to_string(),
+                role: "Reviewer".to_string(),
+            }
+        );
+
+        let assign_msg = ExecuteMsg::AssignLoans {
+            reviewer: addr("reviewer1").to_string(),
+            loans: vec![],
+        };
+        let err = execute(deps.as_mut(), mock_env(), outsider_info.clone(), assign_msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let update_msg = ExecuteMsg::UpdateLoanReviewStatus {
+            user_id: addr("creator").to_string(),
+            loan_id: "loan1".to_string(),
+            new_status: crate::models::LoanStatus::Approved,
+            reason: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), outsider_info, update_msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+    }
+
+    #[test]
+    fn test_two_step_admin_transfer() {
+        let mut deps = setup();
+
+        let propose_msg = ExecuteMsg::ProposeAdminTransfer {
+            new_admin: addr("new_admin").to_string(),
+            expiry: None,
+        };
+        execute(deps.as_mut(), mock_env(), message_info(&addr("admin"), &[]), propose_msg).unwrap();
+
+        // Nothing changes until the proposed admin accepts
+        let pending = ownership(deps.as_ref());
+        assert_eq!(pending.admin, Some(addr("admin")));
+        assert_eq!(pending.pending_admin, Some(addr("new_admin")));
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("outsider"), &[]),
+            ExecuteMsg::AcceptAdminTransfer {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NotPendingAdmin {});
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("new_admin"), &[]),
+            ExecuteMsg::AcceptAdminTransfer {},
+        )
+        .unwrap();
+
+        let accepted = ownership(deps.as_ref());
+        assert_eq!(accepted.admin, Some(addr("new_admin")));
+        assert_eq!(accepted.pending_admin, None);
+        assert_eq!(role_members(deps.as_ref(), Role::Admin), vec![addr("new_admin")]);
+    }
+
+    #[test]
+    fn test_expired_admin_transfer_cannot_be_accepted() {
+        let mut deps = setup();
+        let env = mock_env();
+
+        let propose_msg = ExecuteMsg::ProposeAdminTransfer {
+            new_admin: addr("new_admin").to_string(),
+            expiry: Some(Expiration::AtHeight(env.block.height + 10)),
+        };
+        execute(deps.as_mut(), env.clone(), message_info(&addr("admin"), &[]), propose_msg).unwrap();
+
+        let mut later = env.clone();
+        later.block.height += 10;
+        let err = execute(
+            deps.as_mut(),
+            later,
+            message_info(&addr("new_admin"), &[]),
+            ExecuteMsg::AcceptAdminTransfer {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::AdminTransferExpired {});
+        assert_eq!(ownership(deps.as_ref()).admin, Some(addr("admin")));
+    }
+
+    #[test]
+    fn test_cancel_admin_transfer() {
+        let mut deps = setup();
+        let admin_info = message_info(&addr("admin"), &[]);
+
+        let err = execute(deps.as_mut(), mock_env(), admin_info.clone(), ExecuteMsg::CancelAdminTransfer {})
+            .unwrap_err();
+        assert_eq!(err, ContractError::NoPendingAdminTransfer {});
+
+        let propose_msg = ExecuteMsg::ProposeAdminTransfer {
+            new_admin: addr("new_admin").to_string(),
+            expiry: None,
+        };
+        execute(deps.as_mut(), mock_env(), admin_info.clone(), propose_msg).unwrap();
+        execute(deps.as_mut(), mock_env(), admin_info, ExecuteMsg::CancelAdminTransfer {}).unwrap();
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("new_admin"), &[]),
+            ExecuteMsg::AcceptAdminTransfer {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NoPendingAdminTransfer {});
+    }
+
+    #[test]
+    fn test_renounce_admin() {
+        let mut deps = setup();
+
+        // Only the configured admin may renounce, not other Admin role holders
+        let grant_msg = ExecuteMsg::GrantRole {
+            role: Role::Admin,
+            address: addr("deputy").to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), message_info(&addr("admin"), &[]), grant_msg).unwrap();
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("deputy"), &[]),
+            ExecuteMsg::RenounceAdmin {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&addr("admin"), &[]),
+            ExecuteMsg::RenounceAdmin {},
+        )
+        .unwrap();
+        assert_eq!(ownership(deps.as_ref()).admin, None);
+        assert_eq!(role_members(deps.as_ref(), Role::Admin), vec![addr("deputy")]);
+    }
+}
@@ -0,0 +1,45 @@
+use cosmwasm_std::{Addr, Empty, Order, StdResult, Storage};
+
+use crate::{error::ContractError, models::Role, states::ROLES};
+
+pub fn has_role(storage: &dyn Storage, address: &Addr, role: Role) -> bool {
+    ROLES.has(storage, (role.as_str(), address))
+}
+
+pub fn grant(storage: &mut dyn Storage, address: &Addr, role: Role) -> StdResult<()> {
+    ROLES.save(storage, (role.as_str(), address), &Empty {})
+}
+
+pub fn revoke(storage: &mut dyn Storage, address: &Addr, role: Role) {
+    ROLES.remove(storage, (role.as_str(), address));
+}
+
+// Admins pass every role check; everyone else needs one of the listed roles
+pub fn ensure_role(storage: &dyn Storage, sender: &Addr, roles: &[Role]) -> Result<(), ContractError> {
+    if has_role(storage, sender, Role::Admin)
+        || roles.iter().any(|role| has_role(storage, sender, *role))
+    {
+        Ok(())
+    } else {
+        Err(ContractError::Unauthorized {})
+    }
+}
+
+// Used when the address being acted upon (not the sender) must hold a role
+pub fn ensure_member(storage: &dyn Storage, address: &Addr, role: Role) -> Result<(), ContractError> {
+    if has_role(storage, address, role) {
+        Ok(())
+    } else {
+        Err(ContractError::MissingRole {
+            address: address.to_string(),
+            role: role.to_string(),
+        })
+    }
+}
+
+pub fn members(storage: &dyn Storage, role: Role) -> StdResult<Vec<Addr>> {
+    ROLES
+        .prefix(role.as_str())
+        .keys(storage, None, None, Order::Ascending)
+        .collect()
+}
@@ -0,0 +1,496 @@
+// Cross-field rules: a small expression language over a loan's values, e.g.
+// `down_payment <= amount * 0.2` or
+// `if employment == 'self_employed' then present(tax_id) else true`
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use cosmwasm_std::{Int256, SignedDecimal256};
+
+use crate::{
+    calendar::parse_timestamp,
+    error::ContractError,
+    models::{FieldType, FieldValue, ValidationRule},
+};
+
+pub const MAX_RULES: usize = 64;
+pub const MAX_RULE_LENGTH: usize = 1024;
+// Deepest nesting of parentheses and operators, so parsing cannot exhaust the stack
+const MAX_EXPRESSION_DEPTH: u32 = 32;
+
+// Rejects rules that do not parse, name unknown fields or mix value kinds
+pub fn rule_problems(fields: &HashMap<String, FieldType>, rules: &[ValidationRule]) -> Vec<ContractError> {
+    let mut problems = vec![];
+    if rules.len() > MAX_RULES {
+        problems.push(ContractError::TooManyRules { max: MAX_RULES as u32 });
+    }
+    for (i, rule) in rules.iter().enumerate() {
+        let invalid = |reason: String| ContractError::InvalidRule {
+            rule_id: rule.id.clone(),
+            reason,
+        };
+        if rule.id.trim().is_empty() {
+            problems.push(invalid("rule IDs cannot be empty".to_string()));
+        } else if rules[..i].iter().any(|other| other.id == rule.id) {
+            problems.push(ContractError::DuplicateRuleId {
+                rule_id: rule.id.clone(),
+            });
+        }
+        if let Err(reason) = compile(fields, &rule.expression) {
+            problems.push(invalid(reason));
+        }
+    }
+    problems
+}
+
+// Every rule the values break, in rule order. A rule that reads a field the
+// loan left out does not apply to that loan
+pub fn rule_violations(
+    fields: &HashMap<String, FieldType>,
+    rules: &[ValidationRule],
+    values: &HashMap<String, FieldValue>,
+) -> Result<Vec<ContractError>, ContractError> {
+    let mut violations = vec![];
+    for rule in rules {
+        let expr = compile(fields, &rule.expression).map_err(|reason| ContractError::StoredRuleInvalid {
+            rule_id: rule.id.clone(),
+            reason,
+        })?;
+        let holds = match expr.eval(fields, values) {
+            Ok(Value::Bool(holds)) => holds,
+            Err(Halt::Missing) => true,
+            Ok(_) | Err(Halt::Failed) => false,
+        };
+        if !holds {
+            violations.push(ContractError::RuleViolated {
+                rule_id: rule.id.clone(),
+                message: rule.message.clone().unwrap_or_else(|| rule.expression.clone()),
+            });
+        }
+    }
+    Ok(violations)
+}
+
+fn compile(fields: &HashMap<String, FieldType>, source: &str) -> Result<Expr, String> {
+    if source.len() > MAX_RULE_LENGTH {
+        return Err(format!("rules are limited to {} characters", MAX_RULE_LENGTH));
+    }
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        pos: 0,
+        depth: 0,
+    };
+    let expr = parser.expression()?;
+    if let Some(token) = parser.peek() {
+        return Err(format!("unexpected {}", token));
+    }
+    match expr.kind(fields)? {
+        Kind::Bool => Ok(expr),
+        kind => Err(format!("rule must be true or false, not {}", kind)),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(String),
+    Text(String),
+    Word(String), // Keyword or field path
+    Symbol(&'static str),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Number(n) => write!(f, "'{}'", n),
+            Token::Text(t) => write!(f, "'\"{}\"'", t),
+            Token::Word(w) => write!(f, "'{}'", w),
+            Token::Symbol(s) => write!(f, "'{}'", s),
+        }
+    }
+}
+
+const SYMBOLS: [&str; 12] = ["==", "!=", "<=", ">=", "<", ">", "+", "-", "*", "/", "(", ")"];
+const KEYWORDS: [&str; 9] = ["and", "or", "not", "if", "then", "else", "true", "false", "present"];
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut rest = source.trim_start();
+    while let Some(c) = rest.chars().next() {
+        let (token, len) = if c.is_ascii_digit() {
+            let len = rest
+                .find(|c: char| !c.is_ascii_digit() && c != '.')
+                .unwrap_or(rest.len());
+            (Token::Number(rest[..len].to_string()), len)
+        } else if c == '\'' || c == '"' {
+            let end = rest[1..]
+                .find(c)
+                .ok_or_else(|| "unterminated text".to_string())?;
+            (Token::Text(rest[1..=end].to_string()), end + 2)
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            // Dots join the names of nested fields, e.g. `applicant.income`
+            let len = rest
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_' && c != '.')
+                .unwrap_or(rest.len());
+            (Token::Word(rest[..len].to_string()), len)
+        } else if let Some(symbol) = SYMBOLS.iter().find(|s| rest.starts_with(**s)) {
+            (Token::Symbol(symbol), symbol.len())
+        } else {
+            return Err(format!("unexpected character '{}'", c));
+        };
+        tokens.push(token);
+        rest = rest[len..].trim_start();
+    }
+    Ok(tokens)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Expr {
+    Number(SignedDecimal256),
+    Text(String),
+    Bool(bool),
+    Field(String),
+    Present(String),
+    Neg(Box<Expr>),
+    Not(Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+    If(Box<Expr>, Box<Expr>, Box<Expr>),
+}
+
+// Precedence, loosest first: or, and, not, comparison, + -, * /, unary minus
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: u32,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<Token, String> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or_else(|| "unexpected end of rule".to_string())?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    // Consumes the token if it is the given keyword or symbol
+    fn eat(&mut self, word: &str) -> bool {
+        let found = match self.peek() {
+            Some(Token::Word(w)) => w == word,
+            Some(Token::Symbol(s)) => *s == word,
+            _ => false,
+        };
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, word: &str) -> Result<(), String> {
+        if self.eat(word) {
+            return Ok(());
+        }
+        match self.peek() {
+            Some(token) => Err(format!("expected '{}' but found {}", word, token)),
+            None => Err(format!("expected '{}' but the rule ended", word)),
+        }
+    }
+
+    fn expression(&mut self) -> Result<Expr, String> {
+        self.nested(|p| p.binary(0))
+    }
+
+    fn binary(&mut self, level: usize) -> Result<Expr, String> {
+        const LEVELS: [&[(&str, BinOp)]; 5] = [
+            &[("or", BinOp::Or)],
+            &[("and", BinOp::And)],
+            &[
+                ("==", BinOp::Eq),
+                ("!=", BinOp::Ne),
+                ("<=", BinOp::Le),
+                (">=", BinOp::Ge),
+                ("<", BinOp::Lt),
+                (">", BinOp::Gt),
+            ],
+            &[("+", BinOp::Add), ("-", BinOp::Sub)],
+            &[("*", BinOp::Mul), ("/", BinOp::Div)],
+        ];
+        let Some(ops) = LEVELS.get(level) else {
+            return self.unary();
+        };
+        // `not` binds looser than comparisons, so `not a == b` negates the comparison
+        if level == 2 && self.eat("not") {
+            return Ok(Expr::Not(Box::new(self.nested(|p| p.binary(2))?)));
+        }
+        let mut lhs = self.binary(level + 1)?;
+        while let Some((_, op)) = ops.iter().find(|(word, _)| self.eat(word)) {
+            let rhs = self.binary(level + 1)?;
+            lhs = Expr::Binary(*op, Box::new(lhs), Box::new(rhs));
+            // Comparisons do not chain: `a < b < c` is rejected
+            if level == 2 {
+                break;
+            }
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat("-") {
+            return Ok(Expr::Neg(Box::new(self.nested(Parser::unary)?)));
+        }
+        match self.next()? {
+            Token::Number(n) => SignedDecimal256::from_str(&n)
+                .map(Expr::Number)
+                .map_err(|_| format!("'{}' is not a valid number", n)),
+            Token::Text(t) => Ok(Expr::Text(t)),
+            Token::Symbol("(") => {
+                let expr = self.expression()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Token::Word(w) => match w.as_str() {
+                "true" => Ok(Expr::Bool(true)),
+                "false" => Ok(Expr::Bool(false)),
+                "if" => {
+                    let condition = self.expression()?;
+                    self.expect("then")?;
+                    let then = self.expression()?;
+                    self.expect("else")?;
+                    let otherwise = self.expression()?;
+                    Ok(Expr::If(Box::new(condition), Box::new(then), Box::new(otherwise)))
+                }
+                "present" => {
+                    self.expect("(")?;
+                    let path = match self.next()? {
+                        Token::Word(path) if !KEYWORDS.contains(&path.as_str()) => path,
+                        token => return Err(format!("expected a field name but found {}", token)),
+                    };
+                    self.expect(")")?;
+                    Ok(Expr::Present(path))
+                }
+                word if KEYWORDS.contains(&word) => Err(format!("unexpected '{}'", word)),
+                _ => Ok(Expr::Field(w)),
+            },
+            token => Err(format!("unexpected {}", token)),
+        }
+    }
+
+    fn nested(&mut self, parse: impl FnOnce(&mut Parser) -> Result<Expr, String>) -> Result<Expr, String> {
+        self.depth += 1;
+        if self.depth > MAX_EXPRESSION_DEPTH {
+            return Err("rule is nested too deeply".to_string());
+        }
+        let expr = parse(self);
+        self.depth -= 1;
+        expr
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kind {
+    Number,
+    Text,
+    Bool,
+}
+
+impl std::fmt::Display for Kind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Kind::Number => "a number",
+            Kind::Text => "text",
+            Kind::Bool => "true or false",
+        })
+    }
+}
+
+// Kind of value a field yields in a rule, or None for lists and objects
+fn field_kind(field_type: &FieldType) -> Option<Kind> {
+    match field_type {
+        FieldType::Number { .. }
+        | FieldType::Integer { .. }
+        | FieldType::Decimal { .. }
+        | FieldType::Percentage { .. }
+        | FieldType::Date { .. } => Some(Kind::Number),
+        FieldType::Boolean { .. } => Some(Kind::Bool),
+        FieldType::String { .. }
+        | FieldType::Enum { .. }
+        | FieldType::Address { .. }
+        | FieldType::Coin { .. }
+        | FieldType::Email { .. }
+        | FieldType::PhoneNumber { .. }
+        | FieldType::CountryCode { .. } => Some(Kind::Text),
+        FieldType::Object { .. } | FieldType::List { .. } => None,
+    }
+}
+
+// Follows a dotted path through object fields
+fn resolve<'a>(fields: &'a HashMap<String, FieldType>, path: &str) -> Result<&'a FieldType, String> {
+    let unknown = || format!("unknown field '{}'", path);
+    let mut names = path.split('.');
+    let mut field_type = names.next().and_then(|name| fields.get(name)).ok_or_else(unknown)?;
+    for name in names {
+        field_type = match field_type {
+            FieldType::Object { fields, .. } => fields.get(name).ok_or_else(unknown)?,
+            _ => return Err(unknown()),
+        };
+    }
+    Ok(field_type)
+}
+
+fn lookup<'a>(values: &'a HashMap<String, FieldValue>, path: &str) -> Option<&'a FieldValue> {
+    let mut names = path.split('.');
+    let mut value = values.get(names.next()?)?;
+    for name in names {
+        value = match value {
+            FieldValue::Object(values) => values.get(name)?,
+            _ => return None,
+        };
+    }
+    Some(value)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Value {
+    Number(SignedDecimal256),
+    Text(String),
+    Bool(bool),
+}
+
+enum Halt {
+    Missing, // A referenced field was left out of the loan
+    Failed,  // Arithmetic overflowed or divided by zero
+}
+
+impl Expr {
+    fn kind(&self, fields: &HashMap<String, FieldType>) -> Result<Kind, String> {
+        let same = |lhs: &Expr, rhs: &Expr, want: Option<Kind>, op: &str| -> Result<Kind, String> {
+            let (l, r) = (lhs.kind(fields)?, rhs.kind(fields)?);
+            match want {
+                Some(want) if l != want || r != want => Err(format!("'{}' needs {} on both sides", op, want)),
+                None if l != r => Err(format!("'{}' compares {} with {}", op, l, r)),
+                _ => Ok(l),
+            }
+        };
+        Ok(match self {
+            Expr::Number(_) => Kind::Number,
+            Expr::Text(_) => Kind::Text,
+            Expr::Bool(_) => Kind::Bool,
+            Expr::Field(path) => field_kind(resolve(fields, path)?)
+                .ok_or_else(|| format!("field '{}' has no single value to compare", path))?,
+            Expr::Present(path) => {
+                resolve(fields, path)?;
+                Kind::Bool
+            }
+            Expr::Neg(expr) => match expr.kind(fields)? {
+                Kind::Number => Kind::Number,
+                kind => return Err(format!("cannot negate {}", kind)),
+            },
+            Expr::Not(expr) => match expr.kind(fields)? {
+                Kind::Bool => Kind::Bool,
+                kind => return Err(format!("'not' needs true or false, not {}", kind)),
+            },
+            Expr::Binary(op, lhs, rhs) => match op {
+                BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div => {
+                    same(lhs, rhs, Some(Kind::Number), "arithmetic")?
+                }
+                BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => {
+                    same(lhs, rhs, Some(Kind::Number), "ordering")?;
+                    Kind::Bool
+                }
+                BinOp::Eq | BinOp::Ne => {
+                    same(lhs, rhs, None, "equality")?;
+                    Kind::Bool
+                }
+                BinOp::And | BinOp::Or => same(lhs, rhs, Some(Kind::Bool), "and/or")?,
+            },
+            Expr::If(condition, then, otherwise) => {
+                if condition.kind(fields)? != Kind::Bool {
+                    return Err("'if' needs a true or false condition".to_string());
+                }
+                same(then, otherwise, None, "if")?
+            }
+        })
+    }
+
+    // Only called on expressions `kind` accepted, so values always have the expected kind
+    fn eval(&self, fields: &HashMap<String, FieldType>, values: &HashMap<String, FieldValue>) -> Result<Value, Halt> {
+        let number = |expr: &Expr| match expr.eval(fields, values)? {
+            Value::Number(n) => Ok(n),
+            _ => Err(Halt::Failed),
+        };
+        let boolean = |expr: &Expr| match expr.eval(fields, values)? {
+            Value::Bool(b) => Ok(b),
+            _ => Err(Halt::Failed),
+        };
+        Ok(match self {
+            Expr::Number(n) => Value::Number(*n),
+            Expr::Text(t) => Value::Text(t.clone()),
+            Expr::Bool(b) => Value::Bool(*b),
+            Expr::Field(path) => {
+                let text = lookup(values, path).and_then(FieldValue::as_text).ok_or(Halt::Missing)?;
+                let field_type = resolve(fields, path).map_err(|_| Halt::Failed)?;
+                match field_kind(field_type) {
+                    // Dates compare as unix seconds, whichever form they were given in
+                    Some(Kind::Number) if matches!(field_type, FieldType::Date { .. }) => {
+                        let timestamp = parse_timestamp(text).ok_or(Halt::Failed)?;
+                        Value::Number(SignedDecimal256::from_atomics(Int256::from(timestamp), 0).map_err(|_| Halt::Failed)?)
+                    }
+                    Some(Kind::Number) => Value::Number(SignedDecimal256::from_str(text).map_err(|_| Halt::Failed)?),
+                    Some(Kind::Bool) => Value::Bool(text == "true"),
+                    _ => Value::Text(text.to_string()),
+                }
+            }
+            Expr::Present(path) => Value::Bool(lookup(values, path).is_some()),
+            Expr::Neg(expr) => Value::Number(SignedDecimal256::zero().checked_sub(number(expr)?).map_err(|_| Halt::Failed)?),
+            Expr::Not(expr) => Value::Bool(!boolean(expr)?),
+            Expr::Binary(BinOp::And, lhs, rhs) => Value::Bool(boolean(lhs)? && boolean(rhs)?),
+            Expr::Binary(BinOp::Or, lhs, rhs) => Value::Bool(boolean(lhs)? || boolean(rhs)?),
+            Expr::Binary(op @ (BinOp::Eq | BinOp::Ne), lhs, rhs) => {
+                let equal = lhs.eval(fields, values)? == rhs.eval(fields, values)?;
+                Value::Bool(equal == (*op == BinOp::Eq))
+            }
+            Expr::Binary(op, lhs, rhs) => {
+                let (l, r) = (number(lhs)?, number(rhs)?);
+                let arithmetic = |result: Option<SignedDecimal256>| result.map(Value::Number).ok_or(Halt::Failed);
+                match op {
+                    BinOp::Add => arithmetic(l.checked_add(r).ok())?,
+                    BinOp::Sub => arithmetic(l.checked_sub(r).ok())?,
+                    BinOp::Mul => arithmetic(l.checked_mul(r).ok())?,
+                    BinOp::Div => arithmetic(l.checked_div(r).ok())?,
+                    BinOp::Lt => Value::Bool(l < r),
+                    BinOp::Le => Value::Bool(l <= r),
+                    BinOp::Gt => Value::Bool(l > r),
+                    BinOp::Ge => Value::Bool(l >= r),
+                    BinOp::Eq | BinOp::Ne | BinOp::And | BinOp::Or => return Err(Halt::Failed),
+                }
+            }
+            Expr::If(condition, then, otherwise) => {
+                if boolean(condition)? {
+                    then.eval(fields, values)?
+                } else {
+                    otherwise.eval(fields, values)?
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,212 @@
+mod test {
+
+    use std::collections::HashMap;
+
+    use crate::error::ContractError;
+    use crate::models::{FieldType, TemplateValidation, ValidationRule};
+    use crate::msg::QueryMsg;
+    use crate::query::query;
+    use crate::validation::field_error;
+    use crate::test_helpers::{approved_template, create_loan_json, create_template, setup};
+    use cosmwasm_std::testing::mock_env;
+    use cosmwasm_std::from_json;
+
+    fn rule(id: &str, expression: &str) -> ValidationRule {
+        ValidationRule {
+            id: id.to_string(),
+            expression: expression.to_string(),
+            message: None,
+        }
+    }
+
+    fn number(required: bool) -> FieldType {
+        FieldType::Number {
+            is_editable: false,
+            required,
+            default: None,
+            min_value: None,
+            max_value: None,
+        }
+    }
+
+    fn fields() -> HashMap<String, FieldType> {
+        let text = |required: bool| FieldType::String {
+            is_editable: false,
+            required,
+            default: None,
+            format: None,
+            min_value: None,
+            max_value: None,
+        };
+        let date = FieldType::Date {
+            is_editable: false,
+            required: true,
+            default: None,
+            format: None,
+            min_value: None,
+            max_value: None,
+        };
+        HashMap::from([
+            ("loan_amount".to_string(), number(true)),
+            ("down_payment".to_string(), number(false)),
+            (
+                "employment".to_string(),
+                FieldType::Enum {
+                    is_editable: false,
+                    required: true,
+                    default: None,
+                    options: vec!["employed".to_string(), "self_employed".to_string()],
+                },
+            ),
+            ("tax_id".to_string(), text(false)),
+            ("start_date".to_string(), date.clone()),
+            ("end_date".to_string(), date),
+            (
+                "applicant".to_string(),
+                FieldType::Object {
+                    is_editable: false,
+                    required: true,
+                    fields: HashMap::from([("income".to_string(), number(true))]),
+                },
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_malformed_rules_are_rejected_when_template_is_created() {
+        let mut deps = setup();
+        let mut invalid = |expression: &str, reason: &str| {
+            let err = create_template(&mut deps, "mortgage", fields(), vec![rule("r1", expression)]).unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::InvalidRule {
+                    rule_id: "r1".to_string(),
+                    reason: reason.to_string(),
+                },
+                "accepted {}",
+                expression
+            );
+        };
+        invalid("loan_amount >", "unexpected end of rule");
+        invalid("loan_amount > 1 1", "unexpected '1'");
+        invalid("(loan_amount > 1", "expected ')' but the rule ended");
+        invalid("loan_amount > 1 % 2", "unexpected character '%'");
+        invalid("employment == 'self_employed", "unterminated text");
+        invalid("salary > 1", "unknown field 'salary'");
+        invalid("applicant.age > 18", "unknown field 'applicant.age'");
+        invalid("applicant > 1", "field 'applicant' has no single value to compare");
+        invalid("loan_amount * 2", "rule must be true or false, not a number");
+        invalid("employment > 1", "'ordering' needs a number on both sides");
+        invalid("employment == 1", "'equality' compares text with a number");
+        invalid("loan_amount + employment > 1", "'arithmetic' needs a number on both sides");
+        invalid("0 < loan_amount < 10", "unexpected '<'");
+        invalid("if employment == 'employed' then 1 else present(tax_id)", "'if' compares a number with true or false");
+        invalid(&format!("{}true{}", "(".repeat(40), ")".repeat(40)), "rule is nested too deeply");
+
+        let err = create_template(&mut deps, "mortgage", fields(), vec![rule("r1", "true"), rule("r1", "true")]).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::DuplicateRuleId {
+                rule_id: "r1".to_string()
+            }
+        );
+
+        // The query reports every rule problem after the field problems
+        let msg = QueryMsg::ValidateTemplate {
+            fields: fields(),
+            rules: vec![rule("", "true"), rule("r2", "loan_amount >= 1000"), rule("r3", "salary > 1")],
+        };
+        let validation: TemplateValidation = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(
+            validation.problems,
+            vec![
+                "Rule '' is invalid: rule IDs cannot be empty".to_string(),
+                "Rule 'r3' is invalid: unknown field 'salary'".to_string(),
+            ]
+        );
+
+        create_template(
+            &mut deps,
+            "mortgage",
+            fields(),
+            vec![rule("r1", "not loan_amount == 0 and (loan_amount - -1) / 2 > applicant.income * 0.5 or false")],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_rules_are_checked_on_loan_creation() {
+        let mut deps = setup();
+        approved_template(
+            &mut deps,
+            "mortgage",
+            fields(),
+            vec![
+                rule("down_payment", "down_payment <= loan_amount * 0.2"),
+                rule("term", "end_date > start_date"),
+                ValidationRule {
+                    id: "tax_id".to_string(),
+                    expression: "if employment == 'self_employed' then present(tax_id) else true".to_string(),
+                    message: Some("Self-employed applicants need a tax ID".to_string()),
+                },
+                rule("income", "applicant.income * 5 >= loan_amount"),
+            ],
+        );
+
+        let loan = |down_payment: Option<&str>, employment: &str, tax_id: Option<&str>, end_date: &str, income: &str| {
+            let mut values = vec![
+                r#""loan_amount":"100000""#.to_string(),
+                format!(r#""employment":"{}""#, employment),
+                r#""start_date":"1700000000""#.to_string(),
+                format!(r#""end_date":"{}""#, end_date),
+                format!(r#""applicant":{{"income":"{}"}}"#, income),
+            ];
+            if let Some(down_payment) = down_payment {
+                values.push(format!(r#""down_payment":"{}""#, down_payment));
+            }
+            if let Some(tax_id) = tax_id {
+                values.push(format!(r#""tax_id":"{}""#, tax_id));
+            }
+            format!("{{{}}}", values.join(","))
+        };
+
+        create_loan_json(&mut deps, "mortgage", &loan(Some("20000"), "employed", None, "1800000000", "20000")).unwrap();
+        create_loan_json(&mut deps, "mortgage", &loan(Some("5000"), "self_employed", Some("DE123"), "1800000000", "25000")).unwrap();
+        // Rules reading a left-out field do not apply
+        create_loan_json(&mut deps, "mortgage", &loan(None, "employed", None, "1800000000", "20000")).unwrap();
+
+        let violated = |rule_id: &str, message: &str| ContractError::RuleViolated {
+            rule_id: rule_id.to_string(),
+            message: message.to_string(),
+        };
+        let cases = [
+            (
+                loan(Some("20000.01"), "employed", None, "1800000000", "20000"),
+                violated("down_payment", "down_payment <= loan_amount * 0.2"),
+            ),
+            (
+                loan(None, "employed", None, "1700000000", "20000"),
+                violated("term", "end_date > start_date"),
+            ),
+            (
+                loan(None, "self_employed", None, "1800000000", "20000"),
+                violated("tax_id", "Self-employed applicants need a tax ID"),
+            ),
+            (
+                loan(None, "employed", None, "1800000000", "19999"),
+                violated("income", "applicant.income * 5 >= loan_amount"),
+            ),
+        ];
+        for (values, expected) in cases {
+            let err = create_loan_json(&mut deps, "mortgage", &values).unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::ValidationFailed {
+                    errors: vec![field_error(expected).unwrap()]
+                },
+                "accepted {}",
+                values
+            );
+        }
+    }
+}
@@ -0,0 +1,378 @@
+mod test {
+
+    use std::collections::HashMap;
+
+    use crate::exec::execute;
+    use crate::models::{
+        AllReviewerStatistics, Config, FieldType, Granularity, GroupBy, IdentityMetadata, LoanCounters, LoanData,
+        LoanFilter, LoanRequest, LoanRevision, LoanStatistics, LoanStatus, LoanSummary, LoanTemplate, LoanTimeSeries,
+        LoanValidation, Ownership, Page, Role, TemplateReview, TemplateValidation,
+    };
+    use crate::msg::{ExecuteMsg, QueryMsg};
+    use crate::query::query;
+    use crate::test_helpers::{addr, setup};
+    use cosmwasm_schema::{schema_for, QueryResponses};
+    use cosmwasm_std::testing::{message_info, mock_env};
+    use cosmwasm_std::{from_json, Addr};
+
+    // Checks that `$ty` is the type declared by `#[returns]` for `$name`, and that the
+    // handler's actual response deserializes into it
+    macro_rules! assert_returns {
+        ($deps:expr, $schemas:expr, $name:literal, $msg:expr, $ty:ty) => {{
+            let declared = $schemas
+                .remove($name)
+                .unwrap_or_else(|| panic!("{} is not a query or was checked twice", $name));
+            assert_eq!(declared, schema_for!($ty), "{} declares another type", $name);
+
+            let bin = query($deps.as_ref(), mock_env(), $msg)
+                .unwrap_or_else(|e| panic!("{} failed: {}", $name, e));
+            from_json::<$ty>(&bin)
+                .unwrap_or_else(|e| panic!("{} does not return its declared type: {}", $name, e))
+        }};
+    }
+
+    #[test]
+    fn test_query_responses_match_declared_types() {
+        let mut deps = setup();
+        let creator_info = message_info(&addr("creator"), &[]);
+        let reviewer_info = message_info(&addr("reviewer1"), &[]);
+        let admin_info = message_info(&addr("admin"), &[]);
+
+        // Give every query something to return
+        let metadata = IdentityMetadata {
+            address: addr("creator"),
+            name: "Alice".to_string(),
+            about: "About Alice".to_string(),
+            pic: "ipfs://pic".to_string(),
+            avatar: "ipfs://avatar".to_string(),
+        };
+        let msg = ExecuteMsg::UpdateMetadata {
+            identity_data: metadata,
+        };
+        execute(deps.as_mut(), mock_env(), creator_info.clone(), msg).unwrap();
+
+        let fields = HashMap::from([(
+            "amount".to_string(),
+            FieldType::Number {
+                is_editable: false,
+                required: true,
+                default: None,
+                min_value: None,
+                max_value: None,
+            },
+        )]);
+        for template_id in ["template1", "template2"] {
+            let msg = ExecuteMsg::CreateLoanTemplate {
+                template_id: template_id.to_string(),
+                name: "Home Loan".to_string(),
+                fields: fields.clone(),
+                rules: vec![],
+            };
+            execute(deps.as_mut(), mock_env(), creator_info.clone(), msg).unwrap();
+            let msg = ExecuteMsg::SubmitTemplateForReview {
+                template_id: template_id.to_string(),
+                reviewer: addr("reviewer1").to_string(),
+            };
+            execute(deps.as_mut(), mock_env(), creator_info.clone(), msg).unwrap();
+        }
+        let msg = ExecuteMsg::ReviewTemplate {
+            user_id: addr("creator").to_string(),
+            template_id: "template1".to_string(),
+            approve: true,
+            comment: None,
+        };
+        execute(deps.as_mut(), mock_env(), reviewer_info.clone(), msg).unwrap();
+
+        let loan_request = LoanRequest {
+            template_id: "template1".to_string(),
+            values: HashMap::from([("amount".to_string(), "60000".into())]),
+            draft: false,
+        };
+        let msg = ExecuteMsg::CreateLoan {
+            user_id: addr("creator").to_string(),
+            loan_requests: vec![loan_request.clone(), loan_request],
+        };
+        execute(deps.as_mut(), mock_env(), creator_info, msg).unwrap();
+        let msg = ExecuteMsg::AssignLoans {
+            reviewer: addr("reviewer1").to_string(),
+            loans: vec![(addr("creator").to_string(), "1".to_string())],
+        };
+        execute(deps.as_mut(), mock_env(), admin_info.clone(), msg).unwrap();
+        for new_status in [LoanStatus::UnderReview, LoanStatus::Approved] {
+            let msg = ExecuteMsg::UpdateLoanReviewStatus {
+                user_id: addr("creator").to_string(),
+                loan_id: "1".to_string(),
+                new_status,
+                reason: None,
+            };
+            execute(deps.as_mut(), mock_env(), reviewer_info.clone(), msg).unwrap();
+        }
+        let msg = ExecuteMsg::ProposeAdminTransfer {
+            new_admin: addr("new_admin").to_string(),
+            expiry: None,
+        };
+        execute(deps.as_mut(), mock_env(), admin_info, msg).unwrap();
+
+        let mut schemas = QueryMsg::response_schemas().unwrap();
+        let creator = addr("creator").to_string();
+        let reviewer = addr("reviewer1").to_string();
+
+        assert_returns!(
+            deps,
+            schemas,
+            "user_info",
+            QueryMsg::UserInfo {
+                address: addr("creator"),
+            },
+            IdentityMetadata
+        );
+        let page = assert_returns!(
+            deps,
+            schemas,
+            "user_info_all",
+            QueryMsg::UserInfoAll {
+                start_after: None,
+                limit: None,
+                order: None,
+            },
+            Page<(Addr, IdentityMetadata), String>
+        );
+        assert_eq!(page.items.len(), 1);
+        let page = assert_returns!(
+            deps,
+            schemas,
+            "get_loans_for_user",
+            QueryMsg::GetLoansForUser {
+                user_id: creator.clone(),
+                include_values: Some(true),
+                start_after: None,
+                limit: None,
+                order: None,
+            },
+            Page<LoanSummary, String>
+        );
+        assert_eq!(page.items.len(), 2);
+        assert!(page.items[0].loan.is_some());
+        assert_returns!(
+            deps,
+            schemas,
+            "get_loan_details",
+            QueryMsg::GetLoanDetails {
+                user_id: creator.clone(),
+                loan_id: "1".to_string(),
+            },
+            LoanData
+        );
+        let page = assert_returns!(
+            deps,
+            schemas,
+            "get_loan_revisions",
+            QueryMsg::GetLoanRevisions {
+                loan_id: "1".to_string(),
+                start_after: None,
+                limit: None,
+                order: None,
+            },
+            Page<LoanRevision, u32>
+        );
+        assert!(page.items.is_empty());
+        let page = assert_returns!(
+            deps,
+            schemas,
+            "get_loans_for_reviewer",
+            QueryMsg::GetLoansForReviewer {
+                reviewer: reviewer.clone(),
+                include_values: None,
+                start_after: None,
+                limit: None,
+                order: None,
+            },
+            Page<LoanSummary, (String, String)>
+        );
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].loan, None);
+        let page = assert_returns!(
+            deps,
+            schemas,
+            "get_loans_by_status",
+            QueryMsg::GetLoansByStatus {
+                status: LoanStatus::Approved,
+                include_values: None,
+                start_after: None,
+                limit: None,
+                order: None,
+            },
+            Page<LoanSummary, (String, String)>
+        );
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].amount, Some("60000".to_string()));
+        let page = assert_returns!(
+            deps,
+            schemas,
+            "get_loans_by_date",
+            QueryMsg::GetLoansByDate {
+                from_date: 0,
+                date_type: "created".to_string(),
+                include_values: None,
+                start_after: None,
+                limit: None,
+                order: None,
+            },
+            Page<LoanSummary, (String, String)>
+        );
+        assert_eq!(page.items.len(), 2);
+        let page = assert_returns!(
+            deps,
+            schemas,
+            "search_loans",
+            QueryMsg::SearchLoans {
+                filter: LoanFilter {
+                    owner: Some(creator.clone()),
+                    statuses: vec![LoanStatus::Approved],
+                    ..LoanFilter::default()
+                },
+                include_values: None,
+                start_after: None,
+                limit: None,
+                order: None,
+            },
+            Page<LoanSummary, (String, String)>
+        );
+        assert_eq!(page.items.len(), 1);
+        assert_returns!(deps, schemas, "next_loan_id", QueryMsg::NextLoanId {}, String);
+        assert_returns!(
+            deps,
+            schemas,
+            "get_loan_reviewer",
+            QueryMsg::GetLoanReviewer {
+                user_id: creator.clone(),
+                loan_id: "1".to_string(),
+            },
+            Option<Addr>
+        );
+        assert_returns!(
+            deps,
+            schemas,
+            "get_loan_statistics",
+            QueryMsg::GetLoanStatistics {
+                reviewer: Some(reviewer.clone()),
+                utc_offset_minutes: None,
+            },
+            LoanStatistics
+        );
+        assert_returns!(
+            deps,
+            schemas,
+            "get_all_reviewer_statistics",
+            QueryMsg::GetAllReviewerStatistics {},
+            AllReviewerStatistics
+        );
+        let counters = assert_returns!(deps, schemas, "get_loan_counters", QueryMsg::GetLoanCounters {}, LoanCounters);
+        assert_eq!(counters.by_status.values().sum::<u64>(), 2);
+        let series = assert_returns!(
+            deps,
+            schemas,
+            "get_loan_time_series",
+            QueryMsg::GetLoanTimeSeries {
+                from: mock_env().block.time.seconds() - 86_400,
+                to: mock_env().block.time.seconds(),
+                granularity: Granularity::Month,
+                group_by: GroupBy::Status,
+            },
+            LoanTimeSeries
+        );
+        assert!(!series.buckets.is_empty());
+        let page = assert_returns!(
+            deps,
+            schemas,
+            "get_user_templates",
+            QueryMsg::GetUserTemplates {
+                user_id: creator.clone(),
+                start_after: None,
+                limit: None,
+                order: None,
+            },
+            Page<LoanTemplate, String>
+        );
+        assert_eq!(page.items.len(), 2);
+        let page = assert_returns!(
+            deps,
+            schemas,
+            "get_template_versions",
+            QueryMsg::GetTemplateVersions {
+                user_id: creator.clone(),
+                template_id: "template1".to_string(),
+                start_after: None,
+                limit: None,
+                order: None,
+            },
+            Page<LoanTemplate, u32>
+        );
+        assert_eq!(page.items.len(), 1);
+        let page = assert_returns!(
+            deps,
+            schemas,
+            "get_template_review_history",
+            QueryMsg::GetTemplateReviewHistory {
+                user_id: creator,
+                template_id: "template1".to_string(),
+                start_after: None,
+                limit: None,
+                order: None,
+            },
+            Page<TemplateReview, u32>
+        );
+        assert_eq!(page.items.len(), 1);
+        let page = assert_returns!(
+            deps,
+            schemas,
+            "get_reviewer_templates",
+            QueryMsg::GetReviewerTemplates {
+                reviewer,
+                start_after: None,
+                limit: None,
+                order: None,
+            },
+            Page<LoanTemplate, (String, String)>
+        );
+        assert_eq!(page.items.len(), 1);
+        let validation = assert_returns!(
+            deps,
+            schemas,
+            "validate_template",
+            QueryMsg::ValidateTemplate {
+                fields: fields.clone(),
+                rules: vec![],
+            },
+            TemplateValidation
+        );
+        assert!(validation.valid);
+        let validation = assert_returns!(
+            deps,
+            schemas,
+            "validate_loan_request",
+            QueryMsg::ValidateLoanRequest {
+                template_owner: addr("creator").to_string(),
+                template_id: "template1".to_string(),
+                values: HashMap::from([("amount".to_string(), "sixty".into())]),
+            },
+            LoanValidation
+        );
+        assert_eq!(validation.errors.len(), 1);
+        assert_returns!(deps, schemas, "get_config", QueryMsg::GetConfig {}, Config);
+        assert_returns!(
+            deps,
+            schemas,
+            "get_role_members",
+            QueryMsg::GetRoleMembers {
+                role: Role::Reviewer,
+            },
+            Vec<Addr>
+        );
+        let ownership = assert_returns!(deps, schemas, "ownership", QueryMsg::Ownership {}, Ownership);
+        assert_eq!(ownership.pending_admin, Some(addr("new_admin")));
+
+        // New queries must be added above
+        assert!(schemas.is_empty(), "unchecked queries: {:?}", schemas.keys());
+    }
+}
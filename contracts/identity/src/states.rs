@@ -1,21 +1,147 @@
-use cosmwasm_std::Addr;
-use cw_storage_plus::Map;
+use cosmwasm_std::{Addr, Empty};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
 
-use crate::models::{IdentityMetadata, LoanData, LoanTemplate, ReviewTuple};
+use crate::models::{AdminTransfer, Config, IdentityMetadata, LoanData, LoanRevision, LoanTemplate, ReviewTuple, TemplateReview};
+
+pub const CONFIG: Item<Config> = Item::new("config");
+
+// Pending two-step admin rotation, if any
+pub const ADMIN_TRANSFER: Item<AdminTransfer> = Item::new("admin_transfer");
+
+// Role registry: (role, member) -> Empty
+pub const ROLES: Map<(&str, &Addr), Empty> = Map::new("roles");
 
 pub const IDENTITIES: Map<&Addr, IdentityMetadata> = Map::new("identities");
 
 
-// Map to store loan data with (user_id, loan_id) as composite key
-pub const LOAN_STORAGE: Map<(&str, &str), LoanData> = Map::new("loans");
+// Next loan ID to hand out; IDs are sequential decimal strings starting at 1
+pub const NEXT_LOAN_ID: Item<u64> = Item::new("next_loan_id");
+
+// Width of a u64 in decimal, so padded loan keys sort in numeric order
+const LOAN_KEY_WIDTH: usize = 20;
+
+// Storage key of a loan ID: decimal IDs are zero-padded, legacy UUIDs are kept as they are
+pub fn loan_key(loan_id: &str) -> String {
+    if !loan_id.is_empty() && loan_id.bytes().all(|b| b.is_ascii_digit()) {
+        format!("{:0>width$}", loan_id, width = LOAN_KEY_WIDTH)
+    } else {
+        loan_id.to_string()
+    }
+}
+
+// Secondary indexes over LOAN_STORAGE, each ending in the (user_id, loan key) primary key
+pub struct LoanIndexes<'a> {
+    // Status name, e.g. "UnderReview"
+    pub status: MultiIndex<'a, String, LoanData, (String, String)>,
+    pub template: MultiIndex<'a, String, LoanData, (String, String)>,
+    pub created: MultiIndex<'a, u64, LoanData, (String, String)>,
+    // ("approved" | "rejected", decision date); undecided loans sit under ("", 0)
+    pub decided: MultiIndex<'a, (String, u64), LoanData, (String, String)>,
+    // Assigned reviewer address; unassigned loans sit under ""
+    pub reviewer: MultiIndex<'a, String, LoanData, (String, String)>,
+    // Address the decision counts for, see `decider`; undecided loans sit under ""
+    pub decider: MultiIndex<'a, String, LoanData, (String, String)>,
+}
+
+impl<'a> IndexList<LoanData> for LoanIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<LoanData>> + '_> {
+        let v: Vec<&dyn Index<LoanData>> = vec![
+            &self.status,
+            &self.template,
+            &self.created,
+            &self.decided,
+            &self.reviewer,
+            &self.decider,
+        ];
+        Box::new(v.into_iter())
+    }
+}
 
+fn decision_index(loan: &LoanData) -> (String, u64) {
+    match (loan.approval_date, loan.rejection_date) {
+        (Some(date), _) => ("approved".to_string(), date),
+        (_, Some(date)) => ("rejected".to_string(), date),
+        _ => (String::new(), 0),
+    }
+}
 
-// Map to store assignments of loans to reviewers
-pub const REVIEWER_ASSIGNMENTS: Map<&Addr, Vec<(String, String)>> = Map::new("assignments");
+// Whoever approved or rejected the loan; loans decided before that was recorded count for
+// their reviewer, which can no longer change once they are decided
+pub fn decider(loan: &LoanData) -> String {
+    if loan.approval_date.is_none() && loan.rejection_date.is_none() {
+        return String::new();
+    }
+    loan.decided_by
+        .as_ref()
+        .or(loan.reviewer.as_ref())
+        .map(Addr::to_string)
+        .unwrap_or_default()
+}
+
+// Map to store loan data with (user_id, loan_key(loan_id)) as composite key
+pub const LOAN_STORAGE: IndexedMap<(&str, &str), LoanData, LoanIndexes> = IndexedMap::new(
+    "loans",
+    LoanIndexes {
+        status: MultiIndex::new(|_pk, loan| loan.status.to_string(), "loans", "loans__status"),
+        template: MultiIndex::new(|_pk, loan| loan.template_id.clone(), "loans", "loans__template"),
+        created: MultiIndex::new(|_pk, loan| loan.creation_date, "loans", "loans__created"),
+        decided: MultiIndex::new(|_pk, loan| decision_index(loan), "loans", "loans__decided"),
+        reviewer: MultiIndex::new(
+            |_pk, loan| loan.reviewer.as_ref().map(Addr::to_string).unwrap_or_default(),
+            "loans",
+            "loans__reviewer",
+        ),
+        decider: MultiIndex::new(|_pk, loan| decider(loan), "loans", "loans__decider"),
+    },
+);
+
+// (loan key, revision) -> the loan's values before that amendment
+pub const LOAN_REVISIONS: Map<(&str, u32), LoanRevision> = Map::new("loan_revisions");
+
+// Running loan counters kept in step with LOAN_STORAGE by `stats::save_loan`; months are
+// UTC "YYYY-MM" of the creation or decision date and statuses use their display name
+pub const STATUS_COUNTS: Map<&str, u64> = Map::new("stats_status");
+// (template_id, status) -> loans
+pub const TEMPLATE_COUNTS: Map<(&str, &str), u64> = Map::new("stats_template");
+// (creation month, status) -> loans
+pub const MONTH_COUNTS: Map<(&str, &str), u64> = Map::new("stats_month");
+// (reviewer, creation month, status) -> loans; unassigned loans sit under ""
+pub const REVIEWER_COUNTS: Map<(&str, &str, &str), u64> = Map::new("stats_reviewer");
+// (decider, decision month, "approved" | "rejected") -> decisions
+pub const REVIEWER_DECISIONS: Map<(&str, &str, &str), u64> = Map::new("stats_reviewer_decisions");
+// decider -> total seconds from creation to decision over the loans they decided
+pub const REVIEWER_DECISION_TIME: Map<&str, u64> = Map::new("stats_reviewer_decision_time");
 
 // Store templates per user: (user_id, template_id) -> LoanTemplate
 pub const USER_TEMPLATES: Map<(&str, &str), LoanTemplate> = Map::new("user_templates");
 
-// Map template IDs to reviewers: (template_id) -> reviewer_id
-pub const TEMPLATE_REVIEWERS: Map<&str, ReviewTuple> = Map::new("template_reviewers");
+// Approved template versions, never changed once written: (user_id, template_id, version) -> LoanTemplate
+pub const TEMPLATE_VERSIONS: Map<(&str, &str, u32), LoanTemplate> = Map::new("template_versions");
+
+// Every decision on a template: (user_id, template_id, review) -> TemplateReview
+pub const TEMPLATE_REVIEW_HISTORY: Map<(&str, &str, u32), TemplateReview> = Map::new("template_review_history");
+
+// Templates waiting for a review, indexed by their assigned reviewer
+pub struct TemplateReviewerIndexes<'a> {
+    pub reviewer: MultiIndex<'a, String, ReviewTuple, (String, String)>,
+}
+
+impl<'a> IndexList<ReviewTuple> for TemplateReviewerIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<ReviewTuple>> + '_> {
+        let v: Vec<&dyn Index<ReviewTuple>> = vec![&self.reviewer];
+        Box::new(v.into_iter())
+    }
+}
+
+// Reviewer assignments: (user_id, template_id) -> ReviewTuple, removed once the review is done
+pub const TEMPLATE_REVIEWERS: IndexedMap<(&str, &str), ReviewTuple, TemplateReviewerIndexes> = IndexedMap::new(
+    "template_reviewers",
+    TemplateReviewerIndexes {
+        reviewer: MultiIndex::new(
+            |_pk, tuple| tuple.reviewer.clone(),
+            "template_reviewers",
+            "template_reviewers__reviewer",
+        ),
+    },
+);
 
@@ -0,0 +1,80 @@
+use cosmwasm_std::{Addr, Order, StdResult, Storage};
+use cw_storage_plus::{Map, PrimaryKey};
+
+use crate::{
+    calendar::YearMonth,
+    models::LoanData,
+    states::{
+        decider, LOAN_STORAGE, MONTH_COUNTS, REVIEWER_COUNTS, REVIEWER_DECISIONS, REVIEWER_DECISION_TIME,
+        STATUS_COUNTS, TEMPLATE_COUNTS,
+    },
+};
+
+// Saves a loan and moves the running counters from its previous state to the new one
+pub fn save_loan(storage: &mut dyn Storage, key: (&str, &str), loan: &LoanData) -> StdResult<()> {
+    let old = LOAN_STORAGE.may_load(storage, key)?;
+    LOAN_STORAGE.replace(storage, key, Some(loan), old.as_ref())?;
+    if let Some(old) = &old {
+        tally(storage, old, false)?;
+    }
+    tally(storage, loan, true)
+}
+
+// Rebuilds every counter from LOAN_STORAGE, returning the number of loans counted
+pub fn recompute(storage: &mut dyn Storage) -> StdResult<u64> {
+    STATUS_COUNTS.clear(storage);
+    TEMPLATE_COUNTS.clear(storage);
+    MONTH_COUNTS.clear(storage);
+    REVIEWER_COUNTS.clear(storage);
+    REVIEWER_DECISIONS.clear(storage);
+    REVIEWER_DECISION_TIME.clear(storage);
+
+    let loans = LOAN_STORAGE
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, loan)| loan))
+        .collect::<StdResult<Vec<_>>>()?;
+    for loan in &loans {
+        tally(storage, loan, true)?;
+    }
+    Ok(loans.len() as u64)
+}
+
+// Adds the loan to, or takes it out of, every counter it contributes to
+fn tally(storage: &mut dyn Storage, loan: &LoanData, add: bool) -> StdResult<()> {
+    let status = loan.status.to_string();
+    let month = YearMonth::of(loan.creation_date, 0).to_string();
+    let reviewer = loan.reviewer.as_ref().map(Addr::to_string).unwrap_or_default();
+    let decider = decider(loan);
+
+    bump(storage, STATUS_COUNTS, status.as_str(), 1, add)?;
+    bump(storage, TEMPLATE_COUNTS, (loan.template_id.as_str(), status.as_str()), 1, add)?;
+    bump(storage, MONTH_COUNTS, (month.as_str(), status.as_str()), 1, add)?;
+    bump(storage, REVIEWER_COUNTS, (reviewer.as_str(), month.as_str(), status.as_str()), 1, add)?;
+
+    for (kind, date) in [("approved", loan.approval_date), ("rejected", loan.rejection_date)] {
+        if let Some(date) = date {
+            let decision_month = YearMonth::of(date, 0).to_string();
+            let key = (decider.as_str(), decision_month.as_str(), kind);
+            bump(storage, REVIEWER_DECISIONS, key, 1, add)?;
+            let elapsed = date.saturating_sub(loan.creation_date);
+            bump(storage, REVIEWER_DECISION_TIME, decider.as_str(), elapsed, add)?;
+        }
+    }
+    Ok(())
+}
+
+// Counters that drop to zero are removed, so a rebuild leaves the same keys behind
+fn bump<'a, K>(storage: &mut dyn Storage, map: Map<K, u64>, key: K, by: u64, add: bool) -> StdResult<()>
+where
+    K: PrimaryKey<'a> + Clone,
+{
+    let current = map.may_load(storage, key.clone())?.unwrap_or_default();
+    let next = if add { current + by } else { current.saturating_sub(by) };
+    if next == 0 {
+        map.remove(storage, key);
+        Ok(())
+    } else {
+        map.save(storage, key, &next)
+    }
+}
+
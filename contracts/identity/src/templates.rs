@@ -0,0 +1,42 @@
+use cosmwasm_std::{Order, StdResult, Storage};
+
+use crate::{
+    models::{LoanTemplate, TemplateReview},
+    states::{TEMPLATE_REVIEW_HISTORY, TEMPLATE_VERSIONS},
+};
+
+// Latest approved version of a template, the one new loans are validated against
+pub fn latest_published(
+    storage: &dyn Storage,
+    owner: &str,
+    template_id: &str,
+) -> StdResult<Option<LoanTemplate>> {
+    TEMPLATE_VERSIONS
+        .prefix((owner, template_id))
+        .range(storage, None, None, Order::Descending)
+        .next()
+        .transpose()
+        .map(|latest| latest.map(|(_, template)| template))
+}
+
+// Keeps an approved version for the loans validated against it
+pub fn publish(storage: &mut dyn Storage, owner: &str, template: &LoanTemplate) -> StdResult<()> {
+    TEMPLATE_VERSIONS.save(storage, (owner, &template.id, template.version), template)
+}
+
+// Appends a decision to the template's review history under the next number
+pub fn record_review(
+    storage: &mut dyn Storage,
+    owner: &str,
+    template_id: &str,
+    mut review: TemplateReview,
+) -> StdResult<u32> {
+    review.review = TEMPLATE_REVIEW_HISTORY
+        .prefix((owner, template_id))
+        .keys(storage, None, None, Order::Descending)
+        .next()
+        .transpose()?
+        .map_or(1, |last| last + 1);
+    TEMPLATE_REVIEW_HISTORY.save(storage, (owner, template_id, review.review), &review)?;
+    Ok(review.review)
+}
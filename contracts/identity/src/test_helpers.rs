@@ -0,0 +1,99 @@
+// Fixtures shared by the test modules
+use cosmwasm_std::testing::{message_info, mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::{from_json, Addr, OwnedDeps, Response};
+
+use crate::error::ContractError;
+use crate::exec::execute;
+use crate::instantiate::instantiate;
+use crate::models::{FieldType, LoanRequest, ValidationRule};
+use crate::msg::{ExecuteMsg, InstantiateMsg};
+
+pub type Deps = OwnedDeps<MockStorage, MockApi, MockQuerier>;
+
+pub fn addr(name: &str) -> Addr {
+    MockApi::default().addr_make(name)
+}
+
+// Instantiates the contract and registers every actor used by the tests
+pub fn setup() -> Deps {
+    let mut deps = mock_dependencies();
+    let msg = InstantiateMsg {
+        admin: addr("admin").to_string(),
+        reviewers: vec![addr("reviewer1").to_string(), addr("reviewer2").to_string()],
+        loan_officers: vec![
+            addr("creator").to_string(),
+            addr("creator1").to_string(),
+            addr("creator2").to_string(),
+        ],
+        auditors: vec![addr("auditor1").to_string()],
+    };
+    instantiate(deps.as_mut(), mock_env(), message_info(&addr("admin"), &[]), msg).unwrap();
+    deps
+}
+
+// Creates a template owned by `creator`
+pub fn create_template<K: Into<String>>(
+    deps: &mut Deps,
+    template_id: &str,
+    fields: impl IntoIterator<Item = (K, FieldType)>,
+    rules: Vec<ValidationRule>,
+) -> Result<Response, ContractError> {
+    let msg = ExecuteMsg::CreateLoanTemplate {
+        template_id: template_id.to_string(),
+        name: "Home Loan".to_string(),
+        fields: fields.into_iter().map(|(name, field)| (name.into(), field)).collect(),
+        rules,
+    };
+    execute(deps.as_mut(), mock_env(), message_info(&addr("creator"), &[]), msg)
+}
+
+// Submits `creator`'s template to `reviewer1`, who approves it
+pub fn approve_template(deps: &mut Deps, template_id: &str) {
+    let msg = ExecuteMsg::SubmitTemplateForReview {
+        template_id: template_id.to_string(),
+        reviewer: addr("reviewer1").to_string(),
+    };
+    execute(deps.as_mut(), mock_env(), message_info(&addr("creator"), &[]), msg).unwrap();
+    let msg = ExecuteMsg::ReviewTemplate {
+        user_id: addr("creator").to_string(),
+        template_id: template_id.to_string(),
+        approve: true,
+        comment: None,
+    };
+    execute(deps.as_mut(), mock_env(), message_info(&addr("reviewer1"), &[]), msg).unwrap();
+}
+
+// Creates the template and takes it through review
+pub fn approved_template<K: Into<String>>(
+    deps: &mut Deps,
+    template_id: &str,
+    fields: impl IntoIterator<Item = (K, FieldType)>,
+    rules: Vec<ValidationRule>,
+) {
+    create_template(deps, template_id, fields, rules).unwrap();
+    approve_template(deps, template_id);
+}
+
+// Files one submitted loan for `creator`
+pub fn create_loan(deps: &mut Deps, template_id: &str, values: Vec<(&str, &str)>) -> Result<Response, ContractError> {
+    let msg = ExecuteMsg::CreateLoan {
+        user_id: addr("creator").to_string(),
+        loan_requests: vec![LoanRequest {
+            template_id: template_id.to_string(),
+            values: values.into_iter().map(|(k, v)| (k.to_string(), v.into())).collect(),
+            draft: false,
+        }],
+    };
+    execute(deps.as_mut(), mock_env(), message_info(&addr("creator"), &[]), msg)
+}
+
+// Same as `create_loan`, with the values written as JSON so they can be nested
+pub fn create_loan_json(deps: &mut Deps, template_id: &str, values: &str) -> Result<Response, ContractError> {
+    let request = format!(r#"{{"template_id":"{}","values":{}}}"#, template_id, values);
+    let request: LoanRequest = from_json(request.as_bytes()).unwrap();
+    let msg = ExecuteMsg::CreateLoan {
+        user_id: addr("creator").to_string(),
+        loan_requests: vec![request],
+    };
+    execute(deps.as_mut(), mock_env(), message_info(&addr("creator"), &[]), msg)
+}
@@ -0,0 +1,703 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use cosmwasm_std::{Api, Coin, Decimal256, Uint256};
+
+use crate::{
+    calendar::{add_months, parse_timestamp, SECONDS_PER_DAY},
+    countries,
+    error::ContractError,
+    models::{FieldError, FieldErrorCode, FieldType, FieldValue, LoanTemplate, ValidationRule},
+    rules,
+};
+
+// Decimal256 keeps 18 fractional digits, so no field can ask for more
+pub const MAX_PRECISION: u32 = Decimal256::DECIMAL_PLACES;
+// 100% in basis points
+pub const MAX_BPS: u32 = 10_000;
+// Objects and lists may nest, but not without limit
+pub const MAX_NESTING_DEPTH: u32 = 8;
+// Largest count of units a relative date bound can move by
+const MAX_DATE_OFFSET: i64 = 10_000;
+
+// Rejects field definitions and rules that would otherwise only fail once a loan uses them
+pub fn validate_template_fields(
+    api: &dyn Api,
+    now: i64,
+    fields: &HashMap<String, FieldType>,
+    rules: &[ValidationRule],
+) -> Result<(), ContractError> {
+    match template_problems(api, now, fields, rules).into_iter().next() {
+        Some(problem) => Err(problem),
+        None => Ok(()),
+    }
+}
+
+// Every problem with the field definitions, in field name order, then with the rules
+pub fn template_problems(
+    api: &dyn Api,
+    now: i64,
+    fields: &HashMap<String, FieldType>,
+    rules: &[ValidationRule],
+) -> Vec<ContractError> {
+    let mut problems = vec![];
+    object_problems(api, now, "", fields, 0, &mut problems);
+    problems.extend(rules::rule_problems(fields, rules));
+    problems
+}
+
+// Nested fields are reported by path, e.g. "collateral[].value"
+fn object_problems(
+    api: &dyn Api,
+    now: i64,
+    prefix: &str,
+    fields: &HashMap<String, FieldType>,
+    depth: u32,
+    problems: &mut Vec<ContractError>,
+) {
+    let mut names: Vec<&String> = fields.keys().collect();
+    names.sort();
+
+    for name in names {
+        if name.trim().is_empty() {
+            problems.push(ContractError::EmptyFieldName {});
+        }
+        let path = field_path(prefix, name);
+        let field_type = &fields[name];
+        let before = problems.len();
+        field_problems(api, now, &path, field_type, depth, problems);
+
+        // A default must pass the checks a loan value would, once the field itself is sound
+        if let Some(default) = field_type.default_value().filter(|_| problems.len() == before) {
+            value_errors(api, now, &path, field_type, &default.into(), problems);
+        }
+    }
+}
+
+fn field_problems(
+    api: &dyn Api,
+    now: i64,
+    field_name: &str,
+    field_type: &FieldType,
+    depth: u32,
+    problems: &mut Vec<ContractError>,
+) {
+    match field_type {
+        FieldType::String {
+            format,
+            min_value,
+            max_value,
+            ..
+        } => {
+            // Bounds on a string are lengths
+            format_problems(field_name, format, problems);
+            bound_problems(field_name, min_value, max_value, from_str::<usize>, problems);
+        }
+        FieldType::Number {
+            min_value,
+            max_value,
+            ..
+        } => bound_problems(field_name, min_value, max_value, from_str::<Decimal256>, problems),
+        FieldType::Integer {
+            min_value,
+            max_value,
+            ..
+        } => bound_problems(field_name, min_value, max_value, from_str::<Uint256>, problems),
+        FieldType::Decimal {
+            precision,
+            min_value,
+            max_value,
+            ..
+        } => {
+            if *precision > MAX_PRECISION {
+                problems.push(ContractError::InvalidPrecision {
+                    field_name: field_name.to_string(),
+                    precision: *precision,
+                });
+            }
+            // A bound the field could never hold is as malformed as an unparsable one
+            let mut fits = true;
+            for bound in [min_value, max_value].into_iter().flatten() {
+                if decimal_places(bound) > *precision {
+                    problems.push(ContractError::InvalidFieldBound {
+                        field_name: field_name.to_string(),
+                        bound: bound.clone(),
+                    });
+                    fits = false;
+                }
+            }
+            if fits {
+                bound_problems(field_name, min_value, max_value, from_str::<Decimal256>, problems);
+            }
+        }
+        FieldType::Boolean { .. } => {}
+        FieldType::Date {
+            format,
+            min_value,
+            max_value,
+            ..
+        } => {
+            // Relative bounds are compared as they stand now
+            format_problems(field_name, format, problems);
+            bound_problems(field_name, min_value, max_value, |b| date_bound(b, now), problems);
+        }
+        FieldType::Enum { options, .. } => {
+            if options.is_empty() {
+                problems.push(ContractError::EmptyEnumOptions {
+                    field_name: field_name.to_string(),
+                });
+            }
+            for (i, option) in options.iter().enumerate() {
+                if options[..i].contains(option) {
+                    problems.push(ContractError::DuplicateEnumOption {
+                        field_name: field_name.to_string(),
+                        option: option.clone(),
+                    });
+                }
+            }
+        }
+        FieldType::Coin { allowed_denoms, .. } => {
+            for denom in allowed_denoms.iter().filter(|denom| !is_valid_denom(denom)) {
+                problems.push(ContractError::InvalidDenom {
+                    field_name: field_name.to_string(),
+                    denom: denom.clone(),
+                });
+            }
+        }
+        FieldType::Percentage { min_bps, max_bps, .. } => {
+            for bps in [min_bps, max_bps].into_iter().flatten() {
+                if *bps > MAX_BPS {
+                    problems.push(ContractError::InvalidBasisPoints {
+                        field_name: field_name.to_string(),
+                        bps: *bps,
+                    });
+                }
+            }
+            if let (Some(min), Some(max)) = (min_bps, max_bps) {
+                if min > max {
+                    problems.push(ContractError::InvalidFieldRange {
+                        field_name: field_name.to_string(),
+                    });
+                }
+            }
+        }
+        FieldType::Object { fields, .. } => {
+            if depth >= MAX_NESTING_DEPTH {
+                problems.push(ContractError::NestingTooDeep {
+                    field_name: field_name.to_string(),
+                });
+            } else {
+                object_problems(api, now, field_name, fields, depth + 1, problems);
+            }
+        }
+        FieldType::List {
+            item,
+            min_items,
+            max_items,
+            ..
+        } => {
+            if let (Some(min), Some(max)) = (min_items, max_items) {
+                if min > max {
+                    problems.push(ContractError::InvalidFieldRange {
+                        field_name: field_name.to_string(),
+                    });
+                }
+            }
+            if depth >= MAX_NESTING_DEPTH {
+                problems.push(ContractError::NestingTooDeep {
+                    field_name: field_name.to_string(),
+                });
+            } else {
+                field_problems(api, now, &format!("{}[]", field_name), item, depth + 1, problems);
+            }
+        }
+        FieldType::Address { .. }
+        | FieldType::Email { .. }
+        | FieldType::PhoneNumber { .. }
+        | FieldType::CountryCode { .. } => {}
+    }
+}
+
+fn field_path(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", prefix, name)
+    }
+}
+
+fn format_problems(field_name: &str, format: &Option<String>, problems: &mut Vec<ContractError>) {
+    if let Some(format) = format {
+        if regex_lite::Regex::new(format).is_err() {
+            problems.push(ContractError::InvalidFieldFormat {
+                field_name: field_name.to_string(),
+                format: format.clone(),
+            });
+        }
+    }
+}
+
+// Each bound must parse, and the minimum may not exceed the maximum
+fn bound_problems<T: PartialOrd>(
+    field_name: &str,
+    min_value: &Option<String>,
+    max_value: &Option<String>,
+    parse: impl Fn(&str) -> Option<T>,
+    problems: &mut Vec<ContractError>,
+) {
+    let mut parsed = |bound: &Option<String>| match parse_bound(field_name, bound, &parse) {
+        Ok(parsed) => parsed,
+        Err(problem) => {
+            problems.push(problem);
+            None
+        }
+    };
+    let min = parsed(min_value);
+    let max = parsed(max_value);
+    if let (Some(min), Some(max)) = (min, max) {
+        if min > max {
+            problems.push(ContractError::InvalidFieldRange {
+                field_name: field_name.to_string(),
+            });
+        }
+    }
+}
+
+// Fills in the default of every field the values leave out, nested objects included
+pub fn apply_defaults(fields: &HashMap<String, FieldType>, values: &mut HashMap<String, FieldValue>) {
+    for (name, field_type) in fields {
+        if let Some(value) = values.get_mut(name) {
+            apply_nested_defaults(field_type, value);
+        } else if let Some(default) = field_type.default_value() {
+            values.insert(name.clone(), default.into());
+        }
+    }
+}
+
+fn apply_nested_defaults(field_type: &FieldType, value: &mut FieldValue) {
+    match (field_type, value) {
+        (FieldType::Object { fields, .. }, FieldValue::Object(values)) => apply_defaults(fields, values),
+        (FieldType::List { item, .. }, FieldValue::List(items)) => {
+            for item_value in items {
+                apply_nested_defaults(item, item_value);
+            }
+        }
+        _ => {}
+    }
+}
+
+// Checks a loan's values against its template, then its rules once every value
+// passes. Fails with every problem found: values are named by path, e.g.
+// "collateral[2].value", in field name order
+pub fn validate_loan(
+    api: &dyn Api,
+    now: i64,
+    template: &LoanTemplate,
+    values: &HashMap<String, FieldValue>,
+) -> Result<(), ContractError> {
+    let errors = loan_errors(api, now, template, values)?;
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(ContractError::ValidationFailed { errors })
+    }
+}
+
+// Errs only when the template itself is broken: a stored rule, format or bound it can no
+// longer use, which no change to the values would fix
+pub fn loan_errors(
+    api: &dyn Api,
+    now: i64,
+    template: &LoanTemplate,
+    values: &HashMap<String, FieldValue>,
+) -> Result<Vec<FieldError>, ContractError> {
+    let mut problems = vec![];
+    object_errors(api, now, "", &template.fields, values, &mut problems);
+    if problems.is_empty() {
+        problems = rules::rule_violations(&template.fields, &template.rules, values)?;
+    }
+    problems.into_iter().map(field_error).collect()
+}
+
+// Machine-readable form of a problem with one value; a problem with the template is
+// handed back as the error
+pub fn field_error(problem: ContractError) -> Result<FieldError, ContractError> {
+    let (field, code) = match &problem {
+        ContractError::MissingField { field_name } => (field_name, FieldErrorCode::Missing),
+        ContractError::UnknownField { field_name } => (field_name, FieldErrorCode::Unknown),
+        ContractError::InvalidNumberField { field_name } | ContractError::WrongValueType { field_name, .. } => {
+            (field_name, FieldErrorCode::Type)
+        }
+        ContractError::FieldOutOfRange { field_name }
+        | ContractError::ItemCountOutOfRange { field_name }
+        | ContractError::NotAnOption { field_name }
+        | ContractError::DenomNotAllowed { field_name, .. } => (field_name, FieldErrorCode::Range),
+        // Rule violations carry the rule's ID in place of a field path
+        ContractError::RuleViolated { rule_id, .. } => (rule_id, FieldErrorCode::Rule),
+        ContractError::InvalidFormat { field_name }
+        | ContractError::InvalidAddress { field_name }
+        | ContractError::InvalidCoin { field_name }
+        | ContractError::TooManyDecimalPlaces { field_name, .. } => (field_name, FieldErrorCode::Format),
+        // A stored format or bound the field cannot use is the template's to fix
+        _ => return Err(problem),
+    };
+    Ok(FieldError {
+        field: field.clone(),
+        code,
+        message: problem.to_string(),
+    })
+}
+
+fn object_errors(
+    api: &dyn Api,
+    now: i64,
+    prefix: &str,
+    fields: &HashMap<String, FieldType>,
+    values: &HashMap<String, FieldValue>,
+    problems: &mut Vec<ContractError>,
+) {
+    // Values must not name fields the template does not define
+    let mut unknown: Vec<&String> = values.keys().filter(|name| !fields.contains_key(*name)).collect();
+    unknown.sort();
+    for name in unknown {
+        problems.push(ContractError::UnknownField {
+            field_name: field_path(prefix, name),
+        });
+    }
+
+    let mut names: Vec<&String> = fields.keys().collect();
+    names.sort();
+
+    for name in names {
+        let path = field_path(prefix, name);
+        let field_type = &fields[name];
+        match values.get(name) {
+            Some(value) => value_errors(api, now, &path, field_type, value, problems),
+            None if field_type.required() => problems.push(ContractError::MissingField { field_name: path }),
+            None => {}
+        }
+    }
+}
+
+// Checks one loan value, and everything nested in it, against its field definition
+fn value_errors(
+    api: &dyn Api,
+    now: i64,
+    field_name: &str,
+    field_type: &FieldType,
+    value: &FieldValue,
+    problems: &mut Vec<ContractError>,
+) {
+    let wrong_type = |expected: &str| ContractError::WrongValueType {
+        field_name: field_name.to_string(),
+        expected: expected.to_string(),
+    };
+    match (field_type, value) {
+        (FieldType::Object { fields, .. }, FieldValue::Object(values)) => {
+            object_errors(api, now, field_name, fields, values, problems)
+        }
+        (FieldType::Object { .. }, _) => problems.push(wrong_type("an object")),
+        (
+            FieldType::List {
+                item,
+                min_items,
+                max_items,
+                ..
+            },
+            FieldValue::List(items),
+        ) => {
+            let count = items.len() as u64;
+            let too_few = min_items.is_some_and(|min| count < u64::from(min));
+            let too_many = max_items.is_some_and(|max| count > u64::from(max));
+            if too_few || too_many {
+                problems.push(ContractError::ItemCountOutOfRange {
+                    field_name: field_name.to_string(),
+                });
+            }
+            for (i, item_value) in items.iter().enumerate() {
+                value_errors(api, now, &format!("{}[{}]", field_name, i), item, item_value, problems);
+            }
+        }
+        (FieldType::List { .. }, _) => problems.push(wrong_type("a list")),
+        (_, FieldValue::Text(text)) => {
+            if let Err(problem) = validate_text(api, now, field_name, field_type, text) {
+                problems.push(problem);
+            }
+        }
+        (_, _) => problems.push(wrong_type("a single value")),
+    }
+}
+
+fn validate_text(
+    api: &dyn Api,
+    now: i64,
+    field_name: &str,
+    field_type: &FieldType,
+    value: &str,
+) -> Result<(), ContractError> {
+    let invalid_format = || ContractError::InvalidFormat {
+        field_name: field_name.to_string(),
+    };
+    match field_type {
+        FieldType::String {
+            format,
+            min_value,
+            max_value,
+            ..
+        } => {
+            if let Some(f) = format {
+                check_format(field_name, f, value)?;
+            }
+            // Bounds on a string are lengths in characters
+            check_range(field_name, value.chars().count(), min_value, max_value, from_str::<usize>)?;
+        }
+        FieldType::Number {
+            min_value,
+            max_value,
+            ..
+        } => {
+            let parsed = parse_number::<Decimal256>(field_name, value)?;
+            check_range(field_name, parsed, min_value, max_value, from_str::<Decimal256>)?;
+        }
+        FieldType::Integer {
+            min_value,
+            max_value,
+            ..
+        } => {
+            let parsed = parse_number::<Uint256>(field_name, value)?;
+            check_range(field_name, parsed, min_value, max_value, from_str::<Uint256>)?;
+        }
+        FieldType::Decimal {
+            precision,
+            min_value,
+            max_value,
+            ..
+        } => {
+            let parsed = parse_number::<Decimal256>(field_name, value)?;
+            if decimal_places(value) > *precision {
+                return Err(ContractError::TooManyDecimalPlaces {
+                    field_name: field_name.to_string(),
+                    precision: *precision,
+                });
+            }
+            check_range(field_name, parsed, min_value, max_value, from_str::<Decimal256>)?;
+        }
+        FieldType::Boolean { .. } => {
+            if value != "true" && value != "false" {
+                return Err(invalid_format());
+            }
+        }
+        FieldType::Date {
+            format,
+            min_value,
+            max_value,
+            ..
+        } => {
+            // The value must read as a date; the format can only narrow the accepted forms
+            let timestamp = parse_timestamp(value).ok_or_else(invalid_format)?;
+            if let Some(f) = format {
+                check_format(field_name, f, value)?;
+            }
+            check_range(field_name, timestamp, min_value, max_value, |b| date_bound(b, now))?;
+        }
+        FieldType::Enum { options, .. } => {
+            if !options.iter().any(|option| option == value) {
+                return Err(ContractError::NotAnOption {
+                    field_name: field_name.to_string(),
+                });
+            }
+        }
+        FieldType::Address { .. } => {
+            api.addr_validate(value).map_err(|_| ContractError::InvalidAddress {
+                field_name: field_name.to_string(),
+            })?;
+        }
+        FieldType::Coin { allowed_denoms, .. } => {
+            let coin = Coin::from_str(value)
+                .ok()
+                .filter(|coin| is_valid_denom(&coin.denom))
+                .ok_or_else(|| ContractError::InvalidCoin {
+                    field_name: field_name.to_string(),
+                })?;
+            if !allowed_denoms.is_empty() && !allowed_denoms.contains(&coin.denom) {
+                return Err(ContractError::DenomNotAllowed {
+                    field_name: field_name.to_string(),
+                    denom: coin.denom,
+                });
+            }
+        }
+        FieldType::Percentage { min_bps, max_bps, .. } => {
+            let percent = parse_number::<Decimal256>(field_name, value)?;
+            // Basis points are the finest resolution, i.e. two decimals of a percent
+            if decimal_places(value) > 2 {
+                return Err(ContractError::TooManyDecimalPlaces {
+                    field_name: field_name.to_string(),
+                    precision: 2,
+                });
+            }
+            let bps = percent * Decimal256::from_ratio(100u32, 1u32);
+            let max = Decimal256::from_ratio(max_bps.unwrap_or(MAX_BPS).min(MAX_BPS), 1u32);
+            let min = Decimal256::from_ratio(min_bps.unwrap_or(0), 1u32);
+            if bps < min || bps > max {
+                return Err(ContractError::FieldOutOfRange {
+                    field_name: field_name.to_string(),
+                });
+            }
+        }
+        FieldType::Email { .. } => {
+            if !is_email(value) {
+                return Err(invalid_format());
+            }
+        }
+        FieldType::PhoneNumber { .. } => {
+            if !is_e164(value) {
+                return Err(invalid_format());
+            }
+        }
+        FieldType::CountryCode { .. } => {
+            if !countries::is_country_code(value) {
+                return Err(invalid_format());
+            }
+        }
+        // Only reached with a text value, which `value_errors` already rejects
+        FieldType::Object { .. } | FieldType::List { .. } => {}
+    }
+    Ok(())
+}
+
+fn check_format(field_name: &str, format: &str, value: &str) -> Result<(), ContractError> {
+    // Templates stored before formats were validated may still hold a broken one
+    let re = regex_lite::Regex::new(format).map_err(|_| ContractError::InvalidFieldFormat {
+        field_name: field_name.to_string(),
+        format: format.to_string(),
+    })?;
+    if !re.is_match(value) {
+        return Err(ContractError::InvalidFormat {
+            field_name: field_name.to_string(),
+        });
+    }
+    Ok(())
+}
+
+fn parse_number<T: FromStr>(field_name: &str, value: &str) -> Result<T, ContractError> {
+    value.parse().map_err(|_| ContractError::InvalidNumberField {
+        field_name: field_name.to_string(),
+    })
+}
+
+fn from_str<T: FromStr>(value: &str) -> Option<T> {
+    value.parse().ok()
+}
+
+fn parse_bound<T>(
+    field_name: &str,
+    bound: &Option<String>,
+    parse: impl Fn(&str) -> Option<T>,
+) -> Result<Option<T>, ContractError> {
+    bound
+        .as_ref()
+        .map(|b| {
+            parse(b).ok_or_else(|| ContractError::InvalidFieldBound {
+                field_name: field_name.to_string(),
+                bound: b.clone(),
+            })
+        })
+        .transpose()
+}
+
+// String, Number and Date fields stored before bounds were validated may hold malformed ones
+fn check_range<T: PartialOrd>(
+    field_name: &str,
+    value: T,
+    min_value: &Option<String>,
+    max_value: &Option<String>,
+    parse: impl Fn(&str) -> Option<T>,
+) -> Result<(), ContractError> {
+    let below = parse_bound(field_name, min_value, &parse)?.is_some_and(|min| value < min);
+    let above = parse_bound(field_name, max_value, &parse)?.is_some_and(|max| value > max);
+    if below || above {
+        return Err(ContractError::FieldOutOfRange {
+            field_name: field_name.to_string(),
+        });
+    }
+    Ok(())
+}
+
+// A date bound: an absolute date, or "now" moved by a whole number of units, e.g.
+// "now-18y" or "now+30d". Units are s, h, d, w, m (calendar months) and y
+pub fn date_bound(bound: &str, now: i64) -> Option<i64> {
+    let Some(offset) = bound.strip_prefix("now") else {
+        return parse_timestamp(bound);
+    };
+    if offset.is_empty() {
+        return Some(now);
+    }
+    let (sign, amount) = if let Some(amount) = offset.strip_prefix('+') {
+        (1, amount)
+    } else {
+        (-1, offset.strip_prefix('-')?)
+    };
+    let unit_at = amount.find(|c: char| !c.is_ascii_digit())?;
+    let (count, unit) = amount.split_at(unit_at);
+    // Ten thousand years either way is plenty and keeps the arithmetic in range
+    let count: i64 = count.parse().ok().filter(|count| *count <= MAX_DATE_OFFSET)?;
+    let count = sign * count;
+    match unit {
+        "s" => Some(now + count),
+        "h" => Some(now + count * 3600),
+        "d" => Some(now + count * SECONDS_PER_DAY),
+        "w" => Some(now + count * 7 * SECONDS_PER_DAY),
+        "m" => Some(add_months(now, count)),
+        "y" => Some(add_months(now, count * 12)),
+        _ => None,
+    }
+}
+
+// Digits after the decimal point, ignoring trailing zeros
+fn decimal_places(value: &str) -> u32 {
+    value
+        .split_once('.')
+        .map_or(0, |(_, fraction)| fraction.trim_end_matches('0').len() as u32)
+}
+
+// Cosmos SDK denom rules: a letter, then 2 to 127 letters, digits or "/:._-"
+fn is_valid_denom(denom: &str) -> bool {
+    let mut chars = denom.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && (3..=128).contains(&denom.len())
+        && chars.all(|c| c.is_ascii_alphanumeric() || "/:._-".contains(c))
+}
+
+// A pragmatic subset of RFC 5321: dot-atom local part and a dotted host name
+fn is_email(value: &str) -> bool {
+    let Some((local, domain)) = value.split_once('@') else {
+        return false;
+    };
+    let local_ok = (1..=64).contains(&local.len())
+        && !local.starts_with('.')
+        && !local.ends_with('.')
+        && !local.contains("..")
+        && local
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+/=?^_`{|}~.-".contains(c));
+    let labels: Vec<&str> = domain.split('.').collect();
+    let domain_ok = domain.len() <= 253
+        && labels.len() >= 2
+        && labels.iter().all(|label| {
+            (1..=63).contains(&label.len())
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+        && labels.last().is_some_and(|tld| tld.len() >= 2 && tld.chars().all(|c| c.is_ascii_alphabetic()));
+    local_ok && domain_ok
+}
+
+// "+" then a country code and subscriber number of at most 15 digits, never starting with 0
+fn is_e164(value: &str) -> bool {
+    value.strip_prefix('+').is_some_and(|digits| {
+        (8..=15).contains(&digits.len())
+            && !digits.starts_with('0')
+            && digits.chars().all(|c| c.is_ascii_digit())
+    })
+}
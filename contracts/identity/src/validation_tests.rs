@@ -0,0 +1,874 @@
+mod test {
+
+    use std::collections::HashMap;
+
+    use crate::error::ContractError;
+    use crate::models::{FieldError, FieldErrorCode, FieldType, FieldValue, LoanValidation, TemplateValidation};
+    use crate::msg::QueryMsg;
+    use crate::query::query;
+    use crate::states::TEMPLATE_VERSIONS;
+    use crate::validation::field_error;
+    use crate::test_helpers::{addr, approved_template, create_loan, create_loan_json, create_template, setup};
+    use cosmwasm_std::testing::mock_env;
+    use cosmwasm_std::from_json;
+
+    // The error `create_loan` returns when `problem` is all that is wrong with the values
+    fn failed(problem: ContractError) -> ContractError {
+        ContractError::ValidationFailed {
+            errors: vec![field_error(problem).unwrap()],
+        }
+    }
+
+    fn number(min: Option<&str>, max: Option<&str>) -> FieldType {
+        FieldType::Number {
+            is_editable: false,
+            required: true,
+            default: None,
+            min_value: min.map(str::to_string),
+            max_value: max.map(str::to_string),
+        }
+    }
+
+    fn integer(min: Option<&str>, max: Option<&str>) -> FieldType {
+        FieldType::Integer {
+            is_editable: false,
+            required: true,
+            default: None,
+            min_value: min.map(str::to_string),
+            max_value: max.map(str::to_string),
+        }
+    }
+
+    fn decimal(precision: u32, min: Option<&str>, max: Option<&str>) -> FieldType {
+        FieldType::Decimal {
+            is_editable: false,
+            required: true,
+            default: None,
+            precision,
+            min_value: min.map(str::to_string),
+            max_value: max.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_numeric_bounds_are_checked_when_template_is_created() {
+        let mut deps = setup();
+        let bad_bound = |bound: &str| ContractError::InvalidFieldBound {
+            field_name: "amount".to_string(),
+            bound: bound.to_string(),
+        };
+
+        let err = create_template(&mut deps, "t1", vec![("amount", number(Some("fifty"), None))], vec![]).unwrap_err();
+        assert_eq!(err, bad_bound("fifty"));
+        let err = create_template(&mut deps, "t1", vec![("amount", number(None, Some("-1")))], vec![]).unwrap_err();
+        assert_eq!(err, bad_bound("-1"));
+        let err = create_template(&mut deps, "t1", vec![("amount", integer(Some("1.5"), None))], vec![]).unwrap_err();
+        assert_eq!(err, bad_bound("1.5"));
+        let err = create_template(&mut deps, "t1", vec![("amount", decimal(2, None, Some("10.005")))], vec![]).unwrap_err();
+        assert_eq!(err, bad_bound("10.005"));
+
+        let err = create_template(&mut deps, "t1", vec![("amount", number(Some("10"), Some("9.99")))], vec![]).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidFieldRange {
+                field_name: "amount".to_string()
+            }
+        );
+        let err = create_template(&mut deps, "t1", vec![("amount", decimal(19, None, None))], vec![]).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidPrecision {
+                field_name: "amount".to_string(),
+                precision: 19,
+            }
+        );
+
+        // Trailing zeros do not count against the precision
+        create_template(&mut deps, "t1", vec![("amount", decimal(2, Some("0.10"), Some("10.500")))], vec![]).unwrap();
+    }
+
+    #[test]
+    fn test_numeric_values_are_compared_exactly() {
+        let mut deps = setup();
+        approved_template(
+            &mut deps,
+            "t1",
+            vec![
+                ("rate", number(None, Some("0.3"))),
+                ("term", integer(Some("12"), Some("360"))),
+                ("amount", decimal(2, Some("1000"), None)),
+            ],
+            vec![],
+        );
+
+        create_loan(&mut deps, "t1", vec![("rate", "0.3"), ("term", "360"), ("amount", "1000.50")]).unwrap();
+
+        // A float would round this down to 0.3
+        let err = create_loan(&mut deps, "t1", vec![("rate", "0.300000000000000001"), ("term", "12"), ("amount", "1000")])
+            .unwrap_err();
+        assert_eq!(err, failed(ContractError::FieldOutOfRange { field_name: "rate".to_string() }));
+
+        let err = create_loan(&mut deps, "t1", vec![("rate", "0.1"), ("term", "12.0"), ("amount", "1000")]).unwrap_err();
+        assert_eq!(err, failed(ContractError::InvalidNumberField { field_name: "term".to_string() }));
+        let err = create_loan(&mut deps, "t1", vec![("rate", "0.1"), ("term", "11"), ("amount", "1000")]).unwrap_err();
+        assert_eq!(err, failed(ContractError::FieldOutOfRange { field_name: "term".to_string() }));
+
+        let err = create_loan(&mut deps, "t1", vec![("rate", "0.1"), ("term", "12"), ("amount", "1000.005")]).unwrap_err();
+        assert_eq!(
+            err,
+            failed(ContractError::TooManyDecimalPlaces {
+                field_name: "amount".to_string(),
+                precision: 2,
+            })
+        );
+        let err = create_loan(&mut deps, "t1", vec![("rate", "-0.1"), ("term", "12"), ("amount", "1000")]).unwrap_err();
+        assert_eq!(err, failed(ContractError::InvalidNumberField { field_name: "rate".to_string() }));
+    }
+
+    #[test]
+    fn test_template_structure_is_checked_on_create_and_by_query() {
+        let mut deps = setup();
+        let string = |format: Option<&str>, min: Option<&str>, max: Option<&str>| FieldType::String {
+            is_editable: true,
+            required: true,
+            default: None,
+            format: format.map(str::to_string),
+            min_value: min.map(str::to_string),
+            max_value: max.map(str::to_string),
+        };
+        let date = |min: Option<&str>| FieldType::Date {
+            is_editable: true,
+            required: true,
+            default: None,
+            format: None,
+            min_value: min.map(str::to_string),
+            max_value: None,
+        };
+
+        let err = create_template(&mut deps, "t1", vec![(" ", string(None, None, None))], vec![]).unwrap_err();
+        assert_eq!(err, ContractError::EmptyFieldName {});
+        let err = create_template(&mut deps, "t1", vec![("name", string(Some("[a-z"), None, None))], vec![]).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidFieldFormat {
+                field_name: "name".to_string(),
+                format: "[a-z".to_string(),
+            }
+        );
+        let err = create_template(&mut deps, "t1", vec![("name", string(None, Some("10"), Some("2")))], vec![]).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidFieldRange {
+                field_name: "name".to_string()
+            }
+        );
+        let err = create_template(&mut deps, "t1", vec![("born", date(Some("yesterday")))], vec![]).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidFieldBound {
+                field_name: "born".to_string(),
+                bound: "yesterday".to_string(),
+            }
+        );
+        // Nothing was stored along the way
+        create_template(&mut deps, "t1", vec![("name", string(Some("^[A-Z]"), Some("1"), Some("64")))], vec![]).unwrap();
+
+        // The query reports every problem at once and stores nothing
+        let fields = HashMap::from([
+            ("".to_string(), FieldType::Boolean { is_editable: false, required: true, default: None }),
+            ("amount".to_string(), number(Some("ten"), Some("-5"))),
+            ("code".to_string(), string(Some("("), Some("a"), None)),
+            ("rate".to_string(), decimal(30, Some("2"), Some("1"))),
+        ]);
+        let msg = QueryMsg::ValidateTemplate { fields, rules: vec![] };
+        let validation: TemplateValidation = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert!(!validation.valid);
+        assert_eq!(
+            validation.problems,
+            vec![
+                ContractError::EmptyFieldName {}.to_string(),
+                "Bound 'ten' of field 'amount' is not a valid value for the field".to_string(),
+                "Bound '-5' of field 'amount' is not a valid value for the field".to_string(),
+                "Format '(' of field 'code' is not a valid regular expression".to_string(),
+                "Bound 'a' of field 'code' is not a valid value for the field".to_string(),
+                "Precision 30 of field 'rate' is above the 18 decimal places supported".to_string(),
+                "Minimum of field 'rate' is above its maximum".to_string(),
+            ]
+        );
+
+        let fields = HashMap::from([("amount".to_string(), decimal(2, Some("0.01"), None))]);
+        let msg = QueryMsg::ValidateTemplate { fields, rules: vec![] };
+        let validation: TemplateValidation = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(
+            validation,
+            TemplateValidation {
+                valid: true,
+                problems: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn test_rich_field_definitions_are_checked() {
+        let deps = setup();
+        let fields = HashMap::from([
+            (
+                "purpose".to_string(),
+                FieldType::Enum {
+                    is_editable: false,
+                    required: true,
+                    default: None,
+                    options: vec!["home".to_string(), "car".to_string(), "home".to_string()],
+                },
+            ),
+            (
+                "tier".to_string(),
+                FieldType::Enum {
+                    is_editable: false,
+                    required: true,
+                    default: None,
+                    options: vec![],
+                },
+            ),
+            (
+                "collateral".to_string(),
+                FieldType::Coin {
+                    is_editable: false,
+                    required: true,
+                    default: None,
+                    allowed_denoms: vec!["uatom".to_string(), "1bad".to_string()],
+                },
+            ),
+            (
+                "rate".to_string(),
+                FieldType::Percentage {
+                    is_editable: false,
+                    required: true,
+                    default: None,
+                    min_bps: Some(500),
+                    max_bps: Some(12_000),
+                },
+            ),
+            (
+                "ltv".to_string(),
+                FieldType::Percentage {
+                    is_editable: false,
+                    required: true,
+                    default: None,
+                    min_bps: Some(9_000),
+                    max_bps: Some(8_000),
+                },
+            ),
+        ]);
+        let msg = QueryMsg::ValidateTemplate { fields, rules: vec![] };
+        let validation: TemplateValidation = from_json(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(
+            validation.problems,
+            vec![
+                "Denom '1bad' of field 'collateral' is not a valid denom".to_string(),
+                "Minimum of field 'ltv' is above its maximum".to_string(),
+                "Option 'home' of field 'purpose' is listed more than once".to_string(),
+                "Bound of 12000 basis points on field 'rate' is above 10000".to_string(),
+                "Field 'tier' needs at least one option".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rich_field_values_are_validated() {
+        let mut deps = setup();
+        let single = |field_type: FieldType| vec![("value", field_type)];
+        approved_template(
+            &mut deps,
+            "enum",
+            single(FieldType::Enum {
+                is_editable: false,
+                required: true,
+                default: None,
+                options: vec!["home".to_string(), "car".to_string()],
+            }),
+            vec![],
+        );
+        approved_template(&mut deps, "address", single(FieldType::Address { is_editable: false, required: true, default: None }), vec![]);
+        approved_template(
+            &mut deps,
+            "coin",
+            single(FieldType::Coin {
+                is_editable: false,
+                required: true,
+                default: None,
+                allowed_denoms: vec!["uatom".to_string(), "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2".to_string()],
+            }),
+            vec![],
+        );
+        approved_template(
+            &mut deps,
+            "percentage",
+            single(FieldType::Percentage {
+                is_editable: false,
+                required: true,
+                default: None,
+                min_bps: Some(50),
+                max_bps: Some(2_500),
+            }),
+            vec![],
+        );
+        approved_template(&mut deps, "email", single(FieldType::Email { is_editable: false, required: true, default: None }), vec![]);
+        approved_template(&mut deps, "phone", single(FieldType::PhoneNumber { is_editable: false, required: true, default: None }), vec![]);
+        approved_template(&mut deps, "country", single(FieldType::CountryCode { is_editable: false, required: true, default: None }), vec![]);
+
+        let borrower = addr("borrower").to_string();
+        let valid = [
+            ("enum", "car"),
+            ("address", borrower.as_str()),
+            ("coin", "1000000uatom"),
+            ("coin", "5ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2"),
+            ("percentage", "0.5"),
+            ("percentage", "25"),
+            ("percentage", "12.25"),
+            ("email", "jane.doe+loans@example.co.uk"),
+            ("phone", "+14155550100"),
+            ("country", "DE"),
+        ];
+        for (template_id, value) in valid {
+            create_loan(&mut deps, template_id, vec![("value", value)])
+                .unwrap_or_else(|e| panic!("{} rejected '{}': {}", template_id, value, e));
+        }
+
+        let field_name = "value".to_string();
+        let invalid_format = || ContractError::InvalidFormat {
+            field_name: field_name.clone(),
+        };
+        let out_of_range = || ContractError::FieldOutOfRange {
+            field_name: field_name.clone(),
+        };
+        let invalid = [
+            ("enum", "boat", ContractError::NotAnOption { field_name: field_name.clone() }),
+            ("address", "cosmos1notanaddress", ContractError::InvalidAddress { field_name: field_name.clone() }),
+            ("coin", "uatom", ContractError::InvalidCoin { field_name: field_name.clone() }),
+            ("coin", "10.5uatom", ContractError::InvalidCoin { field_name: field_name.clone() }),
+            (
+                "coin",
+                "10uosmo",
+                ContractError::DenomNotAllowed {
+                    field_name: field_name.clone(),
+                    denom: "uosmo".to_string(),
+                },
+            ),
+            ("percentage", "0.49", out_of_range()),
+            ("percentage", "25.01", out_of_range()),
+            (
+                "percentage",
+                "12.345",
+                ContractError::TooManyDecimalPlaces {
+                    field_name: field_name.clone(),
+                    precision: 2,
+                },
+            ),
+            ("email", "jane@localhost", invalid_format()),
+            ("email", "jane..doe@example.com", invalid_format()),
+            ("email", "@example.com", invalid_format()),
+            ("phone", "4155550100", invalid_format()),
+            ("phone", "+0155550100", invalid_format()),
+            ("phone", "+1415555010012345", invalid_format()),
+            ("country", "de", invalid_format()),
+            ("country", "XX", invalid_format()),
+            ("country", "DEU", invalid_format()),
+        ];
+        for (template_id, value, expected) in invalid {
+            let err = create_loan(&mut deps, template_id, vec![("value", value)]).unwrap_err();
+            assert_eq!(err, failed(expected), "{} accepted '{}'", template_id, value);
+        }
+    }
+
+    fn collateral_fields() -> Vec<(&'static str, FieldType)> {
+        let item = FieldType::Object {
+            is_editable: false,
+            required: true,
+            fields: HashMap::from([
+                (
+                    "kind".to_string(),
+                    FieldType::Enum {
+                        is_editable: false,
+                        required: true,
+                        default: None,
+                        options: vec!["car".to_string(), "house".to_string()],
+                    },
+                ),
+                ("value".to_string(), number(Some("1"), None)),
+            ]),
+        };
+        vec![
+            ("amount", number(None, None)),
+            (
+                "collateral",
+                FieldType::List {
+                    is_editable: false,
+                    required: true,
+                    item: Box::new(item),
+                    min_items: Some(1),
+                    max_items: Some(3),
+                },
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_nested_field_definitions_are_checked_by_path() {
+        let mut deps = setup();
+        let mut fields = collateral_fields();
+        fields[1].1 = FieldType::List {
+            is_editable: false,
+            required: true,
+            item: Box::new(FieldType::Object {
+                is_editable: false,
+                required: true,
+                fields: HashMap::from([("value".to_string(), number(Some("10"), Some("1")))]),
+            }),
+            min_items: Some(4),
+            max_items: Some(2),
+        };
+        let err = create_template(&mut deps, "t1", fields.clone(), vec![]).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidFieldRange {
+                field_name: "collateral".to_string()
+            }
+        );
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ValidateTemplate {
+                fields: fields.into_iter().map(|(name, field)| (name.to_string(), field)).collect(),
+                rules: vec![],
+            },
+        )
+        .unwrap();
+        let validation: TemplateValidation = from_json(res).unwrap();
+        assert_eq!(
+            validation.problems,
+            vec![
+                "Minimum of field 'collateral' is above its maximum".to_string(),
+                "Minimum of field 'collateral[].value' is above its maximum".to_string(),
+            ]
+        );
+
+        // Nesting stops at a fixed depth
+        let mut deep = number(None, None);
+        for _ in 0..=crate::validation::MAX_NESTING_DEPTH {
+            deep = FieldType::Object {
+                is_editable: false,
+                required: true,
+                fields: HashMap::from([("inner".to_string(), deep)]),
+            };
+        }
+        let err = create_template(&mut deps, "t1", vec![("deep", deep)], vec![]).unwrap_err();
+        assert!(matches!(err, ContractError::NestingTooDeep { .. }));
+
+        create_template(&mut deps, "t1", collateral_fields(), vec![]).unwrap();
+    }
+
+    #[test]
+    fn test_nested_values_are_validated_recursively() {
+        let mut deps = setup();
+        approved_template(&mut deps, "secured", collateral_fields(), vec![]);
+
+        create_loan_json(
+            &mut deps,
+            "secured",
+            r#"{"amount":"50000","collateral":[{"kind":"car","value":"20000"},{"kind":"house","value":"300000"}]}"#,
+        )
+        .unwrap();
+        let loan: crate::models::LoanData = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetLoanDetails {
+                    user_id: addr("creator").to_string(),
+                    loan_id: "1".to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let FieldValue::List(collateral) = &loan.values["collateral"] else {
+            panic!("collateral was not stored as a list");
+        };
+        assert_eq!(collateral.len(), 2);
+        assert_eq!(loan.values["amount"], FieldValue::from("50000"));
+
+        let field = |name: &str| name.to_string();
+        let invalid = [
+            (
+                r#"{"amount":"1","collateral":[{"kind":"car","value":"5"},{"kind":"car","value":"5"},{"kind":"boat","value":"5"}]}"#,
+                ContractError::NotAnOption { field_name: field("collateral[2].kind") },
+            ),
+            (
+                r#"{"amount":"1","collateral":[{"kind":"car","value":"0"}]}"#,
+                ContractError::FieldOutOfRange { field_name: field("collateral[0].value") },
+            ),
+            (
+                r#"{"amount":"1","collateral":[{"kind":"car"}]}"#,
+                ContractError::MissingField { field_name: field("collateral[0].value") },
+            ),
+            (
+                r#"{"amount":"1","collateral":[{"kind":"car","value":"5","worth":"5"}]}"#,
+                ContractError::UnknownField { field_name: field("collateral[0].worth") },
+            ),
+            (
+                r#"{"amount":"1","collateral":[]}"#,
+                ContractError::ItemCountOutOfRange { field_name: field("collateral") },
+            ),
+            (
+                r#"{"amount":"1","collateral":{"kind":"car","value":"5"}}"#,
+                ContractError::WrongValueType {
+                    field_name: field("collateral"),
+                    expected: "a list".to_string(),
+                },
+            ),
+            (
+                r#"{"amount":"1","collateral":["car"]}"#,
+                ContractError::WrongValueType {
+                    field_name: field("collateral[0]"),
+                    expected: "an object".to_string(),
+                },
+            ),
+            (
+                r#"{"amount":["1"],"collateral":[{"kind":"car","value":"5"}]}"#,
+                ContractError::WrongValueType {
+                    field_name: field("amount"),
+                    expected: "a single value".to_string(),
+                },
+            ),
+        ];
+        for (values, expected) in invalid {
+            let err = create_loan_json(&mut deps, "secured", values).unwrap_err();
+            assert_eq!(err, failed(expected), "accepted {}", values);
+        }
+    }
+
+    #[test]
+    fn test_optional_fields_and_defaults() {
+        let mut deps = setup();
+        let term = |default: Option<&str>| FieldType::Integer {
+            is_editable: false,
+            required: false,
+            default: default.map(str::to_string),
+            min_value: Some("6".to_string()),
+            max_value: Some("360".to_string()),
+        };
+        let note = FieldType::String {
+            is_editable: true,
+            required: false,
+            default: None,
+            format: None,
+            min_value: None,
+            max_value: None,
+        };
+
+        // Defaults must be valid values of their field
+        let err = create_template(&mut deps, "t1", vec![("term", term(Some("twelve")))], vec![]).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidNumberField {
+                field_name: "term".to_string()
+            }
+        );
+        let err = create_template(&mut deps, "t1", vec![("term", term(Some("3")))], vec![]).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::FieldOutOfRange {
+                field_name: "term".to_string()
+            }
+        );
+
+        approved_template(
+            &mut deps,
+            "t1",
+            vec![("amount", number(None, None)), ("term", term(Some("12"))), ("note", note)],
+            vec![],
+        );
+        create_loan(&mut deps, "t1", vec![("amount", "1000")]).unwrap();
+        create_loan(&mut deps, "t1", vec![("amount", "1000"), ("term", "24"), ("note", "first home")]).unwrap();
+
+        let values = |loan_id: &str| {
+            let res = query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::GetLoanDetails {
+                    user_id: addr("creator").to_string(),
+                    loan_id: loan_id.to_string(),
+                },
+            )
+            .unwrap();
+            from_json::<crate::models::LoanData>(res).unwrap().values
+        };
+        let filled = values("1");
+        assert_eq!(filled["term"], FieldValue::from("12"));
+        assert!(!filled.contains_key("note"));
+        assert_eq!(values("2")["term"], FieldValue::from("24"));
+
+        let err = create_loan(&mut deps, "t1", vec![("term", "24")]).unwrap_err();
+        assert_eq!(
+            err,
+            failed(ContractError::MissingField {
+                field_name: "amount".to_string()
+            })
+        );
+        let err = create_loan(&mut deps, "t1", vec![("amount", "1000"), ("colour", "red")]).unwrap_err();
+        assert_eq!(
+            err,
+            failed(ContractError::UnknownField {
+                field_name: "colour".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_string_lengths_and_date_ranges_are_enforced() {
+        let mut deps = setup();
+        let date = |min: Option<&str>, max: Option<&str>| FieldType::Date {
+            is_editable: false,
+            required: true,
+            default: None,
+            format: None,
+            min_value: min.map(str::to_string),
+            max_value: max.map(str::to_string),
+        };
+
+        // Relative bounds must be well formed, and are compared as of now
+        for bound in ["now-18", "now*2y", "now-18q", "now-100000y", "2024-02-30"] {
+            let err = create_template(&mut deps, "t1", vec![("born", date(Some(bound), None))], vec![]).unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::InvalidFieldBound {
+                    field_name: "born".to_string(),
+                    bound: bound.to_string(),
+                },
+            );
+        }
+        let err = create_template(&mut deps, "t1", vec![("born", date(Some("now-18y"), Some("now-100y")))], vec![]).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidFieldRange {
+                field_name: "born".to_string()
+            }
+        );
+
+        approved_template(
+            &mut deps,
+            "t1",
+            vec![
+                (
+                    "name",
+                    FieldType::String {
+                        is_editable: false,
+                        required: true,
+                        default: None,
+                        format: None,
+                        min_value: Some("2".to_string()),
+                        max_value: Some("5".to_string()),
+                    },
+                ),
+                ("born", date(Some("1900-01-01"), Some("now-18y"))),
+                ("starts", date(Some("now"), Some("now+6m"))),
+            ],
+            vec![],
+        );
+
+        // mock_env's block time is 2019-10-23T02:23:39Z
+        let valid = [
+            ("Jo", "2001-10-23T02:23:39Z", "1571797419"),
+            // Length counts characters, not bytes
+            ("Zoë Ü", "1900-01-01", "2020-04-23"),
+            ("Ana", "1900-01-01T00:00:00Z", "2019-10-24T00:00:00+02:00"),
+        ];
+        for (name, born, starts) in valid {
+            create_loan(&mut deps, "t1", vec![("name", name), ("born", born), ("starts", starts)])
+                .unwrap_or_else(|e| panic!("rejected {} {} {}: {}", name, born, starts, e));
+        }
+
+        let field = |name: &str| name.to_string();
+        let invalid = [
+            (("J", "1990-01-01", "2020-01-01"), ContractError::FieldOutOfRange { field_name: field("name") }),
+            (("Joanna", "1990-01-01", "2020-01-01"), ContractError::FieldOutOfRange { field_name: field("name") }),
+            (("Jo", "2001-10-23T02:23:40Z", "2020-01-01"), ContractError::FieldOutOfRange { field_name: field("born") }),
+            (("Jo", "1899-12-31", "2020-01-01"), ContractError::FieldOutOfRange { field_name: field("born") }),
+            (("Jo", "23/10/1990", "2020-01-01"), ContractError::InvalidFormat { field_name: field("born") }),
+            (("Jo", "1990-01-01", "2019-10-22"), ContractError::FieldOutOfRange { field_name: field("starts") }),
+            (("Jo", "1990-01-01", "2020-04-24"), ContractError::FieldOutOfRange { field_name: field("starts") }),
+        ];
+        for ((name, born, starts), expected) in invalid {
+            let err = create_loan(&mut deps, "t1", vec![("name", name), ("born", born), ("starts", starts)]).unwrap_err();
+            assert_eq!(err, failed(expected), "accepted {} {} {}", name, born, starts);
+        }
+
+        // A date format narrows the forms a date may take, but cannot admit other ones
+        let day_only = FieldType::Date {
+            is_editable: false,
+            required: true,
+            default: None,
+            format: Some(r"^\d{4}-\d{2}-\d{2}$".to_string()),
+            min_value: None,
+            max_value: None,
+        };
+        let slashed = FieldType::Date {
+            is_editable: false,
+            required: true,
+            default: None,
+            format: Some(r"^\d{2}/\d{2}/\d{4}$".to_string()),
+            min_value: None,
+            max_value: None,
+        };
+        approved_template(&mut deps, "t2", vec![("day", day_only), ("slashed", slashed)], vec![]);
+        let invalid_format = |field_name: &str| failed(ContractError::InvalidFormat { field_name: field(field_name) });
+        let err = create_loan(&mut deps, "t2", vec![("day", "1990-01-01T00:00:00Z"), ("slashed", "23/10/1990")]).unwrap_err();
+        let ContractError::ValidationFailed { errors } = err else {
+            panic!("expected a validation failure");
+        };
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, vec!["day", "slashed"]);
+        let err = create_loan(&mut deps, "t2", vec![("day", "1990-01-01"), ("slashed", "1990-01-01")]).unwrap_err();
+        assert_eq!(err, invalid_format("slashed"));
+    }
+
+    #[test]
+    fn test_every_failing_value_is_reported() {
+        let mut deps = setup();
+        let fields = vec![
+            ("amount", number(Some("1"), None)),
+            ("term", integer(None, None)),
+            ("country", FieldType::CountryCode { is_editable: false, required: true, default: None }),
+            (
+                "tags",
+                FieldType::List {
+                    is_editable: false,
+                    required: true,
+                    item: Box::new(FieldType::Boolean { is_editable: false, required: true, default: None }),
+                    min_items: None,
+                    max_items: Some(1),
+                },
+            ),
+        ];
+        approved_template(&mut deps, "t1", fields, vec![]);
+
+        let values = r#"{"amount":"0","country":"Germany","tags":["yes","true"],"colour":"red"}"#;
+        let expected = vec![
+            ("colour", FieldErrorCode::Unknown),
+            ("amount", FieldErrorCode::Range),
+            ("country", FieldErrorCode::Format),
+            ("tags", FieldErrorCode::Range),
+            ("tags[0]", FieldErrorCode::Format),
+            ("term", FieldErrorCode::Missing),
+        ];
+        let codes = |errors: &[FieldError]| {
+            errors
+                .iter()
+                .map(|e| (e.field.clone(), e.code))
+                .collect::<Vec<_>>()
+        };
+        let expected: Vec<_> = expected.into_iter().map(|(field, code)| (field.to_string(), code)).collect();
+
+        let ContractError::ValidationFailed { errors } = create_loan_json(&mut deps, "t1", values).unwrap_err() else {
+            panic!("expected every failing value");
+        };
+        assert_eq!(codes(&errors), expected);
+        assert_eq!(errors[1].message, "Field 'amount' is out of range");
+
+        // The query gives the same report for the same values
+        let query_values = |values: &str| {
+            let msg = QueryMsg::ValidateLoanRequest {
+                template_owner: addr("creator").to_string(),
+                template_id: "t1".to_string(),
+                values: from_json(values.as_bytes()).unwrap(),
+            };
+            from_json::<LoanValidation>(query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap()
+        };
+        let validation = query_values(values);
+        assert!(!validation.valid);
+        assert_eq!(validation.errors, errors);
+
+        let valid = r#"{"amount":"1","term":"12","country":"DE","tags":["true"]}"#;
+        assert_eq!(
+            query_values(valid),
+            LoanValidation {
+                valid: true,
+                errors: vec![],
+            }
+        );
+        // Nothing was stored by the failed creation or the queries
+        create_loan_json(&mut deps, "t1", valid).unwrap();
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetLoanDetails {
+                user_id: addr("creator").to_string(),
+                loan_id: "1".to_string(),
+            },
+        );
+        assert!(res.is_ok());
+
+        // A template that was never approved cannot be validated against, as it cannot be used
+        create_template(&mut deps, "draft", vec![("amount", number(None, None))], vec![]).unwrap();
+        let err = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ValidateLoanRequest {
+                template_owner: addr("creator").to_string(),
+                template_id: "draft".to_string(),
+                values: HashMap::new(),
+            },
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains(&ContractError::TemplateNotApproved { template_id: "draft".to_string() }.to_string()));
+    }
+
+    #[test]
+    fn test_stored_template_defects_are_not_reported_as_value_errors() {
+        let mut deps = setup();
+        let string = |format: Option<&str>, max: Option<&str>| FieldType::String {
+            is_editable: false,
+            required: true,
+            default: None,
+            format: format.map(str::to_string),
+            min_value: None,
+            max_value: max.map(str::to_string),
+        };
+        approved_template(&mut deps, "t1", vec![("name", string(None, None))], vec![]);
+
+        // Versions approved before formats and bounds were checked may still hold broken ones
+        let owner = addr("creator").to_string();
+        let cases = [
+            (
+                string(Some("[a-z"), None),
+                ContractError::InvalidFieldFormat {
+                    field_name: "name".to_string(),
+                    format: "[a-z".to_string(),
+                },
+            ),
+            (
+                string(None, Some("long")),
+                ContractError::InvalidFieldBound {
+                    field_name: "name".to_string(),
+                    bound: "long".to_string(),
+                },
+            ),
+        ];
+        for (field_type, defect) in cases {
+            let mut template = TEMPLATE_VERSIONS.load(&deps.storage, (&owner, "t1", 1)).unwrap();
+            template.fields.insert("name".to_string(), field_type);
+            TEMPLATE_VERSIONS.save(&mut deps.storage, (&owner, "t1", 1), &template).unwrap();
+
+            let err = create_loan(&mut deps, "t1", vec![("name", "anna")]).unwrap_err();
+            assert_eq!(err, defect);
+            let msg = QueryMsg::ValidateLoanRequest {
+                template_owner: owner.clone(),
+                template_id: "t1".to_string(),
+                values: HashMap::from([("name".to_string(), "anna".into())]),
+            };
+            let err = query(deps.as_ref(), mock_env(), msg).unwrap_err();
+            assert!(err.to_string().contains(&defect.to_string()));
+        }
+    }
+}